@@ -0,0 +1,495 @@
+#[cfg(feature = "std")]
+use std::error;
+use {
+    crate::{
+        commands::{AnyCommand, AnyResponse, Command, Hex, RawResponse},
+        edid, framing, responder, ErrorCode, I2C_ADDRESS_DDC_CI, I2C_ADDRESS_EDID, I2C_ADDRESS_EDID_SEGMENT,
+    },
+    alloc::vec::Vec,
+    core::{fmt, time::Duration},
+};
+
+/// The direction of an I2C transfer, from the host's point of view.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Direction {
+    /// The host reads from the device.
+    Read,
+    /// The host writes to the device.
+    Write,
+}
+
+/// A single I2C transfer, from a start condition up to the following repeated
+/// start or stop.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Transfer {
+    /// When the transfer started, if the capture has timestamps.
+    pub time: Option<Duration>,
+    /// The 7-bit device address.
+    pub address: u16,
+    /// The direction of the transfer.
+    pub direction: Direction,
+    /// The bytes transferred, excluding the address.
+    pub data: Vec<u8>,
+}
+
+/// The reasons a capture log may fail to parse.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ParseErrorKind {
+    /// A timestamp or sample number was malformed.
+    InvalidTime,
+    /// The direction was not `R` or `W`.
+    InvalidDirection,
+    /// The address was not a 7-bit hex value.
+    InvalidAddress,
+    /// A data byte was not a hex value.
+    InvalidByte,
+    /// A data byte appeared before any address.
+    MissingAddress,
+}
+
+/// An error parsing a capture log.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    /// The line the error occurred on, starting at 1.
+    pub line: usize,
+    /// What went wrong.
+    pub kind: ParseErrorKind,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let kind = match self.kind {
+            ParseErrorKind::InvalidTime => "invalid timestamp",
+            ParseErrorKind::InvalidDirection => "invalid direction",
+            ParseErrorKind::InvalidAddress => "invalid address",
+            ParseErrorKind::InvalidByte => "invalid data byte",
+            ParseErrorKind::MissingAddress => "data without an address",
+        };
+        write!(f, "line {}: {}", self.line, kind)
+    }
+}
+
+#[cfg(feature = "std")]
+impl error::Error for ParseError {}
+
+fn parse_address(s: &str) -> Option<u16> {
+    let s = s.strip_prefix("0x").unwrap_or(s);
+    u16::from_str_radix(s, 16).ok().filter(|&address| address < 0x80)
+}
+
+fn parse_byte(s: &str) -> Option<u8> {
+    let s = s.strip_prefix("0x").unwrap_or(s);
+    match s.len() {
+        1 | 2 => u8::from_str_radix(s, 16).ok(),
+        _ => None,
+    }
+}
+
+fn parse_seconds(s: &str) -> Option<Duration> {
+    s.parse::<f64>()
+        .ok()
+        .filter(|secs| secs.is_finite() && *secs >= 0.0)
+        .map(Duration::from_secs_f64)
+}
+
+/// Parses a log with one transfer per line, in the form
+/// `[seconds] R|W address bytes...` with all values in hex.
+///
+/// Blank lines and `#` comments are ignored.
+///
+/// ```
+/// use ddc::analyzer::{parse_hex, Direction};
+///
+/// let transfers = parse_hex(
+///     "\
+/// 0.000000 W 37 51 82 01 10 ac # host requests VCP 0x10
+/// 0.040000 R 37 6e 88 02 00 10 00 00 64 00 32 f2
+/// ",
+/// )
+/// .unwrap();
+/// assert_eq!(transfers[0].direction, Direction::Write);
+/// assert_eq!(transfers[1].address, 0x37);
+/// assert_eq!(transfers[1].data.len(), 11);
+/// ```
+pub fn parse_hex(log: &str) -> Result<Vec<Transfer>, ParseError> {
+    let mut transfers = Vec::new();
+    for (line, text) in log.lines().enumerate() {
+        let error = |kind| ParseError { line: line + 1, kind };
+        let text = text.split('#').next().unwrap_or_default();
+        let mut fields = text.split_whitespace().peekable();
+
+        let time = match fields.peek() {
+            None => continue,
+            Some(field) if field.starts_with(|c: char| c.is_ascii_digit()) => {
+                let time = parse_seconds(field).ok_or(error(ParseErrorKind::InvalidTime))?;
+                fields.next();
+                Some(time)
+            },
+            Some(_) => None,
+        };
+
+        let direction = match fields.next() {
+            Some("R" | "r") => Direction::Read,
+            Some("W" | "w") => Direction::Write,
+            _ => return Err(error(ParseErrorKind::InvalidDirection)),
+        };
+        let address = fields
+            .next()
+            .and_then(parse_address)
+            .ok_or(error(ParseErrorKind::InvalidAddress))?;
+        let data = fields
+            .map(|byte| parse_byte(byte).ok_or(error(ParseErrorKind::InvalidByte)))
+            .collect::<Result<_, _>>()?;
+
+        transfers.push(Transfer {
+            time,
+            address,
+            direction,
+            data,
+        });
+    }
+
+    Ok(transfers)
+}
+
+/// Parses the output of the sigrok `i2c` protocol decoder, as printed by
+/// `sigrok-cli -P i2c -A i2c` or exported from PulseView.
+///
+/// Lines such as `i2c-1: Address write: 37` and `i2c-1: Data read: 6E` are
+/// reassembled into transfers, and other annotations are ignored. If the
+/// lines are prefixed by their sample range (`sigrok-cli
+/// --protocol-decoder-samplenum`) and a `samplerate` is provided, the
+/// transfers are timestamped.
+pub fn parse_sigrok(log: &str, samplerate: Option<u64>) -> Result<Vec<Transfer>, ParseError> {
+    let mut transfers = Vec::new();
+    let mut current: Option<Transfer> = None;
+    for (line, text) in log.lines().enumerate() {
+        let error = |kind| ParseError { line: line + 1, kind };
+        let text = text.trim();
+
+        let (sample, text) = match text.split_once(' ') {
+            Some((range, rest)) if range.starts_with(|c: char| c.is_ascii_digit()) => {
+                let start = range.split('-').next().unwrap_or_default();
+                let sample = start.parse::<u64>().map_err(|_| error(ParseErrorKind::InvalidTime))?;
+                (Some(sample), rest)
+            },
+            _ => (None, text),
+        };
+
+        // PulseView quotes each form of the annotation, longest first
+        let annotation = match text.split('"').nth(1) {
+            Some(quoted) => quoted,
+            None => text
+                .split_once(": ")
+                .map(|(_, annotation)| annotation)
+                .unwrap_or_default(),
+        };
+
+        let (kind, value) = annotation.split_once(": ").unwrap_or((annotation, ""));
+        match kind {
+            "Start" | "Start repeat" | "Stop" => transfers.extend(current.take()),
+            "Address read" | "Address write" => {
+                transfers.extend(current.take());
+                current = Some(Transfer {
+                    time: sample.zip(samplerate).map(|(sample, rate)| {
+                        Duration::from_secs(sample / rate)
+                            + Duration::from_nanos((sample % rate) * 1_000_000_000 / rate)
+                    }),
+                    address: parse_address(value).ok_or(error(ParseErrorKind::InvalidAddress))?,
+                    direction: match kind {
+                        "Address read" => Direction::Read,
+                        _ => Direction::Write,
+                    },
+                    data: Vec::new(),
+                });
+            },
+            "Data read" | "Data write" => {
+                let byte = parse_byte(value).ok_or(error(ParseErrorKind::InvalidByte))?;
+                current
+                    .as_mut()
+                    .ok_or(error(ParseErrorKind::MissingAddress))?
+                    .data
+                    .push(byte);
+            },
+            _ => (),
+        }
+    }
+    transfers.extend(current);
+
+    Ok(transfers)
+}
+
+/// Parses a capture log, detecting whether it is sigrok decoder output or
+/// the format accepted by `parse_hex`.
+pub fn parse(log: &str, samplerate: Option<u64>) -> Result<Vec<Transfer>, ParseError> {
+    if log.contains("Address write") || log.contains("Address read") {
+        parse_sigrok(log, samplerate)
+    } else {
+        parse_hex(log)
+    }
+}
+
+/// The meaning of a transfer.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Event {
+    /// A DDC/CI request sent by the host.
+    Request(Result<AnyCommand, ErrorCode>),
+    /// A DDC/CI reply read by the host, or `None` for the null message.
+    Reply(Result<Option<AnyResponse>, ErrorCode>),
+    /// The host selected an E-DDC segment.
+    EdidSegment(u8),
+    /// The host set the EDID offset.
+    EdidOffset(u8),
+    /// The host read part of the EDID.
+    EdidRead {
+        /// The E-DDC segment being read.
+        segment: u8,
+        /// The offset within the segment.
+        offset: u8,
+        /// The EDID bytes.
+        data: Vec<u8>,
+    },
+    /// A transfer that isn't DDC/CI or EDID traffic.
+    Other,
+}
+
+impl fmt::Display for Event {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Event::Request(Ok(ref command)) => fmt::Display::fmt(command, f),
+            Event::Request(Err(ref e)) => write!(f, "invalid request: {}", e),
+            Event::Reply(Ok(Some(ref response))) => fmt::Display::fmt(response, f),
+            Event::Reply(Ok(None)) => f.write_str("null message"),
+            Event::Reply(Err(ref e)) => write!(f, "invalid reply: {}", e),
+            Event::EdidSegment(segment) => write!(f, "EDID segment {}", segment),
+            Event::EdidOffset(offset) => write!(f, "EDID offset {}", offset),
+            Event::EdidRead {
+                segment,
+                offset,
+                ref data,
+            } => write!(
+                f,
+                "EDID read of {} bytes from segment {} offset {}",
+                data.len(),
+                segment,
+                offset
+            ),
+            Event::Other => f.write_str("unrecognized transfer"),
+        }
+    }
+}
+
+/// A problem with the timing or sequencing of the traffic.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Warning {
+    /// The reply was read before the command's `DELAY_RESPONSE_MS` elapsed.
+    ResponseTooSoon {
+        /// The time since the request.
+        elapsed: Duration,
+        /// The time the display is allowed to take.
+        required: Duration,
+    },
+    /// The request was sent before the previous command's `DELAY_COMMAND_MS`
+    /// elapsed.
+    CommandTooSoon {
+        /// The time since the previous command completed.
+        elapsed: Duration,
+        /// The time the display is allowed to take.
+        required: Duration,
+    },
+    /// The reply to the previous request was never read.
+    MissingReply,
+    /// A reply was read without a preceding request.
+    UnexpectedReply,
+    /// An EDID block read in full has an invalid checksum.
+    EdidChecksum {
+        /// The index of the block within the EDID.
+        block: usize,
+    },
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Warning::ResponseTooSoon { elapsed, required } => write!(
+                f,
+                "reply read {:?} after the request, expected at least {:?}",
+                elapsed, required
+            ),
+            Warning::CommandTooSoon { elapsed, required } => write!(
+                f,
+                "sent {:?} after the previous command, expected at least {:?}",
+                elapsed, required
+            ),
+            Warning::MissingReply => f.write_str("the reply to the previous request was not read"),
+            Warning::UnexpectedReply => f.write_str("reply read without a request"),
+            Warning::EdidChecksum { block } => write!(f, "EDID block {} checksum mismatch", block),
+        }
+    }
+}
+
+/// A decoded transfer.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Entry {
+    /// The transfer itself.
+    pub transfer: Transfer,
+    /// What the transfer means.
+    pub event: Event,
+    /// Any problems found with the transfer.
+    pub warnings: Vec<Warning>,
+}
+
+impl fmt::Display for Entry {
+    /// Formats the entry as a line of a transcript, followed by any warnings.
+    ///
+    /// The alternate form `{:#}` also includes the raw bytes.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.transfer.time {
+            Some(time) => write!(f, "{:>12.6} ", time.as_secs_f64())?,
+            None => f.write_str("           - ")?,
+        }
+        let direction = match self.transfer.direction {
+            Direction::Read => 'R',
+            Direction::Write => 'W',
+        };
+        write!(f, "{:02x} {} {}", self.transfer.address, direction, self.event)?;
+        if f.alternate() {
+            write!(f, "\n{:18}{}", "", Hex(&self.transfer.data))?;
+        }
+        for warning in &self.warnings {
+            write!(f, "\n{:18}warning: {}", "", warning)?;
+        }
+        Ok(())
+    }
+}
+
+/// Reassembles DDC/CI and EDID transactions from a sequence of transfers.
+#[derive(Clone, Debug, Default)]
+pub struct Analyzer {
+    /// The outstanding request and when it was sent.
+    request: Option<(AnyCommand, Option<Duration>)>,
+    /// When the previous command completed, and the delay it requires.
+    ready: Option<(Duration, u64)>,
+    edid_segment: u8,
+    edid_offset: u8,
+}
+
+impl Analyzer {
+    /// Creates an analyzer that has not seen any traffic.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Decodes the next transfer of the capture.
+    pub fn analyze(&mut self, transfer: Transfer) -> Entry {
+        let mut warnings = Vec::new();
+        let event = match (transfer.address, transfer.direction) {
+            (I2C_ADDRESS_DDC_CI, Direction::Write) => self.request(&transfer, &mut warnings),
+            (I2C_ADDRESS_DDC_CI, Direction::Read) => self.reply(&transfer, &mut warnings),
+            (I2C_ADDRESS_EDID_SEGMENT, Direction::Write) if transfer.data.len() == 1 => {
+                self.edid_segment = transfer.data[0];
+                Event::EdidSegment(self.edid_segment)
+            },
+            (I2C_ADDRESS_EDID, Direction::Write) if transfer.data.len() == 1 => {
+                self.edid_offset = transfer.data[0];
+                Event::EdidOffset(self.edid_offset)
+            },
+            (I2C_ADDRESS_EDID, Direction::Read) => self.edid_read(&transfer, &mut warnings),
+            _ => Event::Other,
+        };
+
+        Entry {
+            transfer,
+            event,
+            warnings,
+        }
+    }
+
+    fn request(&mut self, transfer: &Transfer, warnings: &mut Vec<Warning>) -> Event {
+        if self.request.take().is_some() {
+            warnings.push(Warning::MissingReply);
+        }
+
+        if let (Some(time), Some((ready, delay))) = (transfer.time, self.ready.take()) {
+            let required = Duration::from_millis(delay);
+            let elapsed = time.saturating_sub(ready);
+            if elapsed < required {
+                warnings.push(Warning::CommandTooSoon { elapsed, required });
+            }
+        }
+
+        let command = responder::decode_request(&transfer.data).and_then(AnyCommand::decode_request);
+        match command {
+            Ok(ref command) if command.reply_len() > 0 => self.request = Some((command.clone(), transfer.time)),
+            Ok(ref command) => self.ready = transfer.time.map(|time| (time, command.delay_command_ms())),
+            Err(_) => (),
+        }
+
+        Event::Request(command)
+    }
+
+    fn reply(&mut self, transfer: &Transfer, warnings: &mut Vec<Warning>) -> Event {
+        let len = match framing::REPLY.payload_len(&transfer.data) {
+            Ok(len) => len,
+            Err(e) => return Event::Reply(Err(e)),
+        };
+        let payload = &transfer.data[2..2 + len];
+
+        let command = match self.request.take() {
+            Some((command, sent)) => {
+                if let (Some(time), Some(sent)) = (transfer.time, sent) {
+                    let required = Duration::from_millis(command.delay_response_ms());
+                    let elapsed = time.saturating_sub(sent);
+                    if elapsed < required {
+                        warnings.push(Warning::ResponseTooSoon { elapsed, required });
+                    }
+                    self.ready = Some((time, command.delay_command_ms()));
+                }
+                command
+            },
+            None => {
+                warnings.push(Warning::UnexpectedReply);
+                return Event::Reply(Ok(Some(AnyResponse::Raw(RawResponse(payload.to_vec())))))
+            },
+        };
+
+        Event::Reply(match len {
+            0 => Ok(None),
+            _ => command.decode(payload).map(Some),
+        })
+    }
+
+    fn edid_read(&mut self, transfer: &Transfer, warnings: &mut Vec<Warning>) -> Event {
+        let (segment, offset) = (self.edid_segment, self.edid_offset);
+        let start = segment as usize * 0x100 + offset as usize;
+
+        // check every block that the read covers in full
+        let first = start.div_ceil(edid::BLOCK_LEN);
+        let end = (start + transfer.data.len()) / edid::BLOCK_LEN;
+        for block in first..end {
+            let pos = block * edid::BLOCK_LEN - start;
+            if !edid::checksum_valid(&transfer.data[pos..pos + edid::BLOCK_LEN]) {
+                warnings.push(Warning::EdidChecksum { block });
+            }
+        }
+
+        // the segment pointer only applies to the read that follows it
+        self.edid_segment = 0;
+        self.edid_offset = offset.wrapping_add(transfer.data.len() as u8);
+
+        Event::EdidRead {
+            segment,
+            offset,
+            data: transfer.data.clone(),
+        }
+    }
+}
+
+/// Decodes a whole capture.
+pub fn analyze<I: IntoIterator<Item = Transfer>>(transfers: I) -> Vec<Entry> {
+    let mut analyzer = Analyzer::new();
+    transfers
+        .into_iter()
+        .map(|transfer| analyzer.analyze(transfer))
+        .collect()
+}
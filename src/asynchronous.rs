@@ -0,0 +1,554 @@
+#[cfg(feature = "alloc")]
+use {crate::caps, alloc::vec::Vec};
+use {
+    crate::{
+        commands, complete_command,
+        features::Feature,
+        fragments::{AsyncFragments, SendAsyncFragments},
+        Command, DdcHost, Delay, ErrorCode, FeatureCode, TimingMessage, VcpValue,
+    },
+    core::{future::Future, time::Duration},
+};
+
+/// A source of asynchronous delays.
+pub trait AsyncTimer {
+    /// Waits for the specified duration.
+    fn sleep(&mut self, duration: Duration) -> impl Future<Output = ()>;
+}
+
+impl<T: AsyncTimer + ?Sized> AsyncTimer for &mut T {
+    fn sleep(&mut self, duration: Duration) -> impl Future<Output = ()> {
+        (**self).sleep(duration)
+    }
+}
+
+/// A `Send` variant of `AsyncTimer`, for use by `Send` implementations of the
+/// DDC traits.
+pub trait SendAsyncTimer {
+    /// Waits for the specified duration.
+    fn sleep(&mut self, duration: Duration) -> impl Future<Output = ()> + Send;
+}
+
+impl<T: SendAsyncTimer + ?Sized> SendAsyncTimer for &mut T {
+    fn sleep(&mut self, duration: Duration) -> impl Future<Output = ()> + Send {
+        (**self).sleep(duration)
+    }
+}
+
+/// An `AsyncTimer` backed by the tokio runtime.
+#[cfg(feature = "tokio")]
+#[derive(Copy, Clone, Debug, Default)]
+pub struct TokioTimer;
+
+#[cfg(feature = "tokio")]
+impl SendAsyncTimer for TokioTimer {
+    async fn sleep(&mut self, duration: Duration) {
+        tokio::time::sleep(duration).await
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl AsyncTimer for TokioTimer {
+    fn sleep(&mut self, duration: Duration) -> impl Future<Output = ()> {
+        SendAsyncTimer::sleep(self, duration)
+    }
+}
+
+/// An `AsyncTimer` backed by `async-io`.
+#[cfg(feature = "async-io")]
+#[derive(Copy, Clone, Debug, Default)]
+pub struct AsyncIoTimer;
+
+#[cfg(feature = "async-io")]
+impl SendAsyncTimer for AsyncIoTimer {
+    async fn sleep(&mut self, duration: Duration) {
+        async_io::Timer::after(duration).await;
+    }
+}
+
+#[cfg(feature = "async-io")]
+impl AsyncTimer for AsyncIoTimer {
+    fn sleep(&mut self, duration: Duration) -> impl Future<Output = ()> {
+        SendAsyncTimer::sleep(self, duration)
+    }
+}
+
+/// An `AsyncTimer` backed by `embassy-time`.
+#[cfg(feature = "embassy-time")]
+#[derive(Copy, Clone, Debug, Default)]
+pub struct EmbassyTimer;
+
+#[cfg(feature = "embassy-time")]
+impl SendAsyncTimer for EmbassyTimer {
+    async fn sleep(&mut self, duration: Duration) {
+        let micros = duration.as_micros().try_into().unwrap_or(u64::MAX);
+        embassy_time::Timer::after(embassy_time::Duration::from_micros(micros)).await
+    }
+}
+
+#[cfg(feature = "embassy-time")]
+impl AsyncTimer for EmbassyTimer {
+    fn sleep(&mut self, duration: Duration) -> impl Future<Output = ()> {
+        SendAsyncTimer::sleep(self, duration)
+    }
+}
+
+/// A trait that allows asynchronously retrieving Extended Display
+/// Identification Data (EDID) from a device.
+pub trait AsyncEdid {
+    /// An error that can occur when reading the EDID from a device.
+    type EdidError;
+
+    /// Read up to 256 bytes of the monitor's EDID.
+    fn read_edid(&mut self, offset: u8, data: &mut [u8]) -> impl Future<Output = Result<usize, Self::EdidError>>;
+}
+
+/// E-DDC allows reading extensions of Enhanced EDID.
+pub trait AsyncEddc: AsyncEdid {
+    /// Read part of the EDID using the segments added in the Enhanced Display
+    /// Data Channel (E-DDC) protocol.
+    fn read_eddc_edid(
+        &mut self,
+        segment: u8,
+        offset: u8,
+        data: &mut [u8],
+    ) -> impl Future<Output = Result<usize, Self::EdidError>>;
+}
+
+/// A `Send` variant of `AsyncEdid`.
+///
+/// Implementing it also implements `AsyncEdid`.
+pub trait SendAsyncEdid {
+    /// An error that can occur when reading the EDID from a device.
+    type EdidError;
+
+    /// Read up to 256 bytes of the monitor's EDID.
+    fn read_edid(&mut self, offset: u8, data: &mut [u8])
+        -> impl Future<Output = Result<usize, Self::EdidError>> + Send;
+}
+
+/// A `Send` variant of `AsyncEddc`.
+///
+/// Implementing it also implements `AsyncEddc`.
+pub trait SendAsyncEddc: SendAsyncEdid {
+    /// Read part of the EDID using the segments added in the Enhanced Display
+    /// Data Channel (E-DDC) protocol.
+    fn read_eddc_edid(
+        &mut self,
+        segment: u8,
+        offset: u8,
+        data: &mut [u8],
+    ) -> impl Future<Output = Result<usize, Self::EdidError>> + Send;
+}
+
+impl<D: SendAsyncEdid + ?Sized> AsyncEdid for D {
+    type EdidError = D::EdidError;
+
+    fn read_edid(&mut self, offset: u8, data: &mut [u8]) -> impl Future<Output = Result<usize, Self::EdidError>> {
+        SendAsyncEdid::read_edid(self, offset, data)
+    }
+}
+
+impl<D: SendAsyncEddc + ?Sized> AsyncEddc for D {
+    fn read_eddc_edid(
+        &mut self,
+        segment: u8,
+        offset: u8,
+        data: &mut [u8],
+    ) -> impl Future<Output = Result<usize, Self::EdidError>> {
+        SendAsyncEddc::read_eddc_edid(self, segment, offset, data)
+    }
+}
+
+/// Allows the asynchronous execution of arbitrary low level DDC commands.
+pub trait AsyncDdcCommandRaw: DdcHost {
+    /// Executes a raw DDC/CI command.
+    ///
+    /// See `DdcCommandRaw::execute_raw`. Implementations should wait out any
+    /// pending `Delay` asynchronously before writing to the device.
+    fn execute_raw<'a>(
+        &mut self,
+        data: &[u8],
+        out: &'a mut [u8],
+        response_delay: Duration,
+    ) -> impl Future<Output = Result<&'a mut [u8], Self::Error>>;
+}
+
+/// A `Send` variant of `AsyncDdcCommandRaw`.
+///
+/// Implementing it also implements `AsyncDdcCommandRaw`, and along with
+/// `AsyncDdcCommandRawMarker` implements both `AsyncDdcCommand` and
+/// `SendAsyncDdcCommand`.
+pub trait SendAsyncDdcCommandRaw: DdcHost {
+    /// Executes a raw DDC/CI command.
+    ///
+    /// See `AsyncDdcCommandRaw::execute_raw`.
+    fn execute_raw<'a>(
+        &mut self,
+        data: &[u8],
+        out: &'a mut [u8],
+        response_delay: Duration,
+    ) -> impl Future<Output = Result<&'a mut [u8], Self::Error>> + Send;
+}
+
+impl<D: SendAsyncDdcCommandRaw + ?Sized> AsyncDdcCommandRaw for D {
+    fn execute_raw<'a>(
+        &mut self,
+        data: &[u8],
+        out: &'a mut [u8],
+        response_delay: Duration,
+    ) -> impl Future<Output = Result<&'a mut [u8], Self::Error>> {
+        SendAsyncDdcCommandRaw::execute_raw(self, data, out, response_delay)
+    }
+}
+
+/// Using this marker trait will automatically implement the `AsyncDdcCommand`
+/// trait, and the `SendAsyncDdcCommand` trait for `Send` implementations of
+/// `SendAsyncDdcCommandRaw`.
+pub trait AsyncDdcCommandRawMarker: AsyncDdcCommandRaw
+where
+    Self::Error: From<ErrorCode>,
+{
+    /// Sets an internal `Delay` that must expire before the next command is
+    /// attempted.
+    fn set_sleep_delay(&mut self, delay: Delay);
+}
+
+/// A (slightly) higher level interface to `AsyncDdcCommandRaw`.
+pub trait AsyncDdcCommand: DdcHost {
+    /// Execute a DDC/CI command. See the `commands` module for all available
+    /// commands. The return type is dependent on the executed command.
+    fn execute<C: Command>(&mut self, command: C) -> impl Future<Output = Result<C::Ok, Self::Error>>;
+}
+
+/// A `Send` variant of `AsyncDdcCommand`, for generic code that spawns
+/// commands on a multithreaded executor.
+pub trait SendAsyncDdcCommand: DdcHost {
+    /// Execute a DDC/CI command. See the `commands` module for all available
+    /// commands. The return type is dependent on the executed command.
+    fn execute<C: Command + Send>(&mut self, command: C) -> impl Future<Output = Result<C::Ok, Self::Error>> + Send;
+}
+
+/// Using this marker trait will automatically implement the `AsyncDdc` and
+/// `AsyncDdcTable` traits, and the `SendAsyncDdc` and `SendAsyncDdcTable`
+/// traits for `Send` implementations of `SendAsyncDdcCommand`.
+pub trait AsyncDdcCommandMarker: AsyncDdcCommand
+where
+    Self::Error: From<ErrorCode>,
+{
+}
+
+/// A high level asynchronous interface to DDC commands.
+pub trait AsyncDdc: DdcHost {
+    /// Retrieve the capability string from the device.
+    ///
+    /// This executes multiple `CapabilitiesRequest` commands to construct the entire string.
+    #[cfg(feature = "alloc")]
+    fn capabilities_string(&mut self) -> impl Future<Output = Result<Vec<u8>, Self::Error>>;
+
+    /// Retrieve the capability string from the device into `buf` without
+    /// allocating, returning its length.
+    ///
+    /// Fails with `ErrorCode::BufferTooSmall` if the string does not fit.
+    fn capabilities_string_into(&mut self, buf: &mut [u8]) -> impl Future<Output = Result<usize, Self::Error>>
+    where
+        Self: AsyncDdcCommand,
+        Self::Error: From<ErrorCode>,
+    {
+        async { AsyncFragments::capabilities(self).read_into(buf).await }
+    }
+
+    /// Retrieve and parse the capability string from the device.
+    #[cfg(feature = "alloc")]
+    fn capabilities(&mut self) -> impl Future<Output = Result<caps::Capabilities, Self::Error>>
+    where
+        Self::Error: From<ErrorCode>,
+    {
+        async {
+            self.capabilities_string()
+                .await
+                .and_then(|caps| caps::Capabilities::parse(&caps).map_err(|e| ErrorCode::InvalidCapabilities(e).into()))
+        }
+    }
+
+    /// Gets the current value of an MCCS VCP feature.
+    fn get_vcp_feature(&mut self, code: FeatureCode) -> impl Future<Output = Result<VcpValue, Self::Error>>;
+
+    /// Sets a VCP feature to the specified value.
+    fn set_vcp_feature(&mut self, code: FeatureCode, value: u16) -> impl Future<Output = Result<(), Self::Error>>;
+
+    /// Instructs the device to save its current settings.
+    fn save_current_settings(&mut self) -> impl Future<Output = Result<(), Self::Error>>;
+
+    /// Retrieves a timing report from the device.
+    fn get_timing_report(&mut self) -> impl Future<Output = Result<TimingMessage, Self::Error>>;
+
+    /// Requests the device's identification data.
+    fn identification(&mut self) -> impl Future<Output = Result<commands::IdentificationReply, Self::Error>>;
+
+    /// Instructs the device to run its self-test, returning the result.
+    fn self_test(&mut self) -> impl Future<Output = Result<commands::SelfTestReply, Self::Error>>;
+
+    /// Enables or disables application reports from the device.
+    fn enable_application_report(&mut self, enable: bool) -> impl Future<Output = Result<(), Self::Error>>;
+
+    /// Gets the current value of a typed VCP feature.
+    fn get_feature<F: Feature>(&mut self) -> impl Future<Output = Result<F::Value, Self::Error>>
+    where
+        Self: Sized,
+    {
+        async { self.get_vcp_feature(F::CODE).await.map(|value| F::decode(&value)) }
+    }
+
+    /// Sets a typed VCP feature to the specified value.
+    fn set_feature<F: Feature>(&mut self, setting: F::Setting) -> impl Future<Output = Result<(), Self::Error>>
+    where
+        Self: Sized,
+    {
+        self.set_vcp_feature(F::CODE, F::encode(setting))
+    }
+}
+
+/// Table commands can asynchronously read and write arbitrary binary data to
+/// a VCP feature.
+pub trait AsyncDdcTable: DdcHost {
+    /// Read a table value from the device.
+    #[cfg(feature = "alloc")]
+    fn table_read(&mut self, code: FeatureCode) -> impl Future<Output = Result<Vec<u8>, Self::Error>>;
+
+    /// Read a table value from the device into `buf` without allocating,
+    /// returning its length.
+    ///
+    /// Fails with `ErrorCode::BufferTooSmall` if the value does not fit.
+    fn table_read_into(&mut self, code: FeatureCode, buf: &mut [u8]) -> impl Future<Output = Result<usize, Self::Error>>
+    where
+        Self: AsyncDdcCommand,
+        Self::Error: From<ErrorCode>,
+    {
+        async move { AsyncFragments::table(self, code).read_into(buf).await }
+    }
+
+    /// Write a table value to the device.
+    fn table_write(
+        &mut self,
+        code: FeatureCode,
+        offset: u16,
+        value: &[u8],
+    ) -> impl Future<Output = Result<(), Self::Error>>;
+}
+
+/// A `Send` variant of `AsyncDdc`.
+pub trait SendAsyncDdc: DdcHost {
+    /// Retrieve the capability string from the device.
+    ///
+    /// This executes multiple `CapabilitiesRequest` commands to construct the entire string.
+    #[cfg(feature = "alloc")]
+    fn capabilities_string(&mut self) -> impl Future<Output = Result<Vec<u8>, Self::Error>> + Send;
+
+    /// Retrieve the capability string from the device into `buf` without
+    /// allocating, returning its length.
+    ///
+    /// Fails with `ErrorCode::BufferTooSmall` if the string does not fit.
+    fn capabilities_string_into(&mut self, buf: &mut [u8]) -> impl Future<Output = Result<usize, Self::Error>> + Send
+    where
+        Self: SendAsyncDdcCommand + Send,
+        Self::Error: From<ErrorCode>,
+    {
+        async { SendAsyncFragments::capabilities(self).read_into(buf).await }
+    }
+
+    /// Retrieve and parse the capability string from the device.
+    #[cfg(feature = "alloc")]
+    fn capabilities(&mut self) -> impl Future<Output = Result<caps::Capabilities, Self::Error>> + Send
+    where
+        Self::Error: From<ErrorCode>,
+    {
+        let caps = self.capabilities_string();
+        async move {
+            caps.await
+                .and_then(|caps| caps::Capabilities::parse(&caps).map_err(|e| ErrorCode::InvalidCapabilities(e).into()))
+        }
+    }
+
+    /// Gets the current value of an MCCS VCP feature.
+    fn get_vcp_feature(&mut self, code: FeatureCode) -> impl Future<Output = Result<VcpValue, Self::Error>> + Send;
+
+    /// Sets a VCP feature to the specified value.
+    fn set_vcp_feature(
+        &mut self,
+        code: FeatureCode,
+        value: u16,
+    ) -> impl Future<Output = Result<(), Self::Error>> + Send;
+
+    /// Instructs the device to save its current settings.
+    fn save_current_settings(&mut self) -> impl Future<Output = Result<(), Self::Error>> + Send;
+
+    /// Retrieves a timing report from the device.
+    fn get_timing_report(&mut self) -> impl Future<Output = Result<TimingMessage, Self::Error>> + Send;
+
+    /// Requests the device's identification data.
+    fn identification(&mut self) -> impl Future<Output = Result<commands::IdentificationReply, Self::Error>> + Send;
+
+    /// Instructs the device to run its self-test, returning the result.
+    fn self_test(&mut self) -> impl Future<Output = Result<commands::SelfTestReply, Self::Error>> + Send;
+
+    /// Enables or disables application reports from the device.
+    fn enable_application_report(&mut self, enable: bool) -> impl Future<Output = Result<(), Self::Error>> + Send;
+
+    /// Gets the current value of a typed VCP feature.
+    fn get_feature<F: Feature>(&mut self) -> impl Future<Output = Result<F::Value, Self::Error>> + Send
+    where
+        Self: Sized,
+    {
+        let value = self.get_vcp_feature(F::CODE);
+        async move { value.await.map(|value| F::decode(&value)) }
+    }
+
+    /// Sets a typed VCP feature to the specified value.
+    fn set_feature<F: Feature>(&mut self, setting: F::Setting) -> impl Future<Output = Result<(), Self::Error>> + Send
+    where
+        Self: Sized,
+    {
+        self.set_vcp_feature(F::CODE, F::encode(setting))
+    }
+}
+
+/// A `Send` variant of `AsyncDdcTable`.
+pub trait SendAsyncDdcTable: DdcHost {
+    /// Read a table value from the device.
+    #[cfg(feature = "alloc")]
+    fn table_read(&mut self, code: FeatureCode) -> impl Future<Output = Result<Vec<u8>, Self::Error>> + Send;
+
+    /// Read a table value from the device into `buf` without allocating,
+    /// returning its length.
+    ///
+    /// Fails with `ErrorCode::BufferTooSmall` if the value does not fit.
+    fn table_read_into(
+        &mut self,
+        code: FeatureCode,
+        buf: &mut [u8],
+    ) -> impl Future<Output = Result<usize, Self::Error>> + Send
+    where
+        Self: SendAsyncDdcCommand + Send,
+        Self::Error: From<ErrorCode>,
+    {
+        async move { SendAsyncFragments::table(self, code).read_into(buf).await }
+    }
+
+    /// Write a table value to the device.
+    fn table_write(
+        &mut self,
+        code: FeatureCode,
+        offset: u16,
+        value: &[u8],
+    ) -> impl Future<Output = Result<(), Self::Error>> + Send;
+}
+
+impl Delay {
+    /// Asynchronously waits out the remaining time in this delay using the
+    /// provided timer.
+    pub async fn sleep_async<T: AsyncTimer + ?Sized>(&mut self, timer: &mut T) {
+        let remaining = self.remaining();
+        *self = Default::default();
+        if remaining > Duration::default() {
+            timer.sleep(remaining).await;
+        }
+    }
+}
+
+/// Implements the high level traits on top of `execute`, for both the local
+/// and `Send` variants.
+macro_rules! ddc_impls {
+    ($ddc:ident, $table:ident, $command:ident, $fragments:ident, $($bounds:tt)*) => {
+        impl<D: $command + AsyncDdcCommandMarker $($bounds)*> $ddc for D
+        where
+            D::Error: From<ErrorCode>,
+        {
+            #[cfg(feature = "alloc")]
+            async fn capabilities_string(&mut self) -> Result<Vec<u8>, Self::Error> {
+                let mut string = Vec::new();
+                $fragments::capabilities(self).read_to_end(&mut string).await?;
+                Ok(string)
+            }
+
+            async fn get_vcp_feature(&mut self, code: FeatureCode) -> Result<VcpValue, Self::Error> {
+                $command::execute(self, commands::GetVcpFeature::new(code)).await
+            }
+
+            async fn set_vcp_feature(&mut self, code: FeatureCode, value: u16) -> Result<(), Self::Error> {
+                $command::execute(self, commands::SetVcpFeature::new(code, value)).await
+            }
+
+            async fn save_current_settings(&mut self) -> Result<(), Self::Error> {
+                $command::execute(self, commands::SaveCurrentSettings).await
+            }
+
+            async fn get_timing_report(&mut self) -> Result<TimingMessage, Self::Error> {
+                $command::execute(self, commands::GetTimingReport).await
+            }
+
+            async fn identification(&mut self) -> Result<commands::IdentificationReply, Self::Error> {
+                $command::execute(self, commands::IdentificationRequest).await
+            }
+
+            async fn self_test(&mut self) -> Result<commands::SelfTestReply, Self::Error> {
+                $command::execute(self, commands::SelfTestRequest).await
+            }
+
+            async fn enable_application_report(&mut self, enable: bool) -> Result<(), Self::Error> {
+                $command::execute(self, commands::EnableApplicationReport::new(enable)).await
+            }
+        }
+
+        impl<D: $command + AsyncDdcCommandMarker $($bounds)*> $table for D
+        where
+            D::Error: From<ErrorCode>,
+        {
+            #[cfg(feature = "alloc")]
+            async fn table_read(&mut self, code: FeatureCode) -> Result<Vec<u8>, Self::Error> {
+                let mut value = Vec::new();
+                $fragments::table(self, code).read_to_end(&mut value).await?;
+                Ok(value)
+            }
+
+            async fn table_write(&mut self, code: FeatureCode, mut offset: u16, value: &[u8]) -> Result<(), Self::Error> {
+                for chunk in value.chunks(32) {
+                    $command::execute(self, commands::TableWrite::new(code, offset, chunk)).await?;
+                    offset += chunk.len() as u16;
+                }
+
+                Ok(())
+            }
+        }
+    };
+}
+
+ddc_impls!(AsyncDdc, AsyncDdcTable, AsyncDdcCommand, AsyncFragments,);
+ddc_impls!(SendAsyncDdc, SendAsyncDdcTable, SendAsyncDdcCommand, SendAsyncFragments, + Send);
+
+/// Implements `execute` on top of `execute_raw`, for both the local and
+/// `Send` variants.
+macro_rules! command_impl {
+    ($command:ident, $raw:ident, $($send:tt)*) => {
+        impl<D: $raw + AsyncDdcCommandRawMarker $($send)*> $command for D
+        where
+            D::Error: From<ErrorCode>,
+        {
+            async fn execute<C: Command $($send)*>(&mut self, command: C) -> Result<C::Ok, Self::Error> {
+                let (mut data, len) = commands::encode_command(&command)?;
+
+                let mut out = commands::reply_buffer(&command);
+                let out = commands::reply_slice(out.as_mut(), command.reply_len())?;
+                let delay = Duration::from_millis(command.delay_response_ms());
+                let res = $raw::execute_raw(self, &data.as_mut()[..len], out, delay).await;
+                let (delay, res) = complete_command(&command, res);
+                self.set_sleep_delay(delay);
+
+                res
+            }
+        }
+    };
+}
+
+command_impl!(AsyncDdcCommand, AsyncDdcCommandRaw,);
+command_impl!(SendAsyncDdcCommand, SendAsyncDdcCommandRaw, + Send);
@@ -0,0 +1,73 @@
+//! Prints a decoded transcript of a captured DDC/CI and EDID I2C log.
+
+use {
+    ddc::analyzer,
+    std::{
+        env,
+        io::{self, Read},
+        process,
+    },
+};
+
+const USAGE: &str = "usage: ddc-analyze [--hex | --sigrok] [--samplerate HZ] [--raw] [FILE]";
+
+enum Format {
+    Detect,
+    Hex,
+    Sigrok,
+}
+
+fn fail(message: &str) -> ! {
+    eprintln!("ddc-analyze: {}", message);
+    process::exit(1)
+}
+
+fn main() {
+    let mut format = Format::Detect;
+    let mut samplerate = None;
+    let mut raw = false;
+    let mut path = None;
+
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match &arg[..] {
+            "--hex" => format = Format::Hex,
+            "--sigrok" => format = Format::Sigrok,
+            "--raw" => raw = true,
+            "--samplerate" => match args.next().and_then(|rate| rate.parse().ok()) {
+                Some(rate) if rate > 0 => samplerate = Some(rate),
+                _ => fail("--samplerate requires a rate in Hz"),
+            },
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                return
+            },
+            _ if path.is_none() && (arg == "-" || !arg.starts_with('-')) => path = Some(arg),
+            _ => fail(USAGE),
+        }
+    }
+
+    let mut log = String::new();
+    let res = match path.as_deref() {
+        None | Some("-") => io::stdin().read_to_string(&mut log).map(drop),
+        Some(path) => std::fs::read_to_string(path).map(|data| log = data),
+    };
+    if let Err(e) = res {
+        fail(&e.to_string())
+    }
+
+    let transfers = match format {
+        Format::Detect => analyzer::parse(&log, samplerate),
+        Format::Hex => analyzer::parse_hex(&log),
+        Format::Sigrok => analyzer::parse_sigrok(&log, samplerate),
+    }
+    .unwrap_or_else(|e| fail(&e.to_string()));
+
+    for entry in analyzer::analyze(transfers) {
+        if raw {
+            println!("{:#}", entry);
+        } else {
+            println!("{}", entry);
+        }
+    }
+}
@@ -0,0 +1,378 @@
+#[cfg(feature = "std")]
+use std::error;
+use {
+    crate::FeatureCode,
+    alloc::{
+        borrow::ToOwned,
+        collections::BTreeMap,
+        string::{String, ToString},
+        vec::Vec,
+    },
+    core::{fmt, str},
+};
+
+/// A parsed MCCS capabilities string.
+///
+/// Parsing is lenient about the mistakes commonly found in real monitors:
+/// missing or unbalanced parentheses, NUL padding, missing whitespace between
+/// hex codes, and entries that aren't understood are all accepted.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Capabilities {
+    /// The protocol class, usually `monitor`.
+    pub protocol: Option<String>,
+    /// The display type, such as `lcd` or `crt`.
+    pub ty: Option<String>,
+    /// The model name of the display.
+    pub model: Option<String>,
+    /// The supported DDC/CI command opcodes.
+    pub commands: Vec<u8>,
+    /// Whether the display is Microsoft WHQL certified.
+    pub ms_whql: Option<u8>,
+    /// The version of the MCCS specification supported.
+    pub mccs_version: Option<Version>,
+    /// The supported VCP features.
+    pub vcp_features: BTreeMap<FeatureCode, VcpDescriptor>,
+    /// The EDID embedded in the capabilities string.
+    pub edid: Option<Vec<u8>>,
+    /// The VDIF blocks embedded in the capabilities string.
+    pub vdif: Vec<Vec<u8>>,
+    /// The window sections, used by displays that support picture-in-picture.
+    pub windows: Vec<Window>,
+    /// Any entries that were not understood.
+    pub unknown: Vec<Entry>,
+}
+
+/// An MCCS specification version.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Version {
+    /// The major version number.
+    pub major: u8,
+    /// The minor version number.
+    pub minor: u8,
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}.{}", self.major, self.minor)
+    }
+}
+
+/// A VCP feature listed in the capabilities string.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct VcpDescriptor {
+    /// The name of the feature, if provided by a `vcpname` entry.
+    pub name: Option<String>,
+    /// The allowed values of a non-continuous feature.
+    pub values: Vec<u8>,
+}
+
+/// A `window` section of the capabilities string.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Window {
+    /// The window number.
+    pub index: u8,
+    /// The entries that describe the window.
+    pub entries: Vec<Entry>,
+}
+
+/// A raw capabilities entry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    /// The name of the entry.
+    pub name: String,
+    /// The contents of the entry.
+    pub value: EntryValue,
+}
+
+/// The contents of a raw capabilities entry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EntryValue {
+    /// The text between the entry's parentheses.
+    String(String),
+    /// The data of a `bin(len(data))` entry.
+    Binary(Vec<u8>),
+}
+
+/// An error that can occur when parsing a capabilities string.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct CapabilitiesError {
+    /// The byte offset within the capabilities string.
+    pub position: usize,
+    /// The type of error.
+    pub kind: CapabilitiesErrorKind,
+}
+
+/// The type of a `CapabilitiesError`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum CapabilitiesErrorKind {
+    /// The string ended in the middle of an entry.
+    UnexpectedEnd,
+    /// A character was found where it isn't allowed.
+    UnexpectedCharacter(u8),
+    /// A binary entry's length doesn't match its data.
+    InvalidLength,
+    /// The `mccs_ver` entry is not a valid version.
+    InvalidVersion,
+    /// Groups are nested deeper than any display would report.
+    NestingTooDeep,
+}
+
+impl fmt::Display for CapabilitiesError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.kind {
+            CapabilitiesErrorKind::UnexpectedEnd => write!(f, "unexpected end of capabilities"),
+            CapabilitiesErrorKind::UnexpectedCharacter(c) => write!(f, "unexpected {:?} in capabilities", c as char),
+            CapabilitiesErrorKind::InvalidLength => write!(f, "invalid binary length in capabilities"),
+            CapabilitiesErrorKind::InvalidVersion => write!(f, "invalid MCCS version in capabilities"),
+            CapabilitiesErrorKind::NestingTooDeep => write!(f, "capabilities nested too deeply"),
+        }?;
+        write!(f, " at offset {}", self.position)
+    }
+}
+
+#[cfg(feature = "std")]
+impl error::Error for CapabilitiesError {}
+
+impl Capabilities {
+    /// Parses a capabilities string as returned by
+    /// `Ddc::capabilities_string`.
+    pub fn parse(data: &[u8]) -> Result<Self, CapabilitiesError> {
+        let mut caps = Capabilities::default();
+        for entry in entries(data, 0, 0)? {
+            match (entry.name, entry.value) {
+                (b"prot", RawValue::String(value, _)) => caps.protocol = Some(text(value)),
+                (b"type", RawValue::String(value, _)) => caps.ty = Some(text(value)),
+                (b"model", RawValue::String(value, _)) => caps.model = Some(text(value)),
+                (b"cmds", RawValue::String(value, pos)) => caps
+                    .commands
+                    .extend(hex_list(value, pos)?.into_iter().map(|(code, _)| code)),
+                (b"mswhql", RawValue::String(value, _)) => caps.ms_whql = number(value),
+                (b"mccs_ver", RawValue::String(value, pos)) => caps.mccs_version = Some(version(value, pos)?),
+                (b"vcp", RawValue::String(value, pos)) =>
+                    for (code, values) in hex_list(value, pos)? {
+                        let feature = caps.vcp_features.entry(code).or_default();
+                        if let Some((values, pos)) = values {
+                            feature
+                                .values
+                                .extend(hex_list(values, pos)?.into_iter().map(|(value, _)| value));
+                        }
+                    },
+                (b"vcpname", RawValue::String(value, pos)) =>
+                    for (code, name) in hex_list(value, pos)? {
+                        caps.vcp_features.entry(code).or_default().name = name.map(|(name, _)| text(name));
+                    },
+                (b"edid", RawValue::Binary(data)) => caps.edid = Some(data.to_owned()),
+                (b"vdif", RawValue::Binary(data)) => caps.vdif.push(data.to_owned()),
+                (name, RawValue::String(value, pos)) if name.starts_with(b"window") => caps.windows.push(Window {
+                    index: number(&name[6..]).unwrap_or_default(),
+                    entries: entries(value, pos, 1)?.into_iter().map(RawEntry::into_entry).collect(),
+                }),
+                (name, value) => caps.unknown.push(RawEntry { name, value }.into_entry()),
+            }
+        }
+
+        Ok(caps)
+    }
+}
+
+#[derive(Debug)]
+struct RawEntry<'a> {
+    name: &'a [u8],
+    value: RawValue<'a>,
+}
+
+#[derive(Debug)]
+enum RawValue<'a> {
+    String(&'a [u8], usize),
+    Binary(&'a [u8]),
+}
+
+impl RawEntry<'_> {
+    fn into_entry(self) -> Entry {
+        Entry {
+            name: text(self.name),
+            value: match self.value {
+                RawValue::String(value, _) => EntryValue::String(text(value)),
+                RawValue::Binary(data) => EntryValue::Binary(data.to_owned()),
+            },
+        }
+    }
+}
+
+fn error(position: usize, kind: CapabilitiesErrorKind) -> CapabilitiesError {
+    CapabilitiesError { position, kind }
+}
+
+fn is_space(c: u8) -> bool {
+    // some displays pad the string with NUL bytes
+    c.is_ascii_whitespace() || c == 0
+}
+
+fn skip_space(data: &[u8], pos: usize) -> usize {
+    pos + data[pos..].iter().take_while(|&&c| is_space(c)).count()
+}
+
+fn text(data: &[u8]) -> String {
+    String::from_utf8_lossy(data).trim().to_string()
+}
+
+fn number(data: &[u8]) -> Option<u8> {
+    str::from_utf8(data).ok()?.trim().parse().ok()
+}
+
+fn version(data: &[u8], pos: usize) -> Result<Version, CapabilitiesError> {
+    let data = str::from_utf8(data).map_err(|_| error(pos, CapabilitiesErrorKind::InvalidVersion))?;
+    let (major, minor) = data.trim().split_once('.').unwrap_or((data.trim(), "0"));
+    match (major.parse(), minor.parse()) {
+        (Ok(major), Ok(minor)) => Ok(Version { major, minor }),
+        _ => Err(error(pos, CapabilitiesErrorKind::InvalidVersion)),
+    }
+}
+
+/// Finds the parenthesis closing a group that starts just before `pos`.
+///
+/// An unterminated group extends to the end of the string.
+fn group_end(data: &[u8], pos: usize) -> (usize, usize) {
+    let mut depth = 0usize;
+    for (i, &c) in data[pos..].iter().enumerate() {
+        match c {
+            b'(' => depth += 1,
+            b')' if depth == 0 => return (pos + i, pos + i + 1),
+            b')' => depth -= 1,
+            _ => (),
+        }
+    }
+
+    (data.len(), data.len())
+}
+
+/// The deepest nesting of unnamed groups and windows that is parsed.
+const MAX_DEPTH: usize = 8;
+
+/// Splits `name(value)` entries, where `base` is the offset of `data` within
+/// the full capabilities string and `depth` the number of groups it is
+/// nested in.
+fn entries(data: &[u8], base: usize, depth: usize) -> Result<Vec<RawEntry<'_>>, CapabilitiesError> {
+    let mut entries = Vec::new();
+    let mut pos = 0;
+    loop {
+        pos = skip_space(data, pos);
+        if pos == data.len() {
+            break
+        }
+
+        let name_len = data[pos..]
+            .iter()
+            .take_while(|&&c| c != b'(' && c != b')' && !is_space(c))
+            .count();
+        let name = &data[pos..pos + name_len];
+        pos = skip_space(data, pos + name_len);
+
+        match data.get(pos) {
+            // stray closing parentheses are harmless
+            Some(b')') if name.is_empty() => {
+                pos += 1;
+                continue
+            },
+            Some(b'(') => (),
+            Some(&c) if !data[pos..].starts_with(b"bin(") =>
+                return Err(error(base + pos, CapabilitiesErrorKind::UnexpectedCharacter(c))),
+            Some(_) => {
+                let (value, end) = binary(data, pos + 4, base)?;
+                entries.push(RawEntry {
+                    name,
+                    value: RawValue::Binary(value),
+                });
+                pos = end;
+                continue
+            },
+            None => return Err(error(base + pos, CapabilitiesErrorKind::UnexpectedEnd)),
+        }
+
+        let start = pos + 1;
+        let (end, next) = group_end(data, start);
+        if name.is_empty() {
+            // the outer parentheses around the whole string
+            if depth >= MAX_DEPTH {
+                return Err(error(base + pos, CapabilitiesErrorKind::NestingTooDeep))
+            }
+            entries.extend(self::entries(&data[start..end], base + start, depth + 1)?);
+        } else {
+            entries.push(RawEntry {
+                name,
+                value: RawValue::String(&data[start..end], base + start),
+            });
+        }
+        pos = next;
+    }
+
+    Ok(entries)
+}
+
+/// Parses the `len(data))` following `bin(`, returning the data and the
+/// position after it.
+fn binary(data: &[u8], pos: usize, base: usize) -> Result<(&[u8], usize), CapabilitiesError> {
+    let digits = data[pos..].iter().take_while(|c| c.is_ascii_digit()).count();
+    let len: usize = str::from_utf8(&data[pos..pos + digits])
+        .ok()
+        .and_then(|len| len.parse().ok())
+        .ok_or(error(base + pos, CapabilitiesErrorKind::InvalidLength))?;
+    let pos = pos + digits;
+    match data.get(pos) {
+        Some(b'(') => (),
+        Some(&c) => return Err(error(base + pos, CapabilitiesErrorKind::UnexpectedCharacter(c))),
+        None => return Err(error(base + pos, CapabilitiesErrorKind::UnexpectedEnd)),
+    }
+
+    let start = pos + 1;
+    let value = data
+        .get(start..start + len)
+        .ok_or(error(base + data.len(), CapabilitiesErrorKind::UnexpectedEnd))?;
+    let pos = start + len;
+    match data.get(pos..pos + 2) {
+        Some(b"))") => Ok((value, pos + 2)),
+        _ => Err(error(base + pos, CapabilitiesErrorKind::InvalidLength)),
+    }
+}
+
+/// A hex code and the contents and position of the group that follows it.
+type HexItem<'a> = (u8, Option<(&'a [u8], usize)>);
+
+/// Parses a list of hex codes, each optionally followed by a parenthesized
+/// group.
+fn hex_list(data: &[u8], base: usize) -> Result<Vec<HexItem<'_>>, CapabilitiesError> {
+    let mut list = Vec::new();
+    let mut pos = 0;
+    loop {
+        pos = skip_space(data, pos);
+        let c = match data.get(pos) {
+            Some(&c) => c,
+            None => break,
+        };
+
+        // codes are usually separated by spaces, but not always
+        let digits = data[pos..].iter().take(2).take_while(|c| c.is_ascii_hexdigit()).count();
+        if digits == 0 {
+            return Err(error(base + pos, CapabilitiesErrorKind::UnexpectedCharacter(c)))
+        }
+
+        let code = str::from_utf8(&data[pos..pos + digits])
+            .ok()
+            .and_then(|code| u8::from_str_radix(code, 16).ok())
+            .unwrap_or_default();
+        pos = skip_space(data, pos + digits);
+
+        let group = match data.get(pos) {
+            Some(b'(') => {
+                let start = pos + 1;
+                let (end, next) = group_end(data, start);
+                pos = next;
+                Some((&data[start..end], base + start))
+            },
+            _ => None,
+        };
+        list.push((code, group));
+    }
+
+    Ok(list)
+}
@@ -1,11 +1,69 @@
 #![allow(missing_docs)]
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 use {
     crate::{ErrorCode, FeatureCode, VcpValue},
-    std::{fmt, mem},
+    core::{fmt, mem},
 };
 
+/// Scratch space for encoding a command or receiving its reply.
+///
+/// Fixed size commands use arrays, while `Vec<u8>` allows sizes that are
+/// only known at runtime.
+///
+/// Buffers are `Send` so that they may be held across an `await` by `Send`
+/// futures.
+pub trait Buffer: AsMut<[u8]> + Send {
+    /// Creates a zeroed buffer of at least `len` bytes if possible.
+    fn with_len(len: usize) -> Self;
+}
+
+impl<const N: usize> Buffer for [u8; N] {
+    fn with_len(_len: usize) -> Self {
+        [0u8; N]
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl Buffer for Vec<u8> {
+    fn with_len(len: usize) -> Self {
+        vec![0u8; len]
+    }
+}
+
+/// Encodes a command into its own buffer, returning the buffer and the
+/// encoded length.
+pub(crate) fn encode_command<C: Command + ?Sized>(command: &C) -> Result<(C::Buffer, usize), ErrorCode> {
+    let len = command.len();
+    let mut data = C::Buffer::with_len(len);
+    if data.as_mut().len() < len {
+        return Err(ErrorCode::InvalidLength)
+    }
+    let len = command.encode(&mut data.as_mut()[..len])?;
+    Ok((data, len))
+}
+
+/// Creates the buffer that a command's framed reply is read into.
+pub(crate) fn reply_buffer<C: Command + ?Sized>(command: &C) -> <C::Ok as CommandResult>::Buffer {
+    <C::Ok as CommandResult>::Buffer::with_len(command.reply_len() + 3)
+}
+
+/// The portion of a reply buffer that a framed reply of up to `len` bytes
+/// may be read into.
+pub(crate) fn reply_slice(out: &mut [u8], len: usize) -> Result<&mut [u8], ErrorCode> {
+    match len {
+        0 => Ok(&mut []),
+        len => out.get_mut(..len + 3).ok_or(ErrorCode::InvalidLength),
+    }
+}
+
+#[allow(clippy::len_without_is_empty)]
 pub trait Command {
     type Ok: CommandResult;
+    /// Holds the encoded command, at least `MAX_LEN` bytes long.
+    type Buffer: Buffer;
     const MIN_LEN: usize;
     const MAX_LEN: usize;
     const DELAY_RESPONSE_MS: u64;
@@ -14,14 +72,90 @@ pub trait Command {
     fn len(&self) -> usize;
 
     fn encode(&self, data: &mut [u8]) -> Result<usize, ErrorCode>;
+
+    /// The maximum length of the reply, or 0 if the command has no reply.
+    fn reply_len(&self) -> usize {
+        Self::Ok::MAX_LEN
+    }
+
+    /// The delay in milliseconds between sending the command and reading its
+    /// reply.
+    fn delay_response_ms(&self) -> u64 {
+        Self::DELAY_RESPONSE_MS
+    }
+
+    /// The delay in milliseconds after the command before another may be
+    /// sent.
+    fn delay_command_ms(&self) -> u64 {
+        Self::DELAY_COMMAND_MS
+    }
+
+    /// Decodes the reply to this command, checking that it corresponds to
+    /// the request rather than a stale reply to an earlier command.
+    fn decode(&self, data: &[u8]) -> Result<Self::Ok, ErrorCode> {
+        Self::Ok::decode(data)
+    }
 }
 
 pub trait CommandResult: Sized {
+    /// Holds the framed reply, at least `MAX_LEN + 3` bytes long.
+    type Buffer: Buffer;
     const MAX_LEN: usize;
     fn decode(data: &[u8]) -> Result<Self, ErrorCode>;
 }
 
-#[derive(Copy, Clone, Debug)]
+/// A command that can be parsed from the payload of a request, as received
+/// by a display.
+pub trait DecodeCommand<'a>: Command + Sized {
+    /// Parses a request payload, starting with the opcode.
+    fn decode_request(data: &'a [u8]) -> Result<Self, ErrorCode>;
+}
+
+/// A command result that can be encoded into the payload of a reply, as sent
+/// by a display.
+pub trait EncodeResult: CommandResult {
+    /// Encodes the reply payload into `data`, returning its length.
+    fn encode_reply(&self, data: &mut [u8]) -> Result<usize, ErrorCode>;
+}
+
+/// Checks the opcode and length of a request or reply payload.
+fn check_payload(data: &[u8], opcode: u8, min_len: usize, max_len: usize) -> Result<(), ErrorCode> {
+    if data.len() < min_len {
+        return Err(ErrorCode::LengthMismatch {
+            expected: min_len,
+            actual: data.len(),
+        })
+    } else if data.len() > max_len {
+        return Err(ErrorCode::LengthMismatch {
+            expected: max_len,
+            actual: data.len(),
+        })
+    }
+
+    if data[0] != opcode {
+        return Err(ErrorCode::OpcodeMismatch {
+            expected: opcode,
+            actual: data[0],
+        })
+    }
+
+    Ok(())
+}
+
+/// Writes a reply payload consisting of `header` followed by `bytes`.
+fn write_reply(data: &mut [u8], header: &[u8], bytes: &[u8]) -> Result<usize, ErrorCode> {
+    let len = header.len() + bytes.len();
+    match data.get_mut(..len) {
+        Some(data) => {
+            data[..header.len()].copy_from_slice(header);
+            data[header.len()..].copy_from_slice(bytes);
+            Ok(len)
+        },
+        None => Err(ErrorCode::BufferTooSmall),
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub struct GetVcpFeature {
     pub code: FeatureCode,
 }
@@ -30,9 +164,20 @@ impl GetVcpFeature {
     pub fn new(code: FeatureCode) -> Self {
         GetVcpFeature { code }
     }
+
+    /// Encodes the reply to this request, reporting the feature as
+    /// unsupported if `value` is `None`.
+    pub fn encode_reply(&self, value: Option<&VcpValue>, data: &mut [u8]) -> Result<usize, ErrorCode> {
+        let reply = match value {
+            Some(value) => [0x02, 0x00, self.code, value.ty, value.mh, value.ml, value.sh, value.sl],
+            None => [0x02, 0x01, self.code, 0x00, 0x00, 0x00, 0x00, 0x00],
+        };
+        write_reply(data, &reply, &[])
+    }
 }
 
 impl Command for GetVcpFeature {
+    type Buffer = [u8; 2];
     type Ok = VcpValue;
 
     // the spec omits this, but 50 corresponds with what all other commands suggest
@@ -52,9 +197,27 @@ impl Command for GetVcpFeature {
 
         Ok(2)
     }
+
+    fn decode(&self, data: &[u8]) -> Result<Self::Ok, ErrorCode> {
+        match *data {
+            [0x02, _, code, ..] if code != self.code => Err(ErrorCode::WrongFeature {
+                expected: self.code,
+                actual: code,
+            }),
+            _ => VcpValue::decode(data),
+        }
+    }
 }
 
-#[derive(Copy, Clone, Debug)]
+impl<'a> DecodeCommand<'a> for GetVcpFeature {
+    fn decode_request(data: &'a [u8]) -> Result<Self, ErrorCode> {
+        check_payload(data, 0x01, Self::MIN_LEN, Self::MAX_LEN)?;
+
+        Ok(GetVcpFeature::new(data[1]))
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub struct SetVcpFeature {
     pub code: FeatureCode,
     pub value: u16,
@@ -67,6 +230,7 @@ impl SetVcpFeature {
 }
 
 impl Command for SetVcpFeature {
+    type Buffer = [u8; 4];
     type Ok = ();
 
     const DELAY_COMMAND_MS: u64 = 50;
@@ -90,29 +254,45 @@ impl Command for SetVcpFeature {
     }
 }
 
+impl<'a> DecodeCommand<'a> for SetVcpFeature {
+    fn decode_request(data: &'a [u8]) -> Result<Self, ErrorCode> {
+        check_payload(data, 0x03, Self::MIN_LEN, Self::MAX_LEN)?;
+
+        Ok(SetVcpFeature::new(data[1], ((data[2] as u16) << 8) | data[3] as u16))
+    }
+}
+
 impl CommandResult for VcpValue {
+    type Buffer = [u8; 8 + 3];
+
     const MAX_LEN: usize = 8;
 
     fn decode(data: &[u8]) -> Result<Self, ErrorCode> {
         if data.len() != 8 {
-            return Err(ErrorCode::InvalidLength)
+            return Err(ErrorCode::LengthMismatch {
+                expected: 8,
+                actual: data.len(),
+            })
         }
 
         if data[0] != 0x02 {
-            return Err(ErrorCode::InvalidOpcode)
+            return Err(ErrorCode::OpcodeMismatch {
+                expected: 0x02,
+                actual: data[0],
+            })
         }
 
         match data[1] {
             // NoError
             0x00 => (),
-            0x01 => return Err(ErrorCode::Invalid("Unsupported VCP code".into())),
-            rc => return Err(ErrorCode::Invalid(format!("Unrecognized VCP error code 0x{:02x}", rc))),
+            0x01 => return Err(ErrorCode::UnsupportedFeature(data[2])),
+            rc => return Err(ErrorCode::UnknownResultCode(rc)),
         }
 
-        // data[2] == vcp code from request
+        // data[2] == vcp code from request, checked by `GetVcpFeature::decode`
 
         Ok(VcpValue {
-            ty: data[2],
+            ty: data[3],
             mh: data[4],
             ml: data[5],
             sh: data[6],
@@ -121,10 +301,11 @@ impl CommandResult for VcpValue {
     }
 }
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub struct SaveCurrentSettings;
 
 impl Command for SaveCurrentSettings {
+    type Buffer = [u8; 1];
     type Ok = ();
 
     const DELAY_COMMAND_MS: u64 = 200;
@@ -137,14 +318,22 @@ impl Command for SaveCurrentSettings {
     }
 
     fn encode(&self, data: &mut [u8]) -> Result<usize, ErrorCode> {
-        assert!(data.len() >= 1);
+        assert!(!data.is_empty());
         data[0] = 0x0c;
 
         Ok(1)
     }
 }
 
-#[derive(Copy, Clone, Debug)]
+impl<'a> DecodeCommand<'a> for SaveCurrentSettings {
+    fn decode_request(data: &'a [u8]) -> Result<Self, ErrorCode> {
+        check_payload(data, 0x0c, Self::MIN_LEN, Self::MAX_LEN)?;
+
+        Ok(SaveCurrentSettings)
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub struct TableWrite<'a> {
     pub code: FeatureCode,
     pub offset: u16,
@@ -158,6 +347,7 @@ impl<'a> TableWrite<'a> {
 }
 
 impl<'a> Command for TableWrite<'a> {
+    type Buffer = [u8; 4 + 32];
     type Ok = ();
 
     const DELAY_COMMAND_MS: u64 = 50;
@@ -171,8 +361,10 @@ impl<'a> Command for TableWrite<'a> {
     }
 
     fn encode(&self, data: &mut [u8]) -> Result<usize, ErrorCode> {
+        if self.data.len() > 32 {
+            return Err(ErrorCode::InvalidLength)
+        }
         assert!(data.len() >= 4 + self.data.len());
-        assert!(self.data.len() <= 32);
 
         data[0] = 0xe7;
         data[1] = self.code;
@@ -184,7 +376,19 @@ impl<'a> Command for TableWrite<'a> {
     }
 }
 
-#[derive(Copy, Clone, Debug)]
+impl<'a> DecodeCommand<'a> for TableWrite<'a> {
+    fn decode_request(data: &'a [u8]) -> Result<Self, ErrorCode> {
+        check_payload(data, 0xe7, Self::MIN_LEN, Self::MAX_LEN)?;
+
+        Ok(TableWrite::new(
+            data[1],
+            ((data[2] as u16) << 8) | data[3] as u16,
+            &data[4..],
+        ))
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub struct TableRead {
     pub code: FeatureCode,
     pub offset: u16,
@@ -197,6 +401,7 @@ impl TableRead {
 }
 
 impl Command for TableRead {
+    type Buffer = [u8; 4];
     type Ok = TableResponse;
 
     const DELAY_COMMAND_MS: u64 = 50;
@@ -218,9 +423,29 @@ impl Command for TableRead {
 
         Ok(4)
     }
+
+    // the reply doesn't include the VCP code, so only the offset can be checked
+    fn decode(&self, data: &[u8]) -> Result<Self::Ok, ErrorCode> {
+        let table = TableResponse::decode(data)?;
+        match table.offset {
+            offset if offset != self.offset => Err(ErrorCode::OffsetMismatch {
+                expected: self.offset,
+                actual: offset,
+            }),
+            _ => Ok(table),
+        }
+    }
 }
 
-#[derive(Copy, Clone, Debug)]
+impl<'a> DecodeCommand<'a> for TableRead {
+    fn decode_request(data: &'a [u8]) -> Result<Self, ErrorCode> {
+        check_payload(data, 0xe2, Self::MIN_LEN, Self::MAX_LEN)?;
+
+        Ok(TableRead::new(data[1], ((data[2] as u16) << 8) | data[3] as u16))
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub struct CapabilitiesRequest {
     pub offset: u16,
 }
@@ -232,6 +457,7 @@ impl CapabilitiesRequest {
 }
 
 impl Command for CapabilitiesRequest {
+    type Buffer = [u8; 3];
     type Ok = CapabilitiesReply;
 
     const DELAY_COMMAND_MS: u64 = 50;
@@ -252,9 +478,28 @@ impl Command for CapabilitiesRequest {
 
         Ok(3)
     }
+
+    fn decode(&self, data: &[u8]) -> Result<Self::Ok, ErrorCode> {
+        let caps = CapabilitiesReply::decode(data)?;
+        match caps.offset {
+            offset if offset != self.offset => Err(ErrorCode::OffsetMismatch {
+                expected: self.offset,
+                actual: offset,
+            }),
+            _ => Ok(caps),
+        }
+    }
 }
 
-#[derive(Copy, Clone)]
+impl<'a> DecodeCommand<'a> for CapabilitiesRequest {
+    fn decode_request(data: &'a [u8]) -> Result<Self, ErrorCode> {
+        check_payload(data, 0xf3, Self::MIN_LEN, Self::MAX_LEN)?;
+
+        Ok(CapabilitiesRequest::new(((data[1] as u16) << 8) | data[2] as u16))
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq)]
 pub struct TableResponse {
     pub offset: u16,
     data: [u8; 32],
@@ -262,6 +507,21 @@ pub struct TableResponse {
 }
 
 impl TableResponse {
+    /// Creates a fragment of up to 32 bytes.
+    pub fn new(offset: u16, bytes: &[u8]) -> Result<Self, ErrorCode> {
+        if bytes.len() > 32 {
+            return Err(ErrorCode::InvalidLength)
+        }
+
+        let mut table = TableResponse {
+            offset,
+            data: [0; 32],
+            len: bytes.len() as u8,
+        };
+        table.data[..bytes.len()].copy_from_slice(bytes);
+        Ok(table)
+    }
+
     pub fn bytes(&self) -> &[u8] {
         &self.data[..self.len as usize]
     }
@@ -283,34 +543,74 @@ impl Default for TableResponse {
 }
 
 impl CommandResult for TableResponse {
-    const MAX_LEN: usize = 36;
+    type Buffer = [u8; 35 + 3];
+
+    const MAX_LEN: usize = 35;
 
     fn decode(data: &[u8]) -> Result<Self, ErrorCode> {
         // spec says 3 - 35???
-        if data.len() < 4 || data.len() > 36 {
+        // an empty fragment terminates the table
+        if data.len() < 3 || data.len() > 35 {
             return Err(ErrorCode::InvalidLength)
         }
 
         if data[0] != 0xe4 {
-            return Err(ErrorCode::InvalidOpcode)
+            return Err(ErrorCode::OpcodeMismatch {
+                expected: 0xe4,
+                actual: data[0],
+            })
         }
 
-        let mut table = TableResponse::default();
-        table.offset = ((data[1] as u16) << 8) | data[2] as u16;
-        let data = &data[3..];
-        table.len = data.len() as u8;
-        table.data[..data.len()].copy_from_slice(data);
-        Ok(table)
+        TableResponse::new(((data[1] as u16) << 8) | data[2] as u16, &data[3..])
+    }
+}
+
+impl EncodeResult for TableResponse {
+    fn encode_reply(&self, data: &mut [u8]) -> Result<usize, ErrorCode> {
+        write_reply(data, &[0xe4, (self.offset >> 8) as u8, self.offset as u8], self.bytes())
     }
 }
 
-#[derive(Clone, Debug)]
+#[derive(Copy, Clone, Default, PartialEq, Eq)]
 pub struct CapabilitiesReply {
     pub offset: u16,
-    pub data: Box<[u8]>,
+    data: [u8; 32],
+    len: u8,
+}
+
+impl CapabilitiesReply {
+    /// Creates a fragment of up to 32 bytes.
+    pub fn new(offset: u16, bytes: &[u8]) -> Result<Self, ErrorCode> {
+        if bytes.len() > 32 {
+            return Err(ErrorCode::InvalidLength)
+        }
+
+        let mut caps = CapabilitiesReply {
+            offset,
+            data: [0; 32],
+            len: bytes.len() as u8,
+        };
+        caps.data[..bytes.len()].copy_from_slice(bytes);
+        Ok(caps)
+    }
+
+    pub fn bytes(&self) -> &[u8] {
+        &self.data[..self.len as usize]
+    }
+}
+
+impl fmt::Debug for CapabilitiesReply {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("CapabilitiesReply")
+            .field("offset", &self.offset)
+            .field("bytes", &self.bytes())
+            .finish()
+    }
 }
 
 impl CommandResult for CapabilitiesReply {
+    type Buffer = [u8; 35 + 3];
+
     const MAX_LEN: usize = 35;
 
     fn decode(data: &[u8]) -> Result<Self, ErrorCode> {
@@ -319,20 +619,27 @@ impl CommandResult for CapabilitiesReply {
         }
 
         if data[0] != 0xe3 {
-            return Err(ErrorCode::InvalidOpcode)
+            return Err(ErrorCode::OpcodeMismatch {
+                expected: 0xe3,
+                actual: data[0],
+            })
         }
 
-        Ok(CapabilitiesReply {
-            offset: ((data[1] as u16) << 8) | data[2] as u16,
-            data: data[3..].to_owned().into_boxed_slice(),
-        })
+        CapabilitiesReply::new(((data[1] as u16) << 8) | data[2] as u16, &data[3..])
+    }
+}
+
+impl EncodeResult for CapabilitiesReply {
+    fn encode_reply(&self, data: &mut [u8]) -> Result<usize, ErrorCode> {
+        write_reply(data, &[0xe3, (self.offset >> 8) as u8, self.offset as u8], self.bytes())
     }
 }
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub struct GetTimingReport;
 
 impl Command for GetTimingReport {
+    type Buffer = [u8; 1];
     type Ok = TimingMessage;
 
     const DELAY_COMMAND_MS: u64 = 50;
@@ -345,14 +652,23 @@ impl Command for GetTimingReport {
     }
 
     fn encode(&self, data: &mut [u8]) -> Result<usize, ErrorCode> {
-        assert!(data.len() >= 1);
+        assert!(!data.is_empty());
         data[0] = 0x07;
 
         Ok(1)
     }
 }
 
-#[derive(Clone, Debug)]
+impl<'a> DecodeCommand<'a> for GetTimingReport {
+    fn decode_request(data: &'a [u8]) -> Result<Self, ErrorCode> {
+        check_payload(data, 0x07, Self::MIN_LEN, Self::MAX_LEN)?;
+
+        Ok(GetTimingReport)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct TimingMessage {
     pub timing_status: u8,
     pub horizontal_frequency: u16,
@@ -360,15 +676,23 @@ pub struct TimingMessage {
 }
 
 impl CommandResult for TimingMessage {
+    type Buffer = [u8; 6 + 3];
+
     const MAX_LEN: usize = 6;
 
     fn decode(data: &[u8]) -> Result<Self, ErrorCode> {
         if data.len() != 6 {
-            return Err(ErrorCode::InvalidLength)
+            return Err(ErrorCode::LengthMismatch {
+                expected: 6,
+                actual: data.len(),
+            })
         }
 
         if data[0] != 0x4e {
-            return Err(ErrorCode::InvalidOpcode)
+            return Err(ErrorCode::OpcodeMismatch {
+                expected: 0x4e,
+                actual: data[0],
+            })
         }
 
         Ok(TimingMessage {
@@ -379,19 +703,273 @@ impl CommandResult for TimingMessage {
     }
 }
 
+impl EncodeResult for TimingMessage {
+    fn encode_reply(&self, data: &mut [u8]) -> Result<usize, ErrorCode> {
+        write_reply(
+            data,
+            &[
+                0x4e,
+                self.timing_status,
+                (self.horizontal_frequency >> 8) as u8,
+                self.horizontal_frequency as u8,
+                (self.vertical_frequency >> 8) as u8,
+                self.vertical_frequency as u8,
+            ],
+            &[],
+        )
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct IdentificationRequest;
+
+impl Command for IdentificationRequest {
+    type Buffer = [u8; 1];
+    type Ok = IdentificationReply;
+
+    const DELAY_COMMAND_MS: u64 = 50;
+    const DELAY_RESPONSE_MS: u64 = 40;
+    const MAX_LEN: usize = 1;
+    const MIN_LEN: usize = 1;
+
+    fn len(&self) -> usize {
+        1
+    }
+
+    fn encode(&self, data: &mut [u8]) -> Result<usize, ErrorCode> {
+        assert!(!data.is_empty());
+        data[0] = 0xf1;
+
+        Ok(1)
+    }
+}
+
+impl<'a> DecodeCommand<'a> for IdentificationRequest {
+    fn decode_request(data: &'a [u8]) -> Result<Self, ErrorCode> {
+        check_payload(data, 0xf1, Self::MIN_LEN, Self::MAX_LEN)?;
+
+        Ok(IdentificationRequest)
+    }
+}
+
+#[derive(Copy, Clone, Default, PartialEq, Eq)]
+pub struct IdentificationReply {
+    data: [u8; 32],
+    len: u8,
+}
+
+impl IdentificationReply {
+    /// Creates a reply of up to 32 bytes.
+    pub fn new(bytes: &[u8]) -> Result<Self, ErrorCode> {
+        if bytes.len() > 32 {
+            return Err(ErrorCode::InvalidLength)
+        }
+
+        let mut id = IdentificationReply {
+            data: [0; 32],
+            len: bytes.len() as u8,
+        };
+        id.data[..bytes.len()].copy_from_slice(bytes);
+        Ok(id)
+    }
+
+    pub fn bytes(&self) -> &[u8] {
+        &self.data[..self.len as usize]
+    }
+}
+
+impl fmt::Debug for IdentificationReply {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("IdentificationReply")
+            .field("bytes", &self.bytes())
+            .finish()
+    }
+}
+
+impl CommandResult for IdentificationReply {
+    type Buffer = [u8; 33 + 3];
+
+    const MAX_LEN: usize = 33;
+
+    fn decode(data: &[u8]) -> Result<Self, ErrorCode> {
+        if data.is_empty() || data.len() > 33 {
+            return Err(ErrorCode::InvalidLength)
+        }
+
+        if data[0] != 0xe1 {
+            return Err(ErrorCode::OpcodeMismatch {
+                expected: 0xe1,
+                actual: data[0],
+            })
+        }
+
+        IdentificationReply::new(&data[1..])
+    }
+}
+
+impl EncodeResult for IdentificationReply {
+    fn encode_reply(&self, data: &mut [u8]) -> Result<usize, ErrorCode> {
+        write_reply(data, &[0xe1], self.bytes())
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct SelfTestRequest;
+
+impl Command for SelfTestRequest {
+    type Buffer = [u8; 1];
+    type Ok = SelfTestReply;
+
+    const DELAY_COMMAND_MS: u64 = 50;
+    const DELAY_RESPONSE_MS: u64 = 40;
+    const MAX_LEN: usize = 1;
+    const MIN_LEN: usize = 1;
+
+    fn len(&self) -> usize {
+        1
+    }
+
+    fn encode(&self, data: &mut [u8]) -> Result<usize, ErrorCode> {
+        assert!(!data.is_empty());
+        data[0] = 0xb1;
+
+        Ok(1)
+    }
+}
+
+impl<'a> DecodeCommand<'a> for SelfTestRequest {
+    fn decode_request(data: &'a [u8]) -> Result<Self, ErrorCode> {
+        check_payload(data, 0xb1, Self::MIN_LEN, Self::MAX_LEN)?;
+
+        Ok(SelfTestRequest)
+    }
+}
+
+#[derive(Copy, Clone, Default, PartialEq, Eq)]
+pub struct SelfTestReply {
+    data: [u8; 32],
+    len: u8,
+}
+
+impl SelfTestReply {
+    /// Creates a reply of up to 32 bytes.
+    pub fn new(bytes: &[u8]) -> Result<Self, ErrorCode> {
+        if bytes.len() > 32 {
+            return Err(ErrorCode::InvalidLength)
+        }
+
+        let mut test = SelfTestReply {
+            data: [0; 32],
+            len: bytes.len() as u8,
+        };
+        test.data[..bytes.len()].copy_from_slice(bytes);
+        Ok(test)
+    }
+
+    pub fn bytes(&self) -> &[u8] {
+        &self.data[..self.len as usize]
+    }
+}
+
+impl fmt::Debug for SelfTestReply {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("SelfTestReply").field("bytes", &self.bytes()).finish()
+    }
+}
+
+impl CommandResult for SelfTestReply {
+    type Buffer = [u8; 33 + 3];
+
+    const MAX_LEN: usize = 33;
+
+    fn decode(data: &[u8]) -> Result<Self, ErrorCode> {
+        if data.is_empty() || data.len() > 33 {
+            return Err(ErrorCode::InvalidLength)
+        }
+
+        if data[0] != 0xa1 {
+            return Err(ErrorCode::OpcodeMismatch {
+                expected: 0xa1,
+                actual: data[0],
+            })
+        }
+
+        SelfTestReply::new(&data[1..])
+    }
+}
+
+impl EncodeResult for SelfTestReply {
+    fn encode_reply(&self, data: &mut [u8]) -> Result<usize, ErrorCode> {
+        write_reply(data, &[0xa1], self.bytes())
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct EnableApplicationReport {
+    pub enable: bool,
+}
+
+impl EnableApplicationReport {
+    pub fn new(enable: bool) -> Self {
+        EnableApplicationReport { enable }
+    }
+}
+
+impl Command for EnableApplicationReport {
+    type Buffer = [u8; 2];
+    type Ok = ();
+
+    const DELAY_COMMAND_MS: u64 = 50;
+    const DELAY_RESPONSE_MS: u64 = 0;
+    const MAX_LEN: usize = 2;
+    const MIN_LEN: usize = 2;
+
+    fn len(&self) -> usize {
+        2
+    }
+
+    fn encode(&self, data: &mut [u8]) -> Result<usize, ErrorCode> {
+        assert!(data.len() >= 2);
+        data[0] = 0xf5;
+        data[1] = self.enable as u8;
+
+        Ok(2)
+    }
+}
+
+impl<'a> DecodeCommand<'a> for EnableApplicationReport {
+    fn decode_request(data: &'a [u8]) -> Result<Self, ErrorCode> {
+        check_payload(data, 0xf5, Self::MIN_LEN, Self::MAX_LEN)?;
+
+        Ok(EnableApplicationReport::new(data[1] != 0))
+    }
+}
+
 impl CommandResult for () {
+    type Buffer = [u8; 0];
+
     const MAX_LEN: usize = 0;
 
     fn decode(data: &[u8]) -> Result<Self, ErrorCode> {
         if data.is_empty() {
             Ok(())
         } else {
-            Err(ErrorCode::InvalidLength)
+            Err(ErrorCode::LengthMismatch {
+                expected: 0,
+                actual: data.len(),
+            })
         }
     }
 }
 
-impl<'a, C: Command> Command for &'a C {
+impl EncodeResult for () {
+    fn encode_reply(&self, _data: &mut [u8]) -> Result<usize, ErrorCode> {
+        Ok(0)
+    }
+}
+
+impl<C: Command> Command for &C {
+    type Buffer = C::Buffer;
     type Ok = C::Ok;
 
     const DELAY_COMMAND_MS: u64 = C::DELAY_COMMAND_MS;
@@ -406,4 +984,537 @@ impl<'a, C: Command> Command for &'a C {
     fn encode(&self, data: &mut [u8]) -> Result<usize, ErrorCode> {
         (*self).encode(data)
     }
+
+    fn reply_len(&self) -> usize {
+        (*self).reply_len()
+    }
+
+    fn delay_response_ms(&self) -> u64 {
+        (*self).delay_response_ms()
+    }
+
+    fn delay_command_ms(&self) -> u64 {
+        (*self).delay_command_ms()
+    }
+
+    fn decode(&self, data: &[u8]) -> Result<Self::Ok, ErrorCode> {
+        (*self).decode(data)
+    }
+}
+
+/// A command not defined by MCCS, such as a manufacturer specific command,
+/// sent as raw bytes.
+#[cfg(feature = "alloc")]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct RawCommand {
+    /// The command, starting with its opcode.
+    pub data: Vec<u8>,
+    /// The maximum length of the reply, or 0 if the command has no reply.
+    pub reply_len: usize,
+    /// The delay in milliseconds before reading the reply.
+    pub delay_response_ms: u64,
+    /// The delay in milliseconds before another command may be sent.
+    pub delay_command_ms: u64,
+}
+
+#[cfg(feature = "alloc")]
+impl RawCommand {
+    /// Creates a command with the standard MCCS delays.
+    pub fn new(data: Vec<u8>, reply_len: usize) -> Self {
+        RawCommand {
+            data,
+            reply_len,
+            delay_response_ms: Self::DELAY_RESPONSE_MS,
+            delay_command_ms: Self::DELAY_COMMAND_MS,
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl Command for RawCommand {
+    type Buffer = Vec<u8>;
+    type Ok = RawResponse;
+
+    const DELAY_COMMAND_MS: u64 = 50;
+    const DELAY_RESPONSE_MS: u64 = 40;
+    const MAX_LEN: usize = 0x7f;
+    const MIN_LEN: usize = 1;
+
+    fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    fn encode(&self, data: &mut [u8]) -> Result<usize, ErrorCode> {
+        if self.data.is_empty() || self.data.len() > Self::MAX_LEN {
+            return Err(ErrorCode::InvalidLength)
+        }
+        assert!(data.len() >= self.data.len());
+
+        data[..self.data.len()].copy_from_slice(&self.data);
+
+        Ok(self.data.len())
+    }
+
+    fn reply_len(&self) -> usize {
+        self.reply_len.min(RawResponse::MAX_LEN)
+    }
+
+    fn delay_response_ms(&self) -> u64 {
+        self.delay_response_ms
+    }
+
+    fn delay_command_ms(&self) -> u64 {
+        self.delay_command_ms
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<'a> DecodeCommand<'a> for RawCommand {
+    /// Parses any request, assuming that it may have a reply of the maximum
+    /// length.
+    fn decode_request(data: &'a [u8]) -> Result<Self, ErrorCode> {
+        if data.is_empty() || data.len() > Self::MAX_LEN {
+            return Err(ErrorCode::InvalidLength)
+        }
+
+        Ok(RawCommand::new(data.to_vec(), RawResponse::MAX_LEN))
+    }
+}
+
+/// The reply to a `RawCommand`, without the DDC/CI framing.
+#[cfg(feature = "alloc")]
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct RawResponse(pub Vec<u8>);
+
+#[cfg(feature = "alloc")]
+impl CommandResult for RawResponse {
+    type Buffer = Vec<u8>;
+
+    const MAX_LEN: usize = 0x7f;
+
+    fn decode(data: &[u8]) -> Result<Self, ErrorCode> {
+        Ok(RawResponse(data.to_vec()))
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl EncodeResult for RawResponse {
+    fn encode_reply(&self, data: &mut [u8]) -> Result<usize, ErrorCode> {
+        write_reply(data, &[], &self.0)
+    }
+}
+
+/// Any DDC/CI command, for driving displays from data such as configuration
+/// files or RPC requests.
+///
+/// It is executed like any other command, replying with an `AnyResponse`.
+#[cfg(feature = "alloc")]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum AnyCommand {
+    GetVcpFeature {
+        code: FeatureCode,
+    },
+    SetVcpFeature {
+        code: FeatureCode,
+        value: u16,
+    },
+    SaveCurrentSettings,
+    TableRead {
+        code: FeatureCode,
+        offset: u16,
+    },
+    TableWrite {
+        code: FeatureCode,
+        offset: u16,
+        data: Vec<u8>,
+    },
+    CapabilitiesRequest {
+        offset: u16,
+    },
+    GetTimingReport,
+    IdentificationRequest,
+    SelfTestRequest,
+    EnableApplicationReport {
+        enable: bool,
+    },
+    Raw(RawCommand),
+}
+
+/// The reply to an `AnyCommand`.
+#[cfg(feature = "alloc")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum AnyResponse {
+    /// The command has no reply.
+    Empty,
+    VcpValue(#[cfg_attr(feature = "serde", serde(with = "VcpValueDef"))] VcpValue),
+    Table {
+        offset: u16,
+        data: Vec<u8>,
+    },
+    Capabilities {
+        offset: u16,
+        data: Vec<u8>,
+    },
+    Timing(TimingMessage),
+    Identification(Vec<u8>),
+    SelfTest(Vec<u8>),
+    Raw(RawResponse),
+}
+
+#[cfg(feature = "serde")]
+#[derive(Serialize, Deserialize)]
+#[serde(remote = "VcpValue")]
+struct VcpValueDef {
+    ty: u8,
+    mh: u8,
+    ml: u8,
+    sh: u8,
+    sl: u8,
+}
+
+#[cfg(feature = "alloc")]
+impl AnyCommand {
+    /// Parses a request payload, falling back to `AnyCommand::Raw` for
+    /// unrecognized opcodes.
+    pub fn decode_request(data: &[u8]) -> Result<Self, ErrorCode> {
+        Ok(match data.first() {
+            Some(0x01) => GetVcpFeature::decode_request(data)?.into(),
+            Some(0x03) => SetVcpFeature::decode_request(data)?.into(),
+            Some(0x07) => GetTimingReport::decode_request(data)?.into(),
+            Some(0x0c) => SaveCurrentSettings::decode_request(data)?.into(),
+            Some(0xb1) => SelfTestRequest::decode_request(data)?.into(),
+            Some(0xe2) => TableRead::decode_request(data)?.into(),
+            Some(0xe7) => TableWrite::decode_request(data)?.into(),
+            Some(0xf1) => IdentificationRequest::decode_request(data)?.into(),
+            Some(0xf3) => CapabilitiesRequest::decode_request(data)?.into(),
+            Some(0xf5) => EnableApplicationReport::decode_request(data)?.into(),
+            _ => AnyCommand::Raw(RawCommand::decode_request(data)?),
+        })
+    }
+}
+
+/// Binds the typed command that an `AnyCommand` corresponds to and
+/// evaluates `$body` with it.
+#[cfg(feature = "alloc")]
+macro_rules! dispatch {
+    ($any:expr, $command:ident => $body:expr) => {
+        match *$any {
+            AnyCommand::GetVcpFeature { code } => {
+                let $command = GetVcpFeature::new(code);
+                $body
+            },
+            AnyCommand::SetVcpFeature { code, value } => {
+                let $command = SetVcpFeature::new(code, value);
+                $body
+            },
+            AnyCommand::SaveCurrentSettings => {
+                let $command = SaveCurrentSettings;
+                $body
+            },
+            AnyCommand::TableRead { code, offset } => {
+                let $command = TableRead::new(code, offset);
+                $body
+            },
+            AnyCommand::TableWrite { code, offset, ref data } => {
+                let $command = TableWrite::new(code, offset, data);
+                $body
+            },
+            AnyCommand::CapabilitiesRequest { offset } => {
+                let $command = CapabilitiesRequest::new(offset);
+                $body
+            },
+            AnyCommand::GetTimingReport => {
+                let $command = GetTimingReport;
+                $body
+            },
+            AnyCommand::IdentificationRequest => {
+                let $command = IdentificationRequest;
+                $body
+            },
+            AnyCommand::SelfTestRequest => {
+                let $command = SelfTestRequest;
+                $body
+            },
+            AnyCommand::EnableApplicationReport { enable } => {
+                let $command = EnableApplicationReport::new(enable);
+                $body
+            },
+            AnyCommand::Raw(ref raw) => {
+                let $command = raw;
+                $body
+            },
+        }
+    };
+}
+
+/// Executes as the typed command of each variant, with `AnyResponse` holding
+/// the typed reply.
+#[cfg(feature = "alloc")]
+impl Command for AnyCommand {
+    type Buffer = Vec<u8>;
+    type Ok = AnyResponse;
+
+    const DELAY_COMMAND_MS: u64 = RawCommand::DELAY_COMMAND_MS;
+    const DELAY_RESPONSE_MS: u64 = RawCommand::DELAY_RESPONSE_MS;
+    const MAX_LEN: usize = RawCommand::MAX_LEN;
+    const MIN_LEN: usize = RawCommand::MIN_LEN;
+
+    fn len(&self) -> usize {
+        dispatch!(self, command => command.len())
+    }
+
+    fn encode(&self, data: &mut [u8]) -> Result<usize, ErrorCode> {
+        dispatch!(self, command => command.encode(data))
+    }
+
+    fn reply_len(&self) -> usize {
+        dispatch!(self, command => command.reply_len())
+    }
+
+    fn delay_response_ms(&self) -> u64 {
+        dispatch!(self, command => command.delay_response_ms())
+    }
+
+    fn delay_command_ms(&self) -> u64 {
+        dispatch!(self, command => command.delay_command_ms())
+    }
+
+    fn decode(&self, data: &[u8]) -> Result<AnyResponse, ErrorCode> {
+        dispatch!(self, command => command.decode(data).map(From::from))
+    }
+}
+
+/// Without the request it replies to, a reply can only be decoded as raw
+/// bytes. `AnyCommand::decode` decodes the typed reply.
+#[cfg(feature = "alloc")]
+impl CommandResult for AnyResponse {
+    type Buffer = Vec<u8>;
+
+    const MAX_LEN: usize = RawResponse::MAX_LEN;
+
+    fn decode(data: &[u8]) -> Result<Self, ErrorCode> {
+        RawResponse::decode(data).map(AnyResponse::Raw)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl From<GetVcpFeature> for AnyCommand {
+    fn from(command: GetVcpFeature) -> Self {
+        AnyCommand::GetVcpFeature { code: command.code }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl From<SetVcpFeature> for AnyCommand {
+    fn from(command: SetVcpFeature) -> Self {
+        AnyCommand::SetVcpFeature {
+            code: command.code,
+            value: command.value,
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl From<SaveCurrentSettings> for AnyCommand {
+    fn from(_: SaveCurrentSettings) -> Self {
+        AnyCommand::SaveCurrentSettings
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl From<TableRead> for AnyCommand {
+    fn from(command: TableRead) -> Self {
+        AnyCommand::TableRead {
+            code: command.code,
+            offset: command.offset,
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<'a> From<TableWrite<'a>> for AnyCommand {
+    fn from(command: TableWrite<'a>) -> Self {
+        AnyCommand::TableWrite {
+            code: command.code,
+            offset: command.offset,
+            data: command.data.to_vec(),
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl From<CapabilitiesRequest> for AnyCommand {
+    fn from(command: CapabilitiesRequest) -> Self {
+        AnyCommand::CapabilitiesRequest { offset: command.offset }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl From<GetTimingReport> for AnyCommand {
+    fn from(_: GetTimingReport) -> Self {
+        AnyCommand::GetTimingReport
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl From<IdentificationRequest> for AnyCommand {
+    fn from(_: IdentificationRequest) -> Self {
+        AnyCommand::IdentificationRequest
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl From<SelfTestRequest> for AnyCommand {
+    fn from(_: SelfTestRequest) -> Self {
+        AnyCommand::SelfTestRequest
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl From<EnableApplicationReport> for AnyCommand {
+    fn from(command: EnableApplicationReport) -> Self {
+        AnyCommand::EnableApplicationReport { enable: command.enable }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl From<()> for AnyResponse {
+    fn from(_: ()) -> Self {
+        AnyResponse::Empty
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl From<VcpValue> for AnyResponse {
+    fn from(value: VcpValue) -> Self {
+        AnyResponse::VcpValue(value)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl From<TableResponse> for AnyResponse {
+    fn from(table: TableResponse) -> Self {
+        AnyResponse::Table {
+            offset: table.offset,
+            data: table.bytes().to_vec(),
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl From<CapabilitiesReply> for AnyResponse {
+    fn from(caps: CapabilitiesReply) -> Self {
+        AnyResponse::Capabilities {
+            offset: caps.offset,
+            data: caps.bytes().to_vec(),
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl From<TimingMessage> for AnyResponse {
+    fn from(timing: TimingMessage) -> Self {
+        AnyResponse::Timing(timing)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl From<IdentificationReply> for AnyResponse {
+    fn from(identification: IdentificationReply) -> Self {
+        AnyResponse::Identification(identification.bytes().to_vec())
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl From<SelfTestReply> for AnyResponse {
+    fn from(self_test: SelfTestReply) -> Self {
+        AnyResponse::SelfTest(self_test.bytes().to_vec())
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl From<RawResponse> for AnyResponse {
+    fn from(raw: RawResponse) -> Self {
+        AnyResponse::Raw(raw)
+    }
+}
+
+/// Formats bytes as space separated hex.
+#[cfg(feature = "alloc")]
+pub(crate) struct Hex<'a>(pub(crate) &'a [u8]);
+
+#[cfg(feature = "alloc")]
+impl<'a> fmt::Display for Hex<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, b) in self.0.iter().enumerate() {
+            if i > 0 {
+                f.write_str(" ")?;
+            }
+            write!(f, "{:02x}", b)?;
+        }
+        Ok(())
+    }
+}
+
+/// Formats bytes as a string, escaping anything that isn't printable ASCII.
+#[cfg(feature = "alloc")]
+struct Escaped<'a>(&'a [u8]);
+
+#[cfg(feature = "alloc")]
+impl<'a> fmt::Display for Escaped<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.0.iter().try_for_each(|&b| write!(f, "{}", b.escape_ascii()))
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl fmt::Display for AnyCommand {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            AnyCommand::GetVcpFeature { code } => write!(f, "get VCP feature 0x{:02x}", code),
+            AnyCommand::SetVcpFeature { code, value } => write!(f, "set VCP feature 0x{:02x} to {}", code, value),
+            AnyCommand::SaveCurrentSettings => f.write_str("save current settings"),
+            AnyCommand::TableRead { code, offset } => write!(f, "read table 0x{:02x} at offset {}", code, offset),
+            AnyCommand::TableWrite { code, offset, ref data } =>
+                write!(f, "write table 0x{:02x} at offset {}: {}", code, offset, Hex(data)),
+            AnyCommand::CapabilitiesRequest { offset } => write!(f, "capabilities request at offset {}", offset),
+            AnyCommand::GetTimingReport => f.write_str("get timing report"),
+            AnyCommand::IdentificationRequest => f.write_str("identification request"),
+            AnyCommand::SelfTestRequest => f.write_str("self-test request"),
+            AnyCommand::EnableApplicationReport { enable } =>
+                write!(f, "{} application report", if enable { "enable" } else { "disable" }),
+            AnyCommand::Raw(ref raw) => write!(f, "command {}", Hex(&raw.data)),
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl fmt::Display for AnyResponse {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            AnyResponse::Empty => f.write_str("no reply"),
+            AnyResponse::VcpValue(ref value) => write!(
+                f,
+                "VCP value {} of {} (type 0x{:02x})",
+                value.value(),
+                value.maximum(),
+                value.ty
+            ),
+            AnyResponse::Table { offset, ref data } => write!(f, "table at offset {}: {}", offset, Hex(data)),
+            AnyResponse::Capabilities { offset, ref data } =>
+                write!(f, "capabilities at offset {}: \"{}\"", offset, Escaped(data)),
+            AnyResponse::Timing(ref timing) => write!(
+                f,
+                "timing status 0x{:02x}, horizontal {}, vertical {}",
+                timing.timing_status, timing.horizontal_frequency, timing.vertical_frequency
+            ),
+            AnyResponse::Identification(ref data) => write!(f, "identification: {}", Hex(data)),
+            AnyResponse::SelfTest(ref data) => write!(f, "self-test: {}", Hex(data)),
+            AnyResponse::Raw(ref raw) => write!(f, "reply {}", Hex(&raw.0)),
+        }
+    }
 }
@@ -0,0 +1,493 @@
+#[cfg(feature = "std")]
+use std::error;
+use {
+    self::{
+        Access::{ReadOnly as RO, ReadWrite as RW, WriteOnly as WO},
+        FeatureType::{Continuous as C, NonContinuous as NC},
+    },
+    crate::{
+        caps::{Capabilities, Version},
+        features::{ColorPreset, InputSource, Mute, PowerMode},
+        FeatureCode, VcpValue,
+    },
+    alloc::{collections::BTreeMap, string::String, vec::Vec},
+    core::fmt,
+};
+
+/// How a VCP feature may be accessed.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Access {
+    /// The feature can only be read.
+    ReadOnly,
+    /// The feature can only be written.
+    WriteOnly,
+    /// The feature can be read and written.
+    ReadWrite,
+}
+
+impl Access {
+    /// Whether the feature can be read.
+    pub fn readable(&self) -> bool {
+        *self != Access::WriteOnly
+    }
+
+    /// Whether the feature can be written.
+    pub fn writable(&self) -> bool {
+        *self != Access::ReadOnly
+    }
+}
+
+/// The kind of value a VCP feature holds.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum FeatureType {
+    /// A value between zero and a maximum reported by the display.
+    Continuous,
+    /// One of a set of enumerated values.
+    NonContinuous,
+    /// Arbitrary binary data accessed with table commands.
+    Table,
+}
+
+/// How the value of a VCP feature is presented.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ValueFormat {
+    /// The value and maximum of a continuous feature, or the name of a
+    /// non-continuous value.
+    Standard,
+    /// A version number, with the major version in the SH byte and the minor
+    /// version in the SL byte.
+    Version,
+}
+
+/// The MCCS specification's definition of a VCP feature.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct FeatureDefinition {
+    /// The VCP feature code.
+    pub code: FeatureCode,
+    /// The name of the feature.
+    pub name: &'static str,
+    /// How the feature may be accessed.
+    pub access: Access,
+    /// The kind of value the feature holds.
+    pub ty: FeatureType,
+    /// The first MCCS version that defines the feature.
+    pub since: Version,
+    /// The names of non-continuous values, or of the special values of a
+    /// continuous feature.
+    pub values: &'static [(u8, &'static str)],
+    /// How the value is presented.
+    pub format: ValueFormat,
+}
+
+impl FeatureDefinition {
+    /// The name of a non-continuous value.
+    pub fn value_name(&self, value: u8) -> Option<&'static str> {
+        self.values.iter().find(|&&(v, _)| v == value).map(|&(_, name)| name)
+    }
+}
+
+const V2_0: Version = Version { major: 2, minor: 0 };
+const V2_2: Version = Version { major: 2, minor: 2 };
+const V3_0: Version = Version { major: 3, minor: 0 };
+
+const fn feature(
+    code: FeatureCode,
+    name: &'static str,
+    access: Access,
+    ty: FeatureType,
+    values: &'static [(u8, &'static str)],
+) -> FeatureDefinition {
+    FeatureDefinition {
+        code,
+        name,
+        access,
+        ty,
+        since: V2_0,
+        values,
+        format: ValueFormat::Standard,
+    }
+}
+
+const fn table(code: FeatureCode, name: &'static str, access: Access) -> FeatureDefinition {
+    FeatureDefinition {
+        code,
+        name,
+        access,
+        ty: FeatureType::Table,
+        since: V2_2,
+        values: &[],
+        format: ValueFormat::Standard,
+    }
+}
+
+/// The VCP features defined by the MCCS specification, sorted by code.
+pub static FEATURES: &[FeatureDefinition] = &[
+    feature(0x01, "Degauss", WO, NC, &[]),
+    feature(0x02, "New Control Value", RW, NC, &[
+        (0x01, "No new control values"),
+        (0x02, "New control values"),
+        (0xff, "No user controls present"),
+    ]),
+    feature(0x03, "Soft Controls", RW, NC, &[]),
+    feature(0x04, "Restore Factory Defaults", WO, NC, &[]),
+    feature(0x05, "Restore Factory Brightness/Contrast", WO, NC, &[]),
+    feature(0x06, "Restore Factory Geometry", WO, NC, &[]),
+    feature(0x08, "Restore Factory Color", WO, NC, &[]),
+    feature(0x0a, "Restore Factory TV Defaults", WO, NC, &[]),
+    feature(0x0b, "Color Temperature Increment", RO, C, &[]),
+    feature(0x0c, "Color Temperature Request", RW, C, &[]),
+    feature(0x0e, "Clock", RW, C, &[]),
+    feature(0x10, "Brightness", RW, C, &[]),
+    feature(0x12, "Contrast", RW, C, &[]),
+    feature(0x13, "Backlight Control", RW, C, &[]),
+    feature(0x14, "Select Color Preset", RW, NC, ColorPreset::VALUES),
+    feature(0x16, "Video Gain: Red", RW, C, &[]),
+    feature(0x18, "Video Gain: Green", RW, C, &[]),
+    feature(0x1a, "Video Gain: Blue", RW, C, &[]),
+    feature(0x1e, "Auto Setup", RW, NC, &[
+        (0x00, "Not applicable"),
+        (0x01, "Perform auto setup"),
+        (0x02, "Enable continuous auto setup"),
+    ]),
+    feature(0x1f, "Auto Color Setup", RW, NC, &[
+        (0x00, "Not applicable"),
+        (0x01, "Perform auto color setup"),
+        (0x02, "Enable continuous auto color setup"),
+    ]),
+    feature(0x20, "Horizontal Position", RW, C, &[]),
+    feature(0x22, "Horizontal Size", RW, C, &[]),
+    feature(0x30, "Vertical Position", RW, C, &[]),
+    feature(0x32, "Vertical Size", RW, C, &[]),
+    feature(0x52, "Active Control", RO, NC, &[]),
+    feature(0x60, "Input Source", RW, NC, InputSource::VALUES),
+    feature(0x62, "Audio Speaker Volume", RW, C, &[]),
+    feature(0x6c, "Video Black Level: Red", RW, C, &[]),
+    feature(0x6e, "Video Black Level: Green", RW, C, &[]),
+    feature(0x70, "Video Black Level: Blue", RW, C, &[]),
+    feature(0x72, "Gamma", RW, NC, &[]),
+    table(0x73, "LUT Size", RO),
+    table(0x74, "Single Point LUT Operation", RW),
+    table(0x75, "Block LUT Operation", RW),
+    feature(0x87, "Sharpness", RW, C, &[]),
+    feature(0x8d, "Audio Mute", RW, NC, Mute::VALUES),
+    feature(0xaa, "Screen Orientation", RO, NC, &[
+        (0x01, "0 degrees"),
+        (0x02, "90 degrees"),
+        (0x03, "180 degrees"),
+        (0x04, "270 degrees"),
+        (0xff, "Not applicable"),
+    ]),
+    feature(0xac, "Horizontal Frequency", RO, C, &[]),
+    feature(0xae, "Vertical Frequency", RO, C, &[]),
+    feature(0xb0, "Settings", WO, NC, &[
+        (0x01, "Store current settings"),
+        (0x02, "Restore factory defaults"),
+    ]),
+    feature(0xb2, "Flat Panel Sub-Pixel Layout", RO, NC, &[
+        (0x00, "Undefined"),
+        (0x01, "RGB vertical stripe"),
+        (0x02, "RGB horizontal stripe"),
+        (0x03, "BGR vertical stripe"),
+        (0x04, "BGR horizontal stripe"),
+        (0x05, "Quad pixel, red top left"),
+        (0x06, "Quad pixel, red bottom left"),
+        (0x07, "Delta"),
+        (0x08, "Mosaic"),
+    ]),
+    feature(0xb6, "Display Technology Type", RO, NC, &[
+        (0x01, "CRT (shadow mask)"),
+        (0x02, "CRT (aperture grill)"),
+        (0x03, "LCD (active matrix)"),
+        (0x04, "LCoS"),
+        (0x05, "Plasma"),
+        (0x06, "OLED"),
+        (0x07, "EL"),
+        (0x08, "Dynamic MEM"),
+        (0x09, "Static MEM"),
+    ]),
+    feature(0xc0, "Display Usage Time", RO, C, &[]),
+    feature(0xc6, "Application Enable Key", RO, C, &[]),
+    feature(0xc8, "Display Controller Type", RO, NC, &[]),
+    feature(0xc9, "Display Firmware Level", RO, C, &[]),
+    feature(0xca, "OSD", RW, NC, &[(0x01, "OSD disabled"), (0x02, "OSD enabled")]),
+    feature(0xcc, "OSD Language", RW, NC, &[
+        (0x01, "Chinese (traditional)"),
+        (0x02, "English"),
+        (0x03, "French"),
+        (0x04, "German"),
+        (0x05, "Italian"),
+        (0x06, "Japanese"),
+        (0x07, "Korean"),
+        (0x08, "Portuguese (Portugal)"),
+        (0x09, "Russian"),
+        (0x0a, "Spanish"),
+    ]),
+    feature(0xd6, "Power Mode", RW, NC, PowerMode::VALUES),
+    feature(0xdc, "Display Application", RW, NC, &[
+        (0x00, "Standard"),
+        (0x01, "Productivity"),
+        (0x02, "Mixed"),
+        (0x03, "Movie"),
+        (0x04, "User defined"),
+        (0x05, "Games"),
+        (0x06, "Sports"),
+        (0x07, "Professional"),
+        (0x08, "Standard, intermediate power"),
+        (0x09, "Standard, low power"),
+        (0x0a, "Demonstration"),
+        (0xf0, "Dynamic contrast"),
+    ]),
+    FeatureDefinition {
+        format: ValueFormat::Version,
+        ..feature(0xdf, "VCP Version", RO, NC, &[])
+    },
+];
+
+/// The VCP features that MCCS 3.0 defines differently, sorted by code.
+///
+/// MCCS 3.0 isn't a superset of the 2.x versions, and 2.2 was published after
+/// it, so these only apply to displays reporting version 3.0.
+pub static FEATURES_V3: &[FeatureDefinition] = &[FeatureDefinition {
+    since: V3_0,
+    ..feature(0x62, "Audio Speaker Volume", RW, C, &[
+        (0x00, "Fixed (default) level"),
+        (0xff, "Mute"),
+    ])
+}];
+
+/// Looks up the MCCS definition of a VCP feature.
+pub fn definition(code: FeatureCode) -> Option<&'static FeatureDefinition> {
+    find(FEATURES, code)
+}
+
+/// Looks up the definition of a VCP feature in a specific MCCS version,
+/// including the features that version redefines.
+pub fn versioned_definition(code: FeatureCode, version: Version) -> Option<&'static FeatureDefinition> {
+    match version.major {
+        3 => find(FEATURES_V3, code).or_else(|| definition(code)),
+        _ => definition(code),
+    }
+    .filter(|definition| definition.since <= version)
+}
+
+fn find(features: &'static [FeatureDefinition], code: FeatureCode) -> Option<&'static FeatureDefinition> {
+    features
+        .binary_search_by_key(&code, |feature| feature.code)
+        .ok()
+        .map(|i| &features[i])
+}
+
+/// A VCP feature supported by a display.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Feature {
+    /// The VCP feature code.
+    pub code: FeatureCode,
+    /// The MCCS definition of the feature, if known.
+    pub definition: Option<&'static FeatureDefinition>,
+    /// The name reported by the display's capabilities, if any.
+    pub display_name: Option<String>,
+    /// The allowed non-continuous values reported by the display's
+    /// capabilities.
+    pub values: Vec<u8>,
+}
+
+impl Feature {
+    fn new(code: FeatureCode, definition: Option<&'static FeatureDefinition>) -> Self {
+        Feature {
+            code,
+            definition,
+            display_name: None,
+            values: Vec::new(),
+        }
+    }
+
+    /// The name of the feature.
+    pub fn name(&self) -> Option<&str> {
+        self.display_name
+            .as_deref()
+            .or_else(|| self.definition.map(|definition| definition.name))
+    }
+
+    /// How the feature may be accessed, assuming read-write when unknown.
+    pub fn access(&self) -> Access {
+        self.definition
+            .map(|definition| definition.access)
+            .unwrap_or(Access::ReadWrite)
+    }
+
+    /// The kind of value the feature holds, if known.
+    pub fn ty(&self) -> Option<FeatureType> {
+        match self.definition {
+            Some(definition) => Some(definition.ty),
+            None if !self.values.is_empty() => Some(FeatureType::NonContinuous),
+            None => None,
+        }
+    }
+
+    /// The name of a non-continuous value.
+    pub fn value_name(&self, value: u8) -> Option<&'static str> {
+        self.definition.and_then(|definition| definition.value_name(value))
+    }
+
+    /// How the value is presented.
+    pub fn format(&self) -> ValueFormat {
+        self.definition
+            .map(|definition| definition.format)
+            .unwrap_or(ValueFormat::Standard)
+    }
+
+    /// Whether the feature is manufacturer specific.
+    pub fn is_manufacturer_specific(&self) -> bool {
+        self.code >= 0xe0
+    }
+}
+
+/// An error describing why a value can't be written to a VCP feature.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ValidationError {
+    /// The display does not support the feature.
+    UnsupportedFeature,
+    /// The feature cannot be written.
+    ReadOnly,
+    /// The feature is a table, and must be written with table commands.
+    Table,
+    /// The value is not one of the feature's allowed values.
+    InvalidValue,
+    /// The value exceeds the feature's maximum.
+    OutOfRange {
+        /// The maximum value reported by the display.
+        maximum: u16,
+    },
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ValidationError::UnsupportedFeature => f.write_str("VCP feature not supported"),
+            ValidationError::ReadOnly => f.write_str("VCP feature is read-only"),
+            ValidationError::Table => f.write_str("VCP feature is a table"),
+            ValidationError::InvalidValue => f.write_str("value not allowed for VCP feature"),
+            ValidationError::OutOfRange { maximum } => write!(f, "value exceeds VCP feature maximum of {}", maximum),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl error::Error for ValidationError {}
+
+/// The VCP features of a display, combining its capabilities and MCCS
+/// version with the feature definitions of the specification.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Database {
+    /// The MCCS version used to select feature definitions.
+    pub version: Option<Version>,
+    features: BTreeMap<FeatureCode, Feature>,
+}
+
+impl Database {
+    /// Creates a database containing every feature defined by the given MCCS
+    /// version, or by any version if unspecified.
+    pub fn new(version: Option<Version>) -> Self {
+        Database {
+            version,
+            features: FEATURES
+                .iter()
+                .filter_map(|definition| match version {
+                    Some(version) => versioned_definition(definition.code, version),
+                    None => Some(definition),
+                })
+                .map(|definition| (definition.code, Feature::new(definition.code, Some(definition))))
+                .collect(),
+        }
+    }
+
+    /// Creates a database of the features listed in a display's
+    /// capabilities.
+    pub fn from_capabilities(caps: &Capabilities) -> Self {
+        let version = caps.mccs_version;
+        let features = caps
+            .vcp_features
+            .iter()
+            .map(|(&code, descriptor)| {
+                let definition = match version {
+                    Some(version) => versioned_definition(code, version),
+                    None => definition(code),
+                };
+                let feature = Feature {
+                    display_name: descriptor.name.clone(),
+                    values: descriptor.values.clone(),
+                    ..Feature::new(code, definition)
+                };
+                (code, feature)
+            })
+            .collect();
+
+        Database { version, features }
+    }
+
+    /// Looks up a supported feature.
+    pub fn get(&self, code: FeatureCode) -> Option<&Feature> {
+        self.features.get(&code)
+    }
+
+    /// Iterates over all supported features in order.
+    pub fn features(&self) -> impl Iterator<Item = &Feature> {
+        self.features.values()
+    }
+
+    /// Describes a feature's value in human-readable form, such as
+    /// `Input Source: DisplayPort-1 (0x0f)`.
+    pub fn format(&self, code: FeatureCode, value: &VcpValue) -> String {
+        let feature = self.get(code);
+        let mut out = match feature.and_then(Feature::name) {
+            Some(name) => String::from(name),
+            None => format!("VCP 0x{:02x}", code),
+        };
+
+        let value_name = feature.and_then(|feature| feature.value_name(value.sl));
+        match feature.and_then(Feature::ty) {
+            _ if feature.map(Feature::format) == Some(ValueFormat::Version) =>
+                out += &format!(": {}.{}", value.sh, value.sl),
+            Some(FeatureType::Continuous) => match value_name {
+                Some(name) if value.sh == 0 => out += &format!(": {} (0x{:02x})", name, value.sl),
+                _ => out += &format!(": {} / {}", value.value(), value.maximum()),
+            },
+            Some(FeatureType::NonContinuous) => match value_name {
+                Some(name) => out += &format!(": {} (0x{:02x})", name, value.sl),
+                None => out += &format!(": 0x{:02x}", value.sl),
+            },
+            Some(FeatureType::Table) => out += ": table",
+            None => out += &format!(": 0x{:04x}", value.value()),
+        }
+
+        out
+    }
+
+    /// Checks whether a value may be written to a feature with
+    /// `Ddc::set_vcp_feature`.
+    ///
+    /// The maximum of continuous features is only checked if the `current`
+    /// value is provided.
+    pub fn validate(&self, code: FeatureCode, value: u16, current: Option<&VcpValue>) -> Result<(), ValidationError> {
+        let feature = self.get(code).ok_or(ValidationError::UnsupportedFeature)?;
+        if !feature.access().writable() {
+            return Err(ValidationError::ReadOnly)
+        }
+
+        match feature.ty() {
+            Some(FeatureType::Table) => Err(ValidationError::Table),
+            Some(FeatureType::Continuous) => match current {
+                Some(current) if value > current.maximum() => Err(ValidationError::OutOfRange {
+                    maximum: current.maximum(),
+                }),
+                _ => Ok(()),
+            },
+            Some(FeatureType::NonContinuous) if !feature.values.is_empty() =>
+                match value <= 0xff && feature.values.contains(&(value as u8)) {
+                    true => Ok(()),
+                    false => Err(ValidationError::InvalidValue),
+                },
+            _ => Ok(()),
+        }
+    }
+}
@@ -1,12 +1,41 @@
-use std::{
-    thread::sleep,
-    time::{Duration, Instant},
-};
+use core::time::Duration;
+#[cfg(feature = "std")]
+use std::{thread, time::Instant};
+
+/// A source of blocking delays.
+pub trait Timer {
+    /// Blocks for the specified duration.
+    fn sleep(&mut self, duration: Duration);
+}
+
+impl<T: Timer + ?Sized> Timer for &mut T {
+    fn sleep(&mut self, duration: Duration) {
+        (**self).sleep(duration)
+    }
+}
+
+/// A `Timer` that sleeps the current thread.
+#[cfg(feature = "std")]
+#[derive(Copy, Clone, Debug, Default)]
+pub struct StdTimer;
+
+#[cfg(feature = "std")]
+impl Timer for StdTimer {
+    fn sleep(&mut self, duration: Duration) {
+        thread::sleep(duration)
+    }
+}
 
 /// A type that can help with implementing the DDC specification delays.
-#[derive(Clone, Debug)]
+///
+/// Without `std` there is no clock to measure elapsed time with, so the
+/// entire delay is waited out.
+#[derive(Clone, Debug, Default)]
 pub struct Delay {
+    #[cfg(feature = "std")]
     time: Option<Instant>,
+    #[cfg(not(feature = "std"))]
+    pending: bool,
     delay: Duration,
 }
 
@@ -14,32 +43,44 @@ impl Delay {
     /// Creates a new delay starting now.
     pub fn new(delay: Duration) -> Self {
         Delay {
+            #[cfg(feature = "std")]
             time: Some(Instant::now()),
+            #[cfg(not(feature = "std"))]
+            pending: true,
             delay,
         }
     }
 
     /// The time remaining in this delay.
+    #[cfg(feature = "std")]
     pub fn remaining(&self) -> Duration {
         self.time
             .as_ref()
             .and_then(|time| self.delay.checked_sub(time.elapsed()))
-            .unwrap_or(Duration::default())
+            .unwrap_or_default()
+    }
+
+    /// The time remaining in this delay.
+    #[cfg(not(feature = "std"))]
+    pub fn remaining(&self) -> Duration {
+        match self.pending {
+            true => self.delay,
+            false => Duration::default(),
+        }
     }
 
     /// Waits out the remaining time in this delay.
+    #[cfg(feature = "std")]
     pub fn sleep(&mut self) {
-        if let Some(delay) = self.time.take().and_then(|time| self.delay.checked_sub(time.elapsed())) {
-            sleep(delay);
-        }
+        self.sleep_with(&mut StdTimer)
     }
-}
 
-impl Default for Delay {
-    fn default() -> Self {
-        Delay {
-            time: None,
-            delay: Default::default(),
+    /// Waits out the remaining time in this delay using the provided timer.
+    pub fn sleep_with<T: Timer + ?Sized>(&mut self, timer: &mut T) {
+        let remaining = self.remaining();
+        *self = Default::default();
+        if remaining > Duration::default() {
+            timer.sleep(remaining);
         }
     }
 }
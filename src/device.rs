@@ -0,0 +1,183 @@
+use {
+    crate::{caps, commands, edid, Ddc, DdcTable, Edid, ErrorCode, FeatureCode, TimingMessage, VcpValue},
+    std::error,
+};
+
+/// The error type returned by `DdcDevice`, erasing the backend's own error.
+pub type DeviceError = Box<dyn error::Error + Send + Sync>;
+
+/// An object-safe interface to a display, allowing displays from different
+/// backends to be used together as `dyn DdcDevice`.
+///
+/// It is implemented for every type that implements `Ddc`, `DdcTable` and
+/// `Edid`, and for references to and boxes of `dyn DdcDevice`.
+pub trait DdcDevice {
+    /// Retrieve the capability string from the device.
+    fn capabilities_string(&mut self) -> Result<Vec<u8>, DeviceError>;
+
+    /// Retrieve and parse the capability string from the device.
+    fn capabilities(&mut self) -> Result<caps::Capabilities, DeviceError> {
+        let caps = self.capabilities_string()?;
+        caps::Capabilities::parse(&caps).map_err(|e| ErrorCode::InvalidCapabilities(e).into())
+    }
+
+    /// Gets the current value of an MCCS VCP feature.
+    fn get_vcp_feature(&mut self, code: FeatureCode) -> Result<VcpValue, DeviceError>;
+
+    /// Sets a VCP feature to the specified value.
+    fn set_vcp_feature(&mut self, code: FeatureCode, value: u16) -> Result<(), DeviceError>;
+
+    /// Instructs the device to save its current settings.
+    fn save_current_settings(&mut self) -> Result<(), DeviceError>;
+
+    /// Retrieves a timing report from the device.
+    fn get_timing_report(&mut self) -> Result<TimingMessage, DeviceError>;
+
+    /// Requests the device's identification data.
+    fn identification(&mut self) -> Result<commands::IdentificationReply, DeviceError>;
+
+    /// Instructs the device to run its self-test, returning the result.
+    fn self_test(&mut self) -> Result<commands::SelfTestReply, DeviceError>;
+
+    /// Enables or disables application reports from the device.
+    fn enable_application_report(&mut self, enable: bool) -> Result<(), DeviceError>;
+
+    /// Read a table value from the device.
+    fn table_read(&mut self, code: FeatureCode) -> Result<Vec<u8>, DeviceError>;
+
+    /// Write a table value to the device.
+    fn table_write(&mut self, code: FeatureCode, offset: u16, value: &[u8]) -> Result<(), DeviceError>;
+
+    /// Read up to 256 bytes of the monitor's EDID.
+    fn read_edid(&mut self, offset: u8, data: &mut [u8]) -> Result<usize, DeviceError>;
+
+    /// Read and validate the EDID base block and its first extension block.
+    fn read_edid_blocks(&mut self) -> Result<Vec<u8>, edid::EdidReadError<DeviceError>> {
+        edid::read_blocks(2, |block, offset, data| {
+            self.read_edid((block * edid::BLOCK_LEN + offset) as u8, data)
+        })
+    }
+}
+
+impl<D: Ddc + DdcTable + Edid> DdcDevice for D
+where
+    D::Error: From<ErrorCode> + Into<DeviceError>,
+    D::EdidError: Into<DeviceError>,
+{
+    fn capabilities_string(&mut self) -> Result<Vec<u8>, DeviceError> {
+        Ddc::capabilities_string(self).map_err(Into::into)
+    }
+
+    fn capabilities(&mut self) -> Result<caps::Capabilities, DeviceError> {
+        Ddc::capabilities(self).map_err(Into::into)
+    }
+
+    fn get_vcp_feature(&mut self, code: FeatureCode) -> Result<VcpValue, DeviceError> {
+        Ddc::get_vcp_feature(self, code).map_err(Into::into)
+    }
+
+    fn set_vcp_feature(&mut self, code: FeatureCode, value: u16) -> Result<(), DeviceError> {
+        Ddc::set_vcp_feature(self, code, value).map_err(Into::into)
+    }
+
+    fn save_current_settings(&mut self) -> Result<(), DeviceError> {
+        Ddc::save_current_settings(self).map_err(Into::into)
+    }
+
+    fn get_timing_report(&mut self) -> Result<TimingMessage, DeviceError> {
+        Ddc::get_timing_report(self).map_err(Into::into)
+    }
+
+    fn identification(&mut self) -> Result<commands::IdentificationReply, DeviceError> {
+        Ddc::identification(self).map_err(Into::into)
+    }
+
+    fn self_test(&mut self) -> Result<commands::SelfTestReply, DeviceError> {
+        Ddc::self_test(self).map_err(Into::into)
+    }
+
+    fn enable_application_report(&mut self, enable: bool) -> Result<(), DeviceError> {
+        Ddc::enable_application_report(self, enable).map_err(Into::into)
+    }
+
+    fn table_read(&mut self, code: FeatureCode) -> Result<Vec<u8>, DeviceError> {
+        DdcTable::table_read(self, code).map_err(Into::into)
+    }
+
+    fn table_write(&mut self, code: FeatureCode, offset: u16, value: &[u8]) -> Result<(), DeviceError> {
+        DdcTable::table_write(self, code, offset, value).map_err(Into::into)
+    }
+
+    fn read_edid(&mut self, offset: u8, data: &mut [u8]) -> Result<usize, DeviceError> {
+        Edid::read_edid(self, offset, data).map_err(Into::into)
+    }
+}
+
+macro_rules! forward_device {
+    ($($ty:ty,)*) => {
+        $(
+            impl<'a> DdcDevice for $ty {
+                fn capabilities_string(&mut self) -> Result<Vec<u8>, DeviceError> {
+                    (**self).capabilities_string()
+                }
+
+                fn capabilities(&mut self) -> Result<caps::Capabilities, DeviceError> {
+                    (**self).capabilities()
+                }
+
+                fn get_vcp_feature(&mut self, code: FeatureCode) -> Result<VcpValue, DeviceError> {
+                    (**self).get_vcp_feature(code)
+                }
+
+                fn set_vcp_feature(&mut self, code: FeatureCode, value: u16) -> Result<(), DeviceError> {
+                    (**self).set_vcp_feature(code, value)
+                }
+
+                fn save_current_settings(&mut self) -> Result<(), DeviceError> {
+                    (**self).save_current_settings()
+                }
+
+                fn get_timing_report(&mut self) -> Result<TimingMessage, DeviceError> {
+                    (**self).get_timing_report()
+                }
+
+                fn identification(&mut self) -> Result<commands::IdentificationReply, DeviceError> {
+                    (**self).identification()
+                }
+
+                fn self_test(&mut self) -> Result<commands::SelfTestReply, DeviceError> {
+                    (**self).self_test()
+                }
+
+                fn enable_application_report(&mut self, enable: bool) -> Result<(), DeviceError> {
+                    (**self).enable_application_report(enable)
+                }
+
+                fn table_read(&mut self, code: FeatureCode) -> Result<Vec<u8>, DeviceError> {
+                    (**self).table_read(code)
+                }
+
+                fn table_write(&mut self, code: FeatureCode, offset: u16, value: &[u8]) -> Result<(), DeviceError> {
+                    (**self).table_write(code, offset, value)
+                }
+
+                fn read_edid(&mut self, offset: u8, data: &mut [u8]) -> Result<usize, DeviceError> {
+                    (**self).read_edid(offset, data)
+                }
+
+                fn read_edid_blocks(&mut self) -> Result<Vec<u8>, edid::EdidReadError<DeviceError>> {
+                    (**self).read_edid_blocks()
+                }
+            }
+        )*
+    };
+}
+
+// a blanket impl for `&mut T` and `Box<T>` would overlap with the one above,
+// so only trait objects are forwarded
+forward_device! {
+    &mut (dyn DdcDevice + 'a),
+    &mut (dyn DdcDevice + Send + 'a),
+    Box<dyn DdcDevice + 'a>,
+    Box<dyn DdcDevice + Send + 'a>,
+}
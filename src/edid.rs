@@ -0,0 +1,691 @@
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+use core::fmt;
+#[cfg(feature = "std")]
+use std::error;
+
+/// CTA-861 extension blocks, used by HDMI and most consumer displays.
+pub mod cta;
+/// DisplayID extension blocks, used by DisplayPort monitors for modes that
+/// don't fit in a detailed timing descriptor.
+pub mod displayid;
+
+/// The size of each EDID block.
+pub const BLOCK_LEN: usize = 128;
+
+/// The fixed pattern that begins every EDID base block.
+pub const HEADER: [u8; 8] = [0x00, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x00];
+
+/// The offset of the extension block count within the base block.
+pub const EXTENSION_COUNT_OFFSET: usize = 126;
+
+/// An error that can occur when reading a complete EDID.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EdidReadError<E> {
+    /// The underlying transport failed.
+    Io(E),
+    /// The base block does not begin with the EDID header.
+    InvalidHeader,
+    /// A block's checksum is invalid.
+    InvalidChecksum {
+        /// The index of the corrupt block.
+        block: usize,
+    },
+    /// The device stopped returning data partway through a block.
+    Truncated {
+        /// The index of the incomplete block.
+        block: usize,
+        /// The number of bytes of the block that were read.
+        len: usize,
+    },
+}
+
+impl<E: fmt::Display> fmt::Display for EdidReadError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            EdidReadError::Io(ref e) => write!(f, "EDID read failed: {}", e),
+            EdidReadError::InvalidHeader => f.write_str("invalid EDID header"),
+            EdidReadError::InvalidChecksum { block } => write!(f, "EDID block {} checksum mismatch", block),
+            EdidReadError::Truncated { block, len } => write!(f, "EDID block {} truncated after {} bytes", block, len),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<E: error::Error + 'static> error::Error for EdidReadError<E> {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            EdidReadError::Io(ref e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+/// Checks that an EDID block sums to zero.
+pub fn checksum_valid(block: &[u8]) -> bool {
+    block.iter().fold(0u8, |sum, &v| sum.wrapping_add(v)) == 0
+}
+
+/// Reads and validates an EDID, following the extension count of the base
+/// block up to a total of `max_blocks` blocks.
+///
+/// `read` is given a block index, an offset into the block, and a buffer to
+/// fill.
+#[cfg(feature = "alloc")]
+pub(crate) fn read_blocks<E, F>(max_blocks: usize, mut read: F) -> Result<Vec<u8>, EdidReadError<E>>
+where
+    F: FnMut(usize, usize, &mut [u8]) -> Result<usize, E>,
+{
+    let mut edid = Vec::with_capacity(BLOCK_LEN);
+    let mut blocks = 1;
+    let mut block = 0;
+    while block < blocks {
+        let start = edid.len();
+        edid.resize(start + BLOCK_LEN, 0);
+        let mut len = 0;
+        while len < BLOCK_LEN {
+            match read(block, len, &mut edid[start + len..]).map_err(EdidReadError::Io)? {
+                0 => return Err(EdidReadError::Truncated { block, len }),
+                read => len += read,
+            }
+        }
+
+        let data = &edid[start..];
+        if block == 0 {
+            if data[..HEADER.len()] != HEADER {
+                return Err(EdidReadError::InvalidHeader)
+            }
+            blocks = 1 + data[EXTENSION_COUNT_OFFSET] as usize;
+        }
+
+        if !checksum_valid(data) {
+            return Err(EdidReadError::InvalidChecksum { block })
+        }
+
+        blocks = blocks.min(max_blocks);
+        block += 1;
+    }
+
+    Ok(edid)
+}
+
+/// An error that can occur when parsing EDID data.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ParseError {
+    /// The data is too short to contain a block.
+    InvalidLength,
+    /// The base block does not begin with the EDID header.
+    InvalidHeader,
+    /// The block's checksum is invalid.
+    InvalidChecksum,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match *self {
+            ParseError::InvalidLength => "EDID block too short",
+            ParseError::InvalidHeader => "invalid EDID header",
+            ParseError::InvalidChecksum => "EDID block checksum mismatch",
+        })
+    }
+}
+
+#[cfg(feature = "std")]
+impl error::Error for ParseError {}
+
+/// A parsed EDID extension block.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Extension<'a> {
+    /// A CTA-861 extension.
+    Cta(cta::CtaExtension<'a>),
+    /// A DisplayID extension.
+    DisplayId(displayid::DisplayIdExtension<'a>),
+    /// An extension type that isn't understood by this crate.
+    Other {
+        /// The extension tag.
+        tag: u8,
+        /// The raw 128 byte block.
+        data: &'a [u8],
+    },
+}
+
+impl<'a> Extension<'a> {
+    /// Parses and validates a 128 byte extension block.
+    pub fn parse(data: &'a [u8]) -> Result<Self, ParseError> {
+        match data.first() {
+            Some(&cta::TAG) => cta::CtaExtension::parse(data).map(Extension::Cta),
+            Some(&displayid::TAG) => displayid::DisplayIdExtension::parse(data).map(Extension::DisplayId),
+            _ if data.len() < BLOCK_LEN => Err(ParseError::InvalidLength),
+            _ if !checksum_valid(&data[..BLOCK_LEN]) => Err(ParseError::InvalidChecksum),
+            _ => Ok(Extension::Other {
+                tag: data[0],
+                data: &data[..BLOCK_LEN],
+            }),
+        }
+    }
+}
+
+/// Iterates over the extension blocks following the base block of a
+/// complete EDID, such as one returned by `Eddc::read_full_edid`.
+pub fn extensions(edid: &[u8]) -> impl Iterator<Item = Result<Extension<'_>, ParseError>> {
+    edid.chunks(BLOCK_LEN).skip(1).map(Extension::parse)
+}
+
+/// The first 128 byte block of an EDID.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BaseBlock {
+    /// The manufacturer's three letter PNP ID.
+    pub manufacturer_id: [u8; 3],
+    /// The manufacturer's product code.
+    pub product_code: u16,
+    /// The numeric serial number, or zero if unused.
+    pub serial_number: u32,
+    /// The week of manufacture, `0xff` if `year` is a model year instead.
+    pub week: u8,
+    /// The year of manufacture or model year.
+    pub year: u16,
+    /// EDID version number.
+    pub version: u8,
+    /// EDID revision number.
+    pub revision: u8,
+    /// The video input definition bitmap.
+    pub video_input: u8,
+    /// The horizontal screen size in centimeters, or zero if unknown.
+    pub width_cm: u8,
+    /// The vertical screen size in centimeters, or zero if unknown.
+    pub height_cm: u8,
+    /// The display transfer characteristic, stored as `(gamma * 100) - 100`.
+    ///
+    /// `0xff` indicates that the gamma is defined in an extension block.
+    pub gamma: u8,
+    /// The feature support bitmap.
+    pub features: u8,
+    /// The display's color characteristics.
+    pub chromaticity: Chromaticity,
+    /// Supported established timings.
+    pub established_timings: EstablishedTimings,
+    /// Supported standard timings.
+    pub standard_timings: [Option<StandardTiming>; 8],
+    /// The four 18 byte descriptors.
+    pub descriptors: [Descriptor; 4],
+    /// The number of extension blocks that follow.
+    pub extension_count: u8,
+}
+
+impl BaseBlock {
+    /// Parses and validates the base block from the first 128 bytes of
+    /// `data`.
+    pub fn parse(data: &[u8]) -> Result<Self, ParseError> {
+        if data.len() < BLOCK_LEN {
+            return Err(ParseError::InvalidLength)
+        }
+
+        let data = &data[..BLOCK_LEN];
+        if data[..HEADER.len()] != HEADER {
+            return Err(ParseError::InvalidHeader)
+        }
+
+        if !checksum_valid(data) {
+            return Err(ParseError::InvalidChecksum)
+        }
+
+        let id = ((data[8] as u16) << 8) | data[9] as u16;
+        let mut standard_timings = [None; 8];
+        for (timing, bytes) in standard_timings.iter_mut().zip(data[38..54].chunks(2)) {
+            *timing = StandardTiming::parse([bytes[0], bytes[1]], data[18], data[19]);
+        }
+
+        Ok(BaseBlock {
+            manufacturer_id: [
+                b'@' + ((id >> 10) & 0x1f) as u8,
+                b'@' + ((id >> 5) & 0x1f) as u8,
+                b'@' + (id & 0x1f) as u8,
+            ],
+            product_code: u16::from_le_bytes([data[10], data[11]]),
+            serial_number: u32::from_le_bytes([data[12], data[13], data[14], data[15]]),
+            week: data[16],
+            year: 1990 + data[17] as u16,
+            version: data[18],
+            revision: data[19],
+            video_input: data[20],
+            width_cm: data[21],
+            height_cm: data[22],
+            gamma: data[23],
+            features: data[24],
+            chromaticity: Chromaticity::parse(&data[25..35]),
+            established_timings: EstablishedTimings(
+                ((data[35] as u32) << 16) | ((data[36] as u32) << 8) | data[37] as u32,
+            ),
+            standard_timings,
+            descriptors: [
+                Descriptor::parse(&data[54..72]),
+                Descriptor::parse(&data[72..90]),
+                Descriptor::parse(&data[90..108]),
+                Descriptor::parse(&data[108..126]),
+            ],
+            extension_count: data[EXTENSION_COUNT_OFFSET],
+        })
+    }
+
+    /// The manufacturer's three letter PNP ID.
+    pub fn manufacturer(&self) -> &str {
+        core::str::from_utf8(&self.manufacturer_id).unwrap_or_default()
+    }
+
+    /// Whether the display has a digital input.
+    pub fn is_digital(&self) -> bool {
+        self.video_input & 0x80 != 0
+    }
+
+    /// The display gamma, if specified in the base block.
+    pub fn gamma(&self) -> Option<f32> {
+        match self.gamma {
+            0xff => None,
+            gamma => Some((gamma as f32 + 100.0) / 100.0),
+        }
+    }
+
+    /// The monitor name descriptor, if present.
+    pub fn name(&self) -> Option<&str> {
+        self.descriptors.iter().find_map(|d| match *d {
+            Descriptor::ProductName(ref name) => Some(name.as_str()),
+            _ => None,
+        })
+    }
+
+    /// The serial number descriptor, if present.
+    pub fn serial(&self) -> Option<&str> {
+        self.descriptors.iter().find_map(|d| match *d {
+            Descriptor::SerialNumber(ref serial) => Some(serial.as_str()),
+            _ => None,
+        })
+    }
+
+    /// The display range limits descriptor, if present.
+    pub fn range_limits(&self) -> Option<&RangeLimits> {
+        self.descriptors.iter().find_map(|d| match *d {
+            Descriptor::RangeLimits(ref limits) => Some(limits),
+            _ => None,
+        })
+    }
+
+    /// The preferred timing mode, which is always the first descriptor.
+    pub fn preferred_timing(&self) -> Option<&DetailedTiming> {
+        match self.descriptors[0] {
+            Descriptor::DetailedTiming(ref timing) => Some(timing),
+            _ => None,
+        }
+    }
+
+    /// All detailed timing descriptors.
+    pub fn detailed_timings(&self) -> impl Iterator<Item = &DetailedTiming> {
+        self.descriptors.iter().filter_map(|d| match *d {
+            Descriptor::DetailedTiming(ref timing) => Some(timing),
+            _ => None,
+        })
+    }
+}
+
+/// CIE 1931 chromaticity coordinates, as 10-bit binary fractions.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Chromaticity {
+    /// Red x and y.
+    pub red: (u16, u16),
+    /// Green x and y.
+    pub green: (u16, u16),
+    /// Blue x and y.
+    pub blue: (u16, u16),
+    /// White point x and y.
+    pub white: (u16, u16),
+}
+
+impl Chromaticity {
+    fn parse(data: &[u8]) -> Self {
+        let coord = |hi: u8, lo: u8, shift: u8| ((hi as u16) << 2) | ((lo >> shift) & 0x03) as u16;
+        Chromaticity {
+            red: (coord(data[2], data[0], 6), coord(data[3], data[0], 4)),
+            green: (coord(data[4], data[0], 2), coord(data[5], data[0], 0)),
+            blue: (coord(data[6], data[1], 6), coord(data[7], data[1], 4)),
+            white: (coord(data[8], data[1], 2), coord(data[9], data[1], 0)),
+        }
+    }
+}
+
+/// A display mode consisting of a resolution and refresh rate.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Mode {
+    /// Horizontal addressable pixels.
+    pub width: u16,
+    /// Vertical addressable lines.
+    pub height: u16,
+    /// Vertical refresh rate in Hz.
+    pub refresh_rate: u16,
+}
+
+const ESTABLISHED_TIMINGS: [(u16, u16, u16); 17] = [
+    (720, 400, 70),
+    (720, 400, 88),
+    (640, 480, 60),
+    (640, 480, 67),
+    (640, 480, 72),
+    (640, 480, 75),
+    (800, 600, 56),
+    (800, 600, 60),
+    (800, 600, 72),
+    (800, 600, 75),
+    (832, 624, 75),
+    (1024, 768, 87),
+    (1024, 768, 60),
+    (1024, 768, 70),
+    (1024, 768, 75),
+    (1280, 1024, 75),
+    (1152, 870, 75),
+];
+
+/// The established timings bitmap.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Hash)]
+pub struct EstablishedTimings(pub u32);
+
+impl EstablishedTimings {
+    /// The supported modes.
+    ///
+    /// Manufacturer reserved timings are not included.
+    pub fn modes(&self) -> impl Iterator<Item = Mode> + '_ {
+        ESTABLISHED_TIMINGS
+            .iter()
+            .enumerate()
+            .filter(move |&(bit, _)| self.0 & (0x80_0000 >> bit) != 0)
+            .map(|(_, &(width, height, refresh_rate))| Mode {
+                width,
+                height,
+                refresh_rate,
+            })
+    }
+}
+
+/// The aspect ratio of a standard timing.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum AspectRatio {
+    /// 1:1, only used before EDID 1.3.
+    Square,
+    /// 16:10
+    Wide16x10,
+    /// 4:3
+    Standard4x3,
+    /// 5:4
+    Standard5x4,
+    /// 16:9
+    Wide16x9,
+}
+
+/// A standard timing identifier.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct StandardTiming {
+    /// Horizontal addressable pixels.
+    pub width: u16,
+    /// The image aspect ratio.
+    pub aspect_ratio: AspectRatio,
+    /// Vertical refresh rate in Hz.
+    pub refresh_rate: u16,
+}
+
+impl StandardTiming {
+    /// Parses a two byte standard timing, returning `None` if it is unused.
+    pub fn parse(data: [u8; 2], version: u8, revision: u8) -> Option<Self> {
+        if data == [0x01, 0x01] || data[0] == 0x00 {
+            return None
+        }
+
+        Some(StandardTiming {
+            width: (data[0] as u16 + 31) * 8,
+            aspect_ratio: match data[1] >> 6 {
+                0 if version == 1 && revision < 3 => AspectRatio::Square,
+                0 => AspectRatio::Wide16x10,
+                1 => AspectRatio::Standard4x3,
+                2 => AspectRatio::Standard5x4,
+                _ => AspectRatio::Wide16x9,
+            },
+            refresh_rate: (data[1] & 0x3f) as u16 + 60,
+        })
+    }
+
+    /// Vertical addressable lines.
+    pub fn height(&self) -> u16 {
+        match self.aspect_ratio {
+            AspectRatio::Square => self.width,
+            AspectRatio::Wide16x10 => self.width / 16 * 10,
+            AspectRatio::Standard4x3 => self.width / 4 * 3,
+            AspectRatio::Standard5x4 => self.width / 5 * 4,
+            AspectRatio::Wide16x9 => self.width / 16 * 9,
+        }
+    }
+
+    /// The display mode described by this timing.
+    pub fn mode(&self) -> Mode {
+        Mode {
+            width: self.width,
+            height: self.height(),
+            refresh_rate: self.refresh_rate,
+        }
+    }
+}
+
+/// An 18 byte EDID descriptor.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Descriptor {
+    /// A detailed timing descriptor.
+    DetailedTiming(DetailedTiming),
+    /// Display product serial number (`0xff`).
+    SerialNumber(DescriptorText),
+    /// Alphanumeric data string (`0xfe`).
+    Text(DescriptorText),
+    /// Display range limits (`0xfd`).
+    RangeLimits(RangeLimits),
+    /// Display product name (`0xfc`).
+    ProductName(DescriptorText),
+    /// Additional standard timings (`0xfa`).
+    StandardTimings([Option<StandardTiming>; 6]),
+    /// Dummy descriptor (`0x10`).
+    Dummy,
+    /// Any other display descriptor.
+    Other {
+        /// The descriptor tag.
+        tag: u8,
+        /// The raw descriptor bytes.
+        data: [u8; 18],
+    },
+}
+
+impl Descriptor {
+    /// Parses an 18 byte descriptor.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `data` is shorter than 18 bytes.
+    pub fn parse(data: &[u8]) -> Self {
+        let data = &data[..18];
+        if data[0] != 0 || data[1] != 0 {
+            return Descriptor::DetailedTiming(DetailedTiming::parse(data))
+        }
+
+        match data[3] {
+            0xff => Descriptor::SerialNumber(DescriptorText::parse(&data[5..])),
+            0xfe => Descriptor::Text(DescriptorText::parse(&data[5..])),
+            0xfd => Descriptor::RangeLimits(RangeLimits::parse(data)),
+            0xfc => Descriptor::ProductName(DescriptorText::parse(&data[5..])),
+            0xfa => {
+                let mut timings = [None; 6];
+                for (timing, bytes) in timings.iter_mut().zip(data[5..17].chunks(2)) {
+                    // only used by EDID 1.3 and later
+                    *timing = StandardTiming::parse([bytes[0], bytes[1]], 1, 3);
+                }
+                Descriptor::StandardTimings(timings)
+            },
+            0x10 => Descriptor::Dummy,
+            tag => {
+                let mut raw = [0u8; 18];
+                raw.copy_from_slice(data);
+                Descriptor::Other { tag, data: raw }
+            },
+        }
+    }
+}
+
+/// A descriptor string of up to 13 bytes.
+#[derive(Copy, Clone, PartialEq, Eq, Hash)]
+pub struct DescriptorText {
+    data: [u8; 13],
+    len: u8,
+}
+
+impl DescriptorText {
+    fn parse(data: &[u8]) -> Self {
+        let mut text = DescriptorText {
+            data: [0u8; 13],
+            len: 0,
+        };
+        let data = &data[..13];
+        let len = data.iter().position(|&c| c == b'\n').unwrap_or(data.len());
+        let len = data[..len].iter().rposition(|&c| c != b' ').map(|i| i + 1).unwrap_or(0);
+        text.data[..len].copy_from_slice(&data[..len]);
+        text.len = len as u8;
+        text
+    }
+
+    /// The raw string bytes, with padding removed.
+    pub fn bytes(&self) -> &[u8] {
+        &self.data[..self.len as usize]
+    }
+
+    /// The string, or as much of it as is valid ASCII.
+    pub fn as_str(&self) -> &str {
+        let bytes = self.bytes();
+        let len = bytes.iter().position(|c| !c.is_ascii()).unwrap_or(bytes.len());
+        core::str::from_utf8(&bytes[..len]).unwrap_or_default()
+    }
+}
+
+impl fmt::Debug for DescriptorText {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(self.as_str(), f)
+    }
+}
+
+impl fmt::Display for DescriptorText {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Display range limits.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct RangeLimits {
+    /// Minimum vertical rate in Hz.
+    pub min_vertical_hz: u16,
+    /// Maximum vertical rate in Hz.
+    pub max_vertical_hz: u16,
+    /// Minimum horizontal rate in kHz.
+    pub min_horizontal_khz: u16,
+    /// Maximum horizontal rate in kHz.
+    pub max_horizontal_khz: u16,
+    /// Maximum pixel clock in MHz.
+    pub max_pixel_clock_mhz: u16,
+    /// The video timing support flags.
+    pub timing_support: u8,
+}
+
+impl RangeLimits {
+    fn parse(data: &[u8]) -> Self {
+        let flags = data[4];
+        let offset = |set: bool| if set { 255 } else { 0 };
+        RangeLimits {
+            min_vertical_hz: data[5] as u16 + offset(flags & 0x03 == 0x03),
+            max_vertical_hz: data[6] as u16 + offset(flags & 0x02 != 0),
+            min_horizontal_khz: data[7] as u16 + offset(flags & 0x0c == 0x0c),
+            max_horizontal_khz: data[8] as u16 + offset(flags & 0x08 != 0),
+            max_pixel_clock_mhz: data[9] as u16 * 10,
+            timing_support: data[10],
+        }
+    }
+}
+
+/// A detailed timing descriptor.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct DetailedTiming {
+    /// Pixel clock in kHz.
+    pub pixel_clock_khz: u32,
+    /// Horizontal addressable pixels.
+    pub horizontal_active: u16,
+    /// Horizontal blanking pixels.
+    pub horizontal_blanking: u16,
+    /// Vertical addressable lines.
+    pub vertical_active: u16,
+    /// Vertical blanking lines.
+    pub vertical_blanking: u16,
+    /// Horizontal front porch in pixels.
+    pub horizontal_front_porch: u16,
+    /// Horizontal sync pulse width in pixels.
+    pub horizontal_sync_width: u16,
+    /// Vertical front porch in lines.
+    pub vertical_front_porch: u16,
+    /// Vertical sync pulse width in lines.
+    pub vertical_sync_width: u16,
+    /// Horizontal image size in millimeters.
+    pub width_mm: u16,
+    /// Vertical image size in millimeters.
+    pub height_mm: u16,
+    /// Horizontal border pixels.
+    pub horizontal_border: u8,
+    /// Vertical border lines.
+    pub vertical_border: u8,
+    /// The signal interface flags.
+    pub flags: u8,
+}
+
+impl DetailedTiming {
+    /// Parses an 18 byte detailed timing descriptor.
+    pub fn parse(data: &[u8]) -> Self {
+        let hi = |value: u8, high: u8, shift: u8, mask: u8| value as u16 | ((((high >> shift) & mask) as u16) << 8);
+        DetailedTiming {
+            pixel_clock_khz: u16::from_le_bytes([data[0], data[1]]) as u32 * 10,
+            horizontal_active: hi(data[2], data[4], 4, 0x0f),
+            horizontal_blanking: hi(data[3], data[4], 0, 0x0f),
+            vertical_active: hi(data[5], data[7], 4, 0x0f),
+            vertical_blanking: hi(data[6], data[7], 0, 0x0f),
+            horizontal_front_porch: hi(data[8], data[11], 6, 0x03),
+            horizontal_sync_width: hi(data[9], data[11], 4, 0x03),
+            vertical_front_porch: (data[10] >> 4) as u16 | ((((data[11] >> 2) & 0x03) as u16) << 4),
+            vertical_sync_width: (data[10] & 0x0f) as u16 | (((data[11] & 0x03) as u16) << 4),
+            width_mm: hi(data[12], data[14], 4, 0x0f),
+            height_mm: hi(data[13], data[14], 0, 0x0f),
+            horizontal_border: data[15],
+            vertical_border: data[16],
+            flags: data[17],
+        }
+    }
+
+    /// Whether the mode is interlaced.
+    pub fn is_interlaced(&self) -> bool {
+        self.flags & 0x80 != 0
+    }
+
+    /// The vertical refresh rate in millihertz.
+    pub fn refresh_rate_millihertz(&self) -> u32 {
+        let total = (self.horizontal_active as u64 + self.horizontal_blanking as u64)
+            * (self.vertical_active as u64 + self.vertical_blanking as u64);
+        match total {
+            0 => 0,
+            total => (self.pixel_clock_khz as u64 * 1_000_000 / total) as u32,
+        }
+    }
+
+    /// The display mode described by this timing, with the refresh rate
+    /// rounded to the nearest Hz.
+    pub fn mode(&self) -> Mode {
+        Mode {
+            width: self.horizontal_active,
+            height: self.vertical_active,
+            refresh_rate: ((self.refresh_rate_millihertz() + 500) / 1000) as u16,
+        }
+    }
+}
@@ -0,0 +1,424 @@
+use {
+    super::{checksum_valid, DetailedTiming, ParseError, BLOCK_LEN},
+    core::iter,
+};
+
+/// The extension tag of a CTA-861 block.
+pub const TAG: u8 = 0x02;
+
+/// The IEEE OUI of HDMI Licensing, LLC.
+pub const OUI_HDMI: u32 = 0x000c03;
+
+/// The IEEE OUI of the HDMI Forum.
+pub const OUI_HDMI_FORUM: u32 = 0xc45dd8;
+
+/// A CTA-861 extension block.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct CtaExtension<'a> {
+    /// The revision of the extension block.
+    pub revision: u8,
+    /// Display support flags and the native detailed timing count.
+    pub flags: u8,
+    data_blocks: &'a [u8],
+    detailed_timings: &'a [u8],
+}
+
+impl<'a> CtaExtension<'a> {
+    /// Parses and validates a 128 byte CTA-861 extension block.
+    pub fn parse(data: &'a [u8]) -> Result<Self, ParseError> {
+        if data.len() < BLOCK_LEN {
+            return Err(ParseError::InvalidLength)
+        }
+
+        let data = &data[..BLOCK_LEN];
+        if data[0] != TAG {
+            return Err(ParseError::InvalidHeader)
+        }
+
+        if !checksum_valid(data) {
+            return Err(ParseError::InvalidChecksum)
+        }
+
+        let (data_blocks, detailed_timings) = match data[2] as usize {
+            // no detailed timings or data blocks
+            0 => (&data[4..4], &data[4..4]),
+            offset if !(4..BLOCK_LEN).contains(&offset) => return Err(ParseError::InvalidLength),
+            // revisions 1 and 2 have no data block collection
+            offset if data[1] < 3 => (&data[4..4], &data[offset..BLOCK_LEN - 1]),
+            offset => (&data[4..offset], &data[offset..BLOCK_LEN - 1]),
+        };
+
+        Ok(CtaExtension {
+            revision: data[1],
+            flags: data[3],
+            data_blocks,
+            detailed_timings,
+        })
+    }
+
+    /// Whether the display underscans IT formats by default.
+    pub fn underscan(&self) -> bool {
+        self.flags & 0x80 != 0
+    }
+
+    /// Whether the display supports basic audio.
+    pub fn basic_audio(&self) -> bool {
+        self.flags & 0x40 != 0
+    }
+
+    /// Whether the display supports YCbCr 4:4:4.
+    pub fn ycbcr444(&self) -> bool {
+        self.flags & 0x20 != 0
+    }
+
+    /// Whether the display supports YCbCr 4:2:2.
+    pub fn ycbcr422(&self) -> bool {
+        self.flags & 0x10 != 0
+    }
+
+    /// The number of native detailed timings.
+    pub fn native_timing_count(&self) -> u8 {
+        self.flags & 0x0f
+    }
+
+    /// The data block collection.
+    pub fn data_blocks(&self) -> DataBlocks<'a> {
+        DataBlocks { data: self.data_blocks }
+    }
+
+    /// The detailed timing descriptors that follow the data block collection.
+    pub fn detailed_timings(&self) -> impl Iterator<Item = DetailedTiming> + 'a {
+        self.detailed_timings
+            .chunks_exact(18)
+            .take_while(|d| d[0] != 0 || d[1] != 0)
+            .map(DetailedTiming::parse)
+    }
+
+    /// The video formats supported by the display.
+    pub fn video_descriptors(&self) -> impl Iterator<Item = ShortVideoDescriptor> + 'a {
+        self.data_blocks()
+            .filter_map(|block| match block {
+                DataBlock::Video(data) => Some(data),
+                _ => None,
+            })
+            .flat_map(|data| data.iter().filter_map(|&svd| ShortVideoDescriptor::parse(svd)))
+    }
+
+    /// The audio formats supported by the display.
+    pub fn audio_descriptors(&self) -> impl Iterator<Item = ShortAudioDescriptor> + 'a {
+        self.data_blocks()
+            .filter_map(|block| match block {
+                DataBlock::Audio(data) => Some(data),
+                _ => None,
+            })
+            .flat_map(|data| data.chunks_exact(3).map(ShortAudioDescriptor::parse))
+    }
+
+    /// The HDR static metadata data block, if present.
+    pub fn hdr_static_metadata(&self) -> Option<HdrStaticMetadata> {
+        self.data_blocks().find_map(|block| match block {
+            DataBlock::HdrStaticMetadata(hdr) => Some(hdr),
+            _ => None,
+        })
+    }
+
+    /// The colorimetry data block, if present.
+    pub fn colorimetry(&self) -> Option<Colorimetry> {
+        self.data_blocks().find_map(|block| match block {
+            DataBlock::Colorimetry(colorimetry) => Some(colorimetry),
+            _ => None,
+        })
+    }
+
+    /// The HDMI vendor-specific data block, if present.
+    pub fn hdmi(&self) -> Option<Hdmi> {
+        self.data_blocks().find_map(|block| match block {
+            DataBlock::Hdmi(hdmi) => Some(hdmi),
+            _ => None,
+        })
+    }
+}
+
+/// An iterator over a CTA-861 data block collection.
+#[derive(Debug, Clone)]
+pub struct DataBlocks<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> DataBlocks<'a> {
+    /// Iterates over a raw data block collection.
+    pub fn new(data: &'a [u8]) -> Self {
+        DataBlocks { data }
+    }
+}
+
+impl<'a> Iterator for DataBlocks<'a> {
+    type Item = DataBlock<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (&header, rest) = self.data.split_first()?;
+        let len = (header & 0x1f) as usize;
+        if rest.len() < len {
+            // malformed length, stop rather than read past the collection
+            self.data = &[];
+            return None
+        }
+
+        let (payload, rest) = rest.split_at(len);
+        self.data = rest;
+        Some(DataBlock::parse(header >> 5, payload))
+    }
+}
+
+impl iter::FusedIterator for DataBlocks<'_> {}
+
+/// A CTA-861 data block.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum DataBlock<'a> {
+    /// Short audio descriptors, three bytes each.
+    Audio(&'a [u8]),
+    /// Short video descriptors, one byte each.
+    Video(&'a [u8]),
+    /// The HDMI 1.x vendor-specific data block.
+    Hdmi(Hdmi),
+    /// The HDMI Forum vendor-specific data block.
+    HdmiForum(HdmiForum),
+    /// Any other vendor-specific data block.
+    VendorSpecific {
+        /// The IEEE OUI of the vendor.
+        oui: u32,
+        /// The data following the OUI.
+        payload: &'a [u8],
+    },
+    /// Speaker allocation.
+    SpeakerAllocation(SpeakerAllocation),
+    /// Colorimetry.
+    Colorimetry(Colorimetry),
+    /// HDR static metadata.
+    HdrStaticMetadata(HdrStaticMetadata),
+    /// Any other extended tag data block.
+    Extended {
+        /// The extended tag code.
+        tag: u8,
+        /// The data following the extended tag.
+        payload: &'a [u8],
+    },
+    /// Any other data block.
+    Other {
+        /// The tag code.
+        tag: u8,
+        /// The block payload.
+        payload: &'a [u8],
+    },
+}
+
+impl<'a> DataBlock<'a> {
+    /// Parses a data block from its tag code and payload.
+    pub fn parse(tag: u8, payload: &'a [u8]) -> Self {
+        match (tag, payload) {
+            (1, payload) => DataBlock::Audio(payload),
+            (2, payload) => DataBlock::Video(payload),
+            (3, &[o0, o1, o2, ref payload @ ..]) => {
+                let oui = u32::from_le_bytes([o0, o1, o2, 0]);
+                match oui {
+                    OUI_HDMI if payload.len() >= 2 => DataBlock::Hdmi(Hdmi::parse(payload)),
+                    OUI_HDMI_FORUM if payload.len() >= 3 => DataBlock::HdmiForum(HdmiForum::parse(payload)),
+                    oui => DataBlock::VendorSpecific { oui, payload },
+                }
+            },
+            (4, &[a, b, c, ..]) => DataBlock::SpeakerAllocation(SpeakerAllocation([a, b, c])),
+            (7, &[5, a, b, ..]) => DataBlock::Colorimetry(Colorimetry(u16::from_le_bytes([a, b]))),
+            (7, &[6, ref payload @ ..]) if payload.len() >= 2 =>
+                DataBlock::HdrStaticMetadata(HdrStaticMetadata::parse(payload)),
+            (7, &[tag, ref payload @ ..]) => DataBlock::Extended { tag, payload },
+            (tag, payload) => DataBlock::Other { tag, payload },
+        }
+    }
+}
+
+/// A short video descriptor.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ShortVideoDescriptor {
+    /// The CTA-861 video identification code.
+    pub vic: u8,
+    /// Whether this is a native format of the display.
+    pub native: bool,
+}
+
+impl ShortVideoDescriptor {
+    /// Parses a short video descriptor, returning `None` for reserved values.
+    pub fn parse(svd: u8) -> Option<Self> {
+        match svd {
+            0 | 128 | 254 | 255 => None,
+            129..=192 => Some(ShortVideoDescriptor {
+                vic: svd & 0x7f,
+                native: true,
+            }),
+            vic => Some(ShortVideoDescriptor { vic, native: false }),
+        }
+    }
+}
+
+/// A short audio descriptor.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct ShortAudioDescriptor {
+    /// The audio format code, where 1 is L-PCM.
+    pub format: u8,
+    /// The maximum number of channels.
+    pub channels: u8,
+    /// The supported sampling rates bitmap, from 32 kHz to 192 kHz.
+    pub sample_rates: u8,
+    /// Format dependent data; supported bit depths for L-PCM.
+    pub detail: u8,
+}
+
+impl ShortAudioDescriptor {
+    /// Parses a three byte short audio descriptor.
+    pub fn parse(data: &[u8]) -> Self {
+        ShortAudioDescriptor {
+            format: (data[0] >> 3) & 0x0f,
+            channels: (data[0] & 0x07) + 1,
+            sample_rates: data[1] & 0x7f,
+            detail: data[2],
+        }
+    }
+}
+
+/// The HDMI 1.x vendor-specific data block.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Hdmi {
+    /// The CEC physical address of the source.
+    pub physical_address: u16,
+    /// Deep color and other support flags.
+    pub flags: u8,
+    /// The maximum TMDS clock in MHz, if specified.
+    pub max_tmds_clock_mhz: Option<u16>,
+}
+
+impl Hdmi {
+    fn parse(payload: &[u8]) -> Self {
+        Hdmi {
+            physical_address: u16::from_be_bytes([payload[0], payload[1]]),
+            flags: payload.get(2).cloned().unwrap_or_default(),
+            max_tmds_clock_mhz: payload
+                .get(3)
+                .filter(|&&clock| clock != 0)
+                .map(|&clock| clock as u16 * 5),
+        }
+    }
+}
+
+/// The HDMI Forum vendor-specific data block.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct HdmiForum {
+    /// The block version.
+    pub version: u8,
+    /// The maximum TMDS character rate in MHz, or zero if no higher than
+    /// 340 MHz.
+    pub max_tmds_rate_mhz: u16,
+    /// SCDC and other support flags.
+    pub flags: u8,
+}
+
+impl HdmiForum {
+    fn parse(payload: &[u8]) -> Self {
+        HdmiForum {
+            version: payload[0],
+            max_tmds_rate_mhz: payload[1] as u16 * 5,
+            flags: payload[2],
+        }
+    }
+}
+
+/// The speaker allocation bitmap.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Hash)]
+pub struct SpeakerAllocation(pub [u8; 3]);
+
+/// The colorimetry data block bitmap.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Colorimetry(pub u16);
+
+impl Colorimetry {
+    /// Whether BT.2020 RGB is supported.
+    pub fn bt2020_rgb(&self) -> bool {
+        self.0 & 0x0080 != 0
+    }
+
+    /// Whether BT.2020 YCbCr is supported.
+    pub fn bt2020_ycc(&self) -> bool {
+        self.0 & 0x0040 != 0
+    }
+
+    /// Whether DCI-P3 is supported.
+    pub fn dci_p3(&self) -> bool {
+        self.0 & 0x8000 != 0
+    }
+}
+
+/// The HDR static metadata data block.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Hash)]
+pub struct HdrStaticMetadata {
+    /// The supported electro-optical transfer functions bitmap.
+    pub eotf: u8,
+    /// The supported static metadata descriptors bitmap.
+    pub descriptors: u8,
+    /// The desired content max luminance code value.
+    pub max_luminance: Option<u8>,
+    /// The desired content max frame-average luminance code value.
+    pub max_frame_average_luminance: Option<u8>,
+    /// The desired content min luminance code value.
+    pub min_luminance: Option<u8>,
+}
+
+impl HdrStaticMetadata {
+    fn parse(payload: &[u8]) -> Self {
+        HdrStaticMetadata {
+            eotf: payload[0],
+            descriptors: payload[1],
+            max_luminance: payload.get(2).cloned(),
+            max_frame_average_luminance: payload.get(3).cloned(),
+            min_luminance: payload.get(4).cloned(),
+        }
+    }
+
+    /// Whether traditional gamma SDR is supported.
+    pub fn sdr(&self) -> bool {
+        self.eotf & 0x01 != 0
+    }
+
+    /// Whether SMPTE ST 2084 (PQ, as used by HDR10) is supported.
+    pub fn pq(&self) -> bool {
+        self.eotf & 0x04 != 0
+    }
+
+    /// Whether hybrid log-gamma is supported.
+    pub fn hlg(&self) -> bool {
+        self.eotf & 0x08 != 0
+    }
+
+    /// The desired content max luminance in cd/m².
+    pub fn max_luminance_nits(&self) -> Option<f32> {
+        self.max_luminance.map(luminance)
+    }
+
+    /// The desired content max frame-average luminance in cd/m².
+    pub fn max_frame_average_luminance_nits(&self) -> Option<f32> {
+        self.max_frame_average_luminance.map(luminance)
+    }
+
+    /// The desired content min luminance in cd/m².
+    pub fn min_luminance_nits(&self) -> Option<f32> {
+        let max = self.max_luminance_nits()?;
+        let min = self.min_luminance? as f32 / 255.0;
+        Some(max * min * min / 100.0)
+    }
+}
+
+/// `50 * 2^(code / 32)`, computed without `powf` so it's available in
+/// `no_std` builds.
+fn luminance(code: u8) -> f32 {
+    // the fractional power of two is a short series of e^(x ln 2)
+    let x = (code % 32) as f32 / 32.0 * core::f32::consts::LN_2;
+    let fraction = 1.0 + x * (1.0 + x / 2.0 * (1.0 + x / 3.0 * (1.0 + x / 4.0 * (1.0 + x / 5.0))));
+    50.0 * (1u32 << (code / 32)) as f32 * fraction
+}
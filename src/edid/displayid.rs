@@ -0,0 +1,220 @@
+use {
+    super::{checksum_valid, cta::DataBlocks, Mode, ParseError, BLOCK_LEN},
+    core::iter,
+};
+
+/// The extension tag of a DisplayID block.
+pub const TAG: u8 = 0x70;
+
+/// The size of a type I or type VII detailed timing descriptor.
+pub const TIMING_LEN: usize = 20;
+
+/// The DisplayID 1.3 type I detailed timing block tag.
+pub const TAG_TIMING_TYPE_I: u8 = 0x03;
+
+/// The DisplayID 2.0 type VII detailed timing block tag.
+pub const TAG_TIMING_TYPE_VII: u8 = 0x22;
+
+/// The tag of a block containing CTA-861 data blocks.
+pub const TAG_CTA: u8 = 0x81;
+
+/// A DisplayID section carried in an EDID extension block.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct DisplayIdExtension<'a> {
+    /// The DisplayID version, `0x12` for 1.2 and 1.3 or `0x20` for 2.0.
+    pub version: u8,
+    /// The display product type, or primary use case for 2.0.
+    pub product_type: u8,
+    /// The number of extension sections that follow.
+    pub extension_count: u8,
+    data_blocks: &'a [u8],
+}
+
+impl<'a> DisplayIdExtension<'a> {
+    /// Parses and validates a 128 byte DisplayID extension block.
+    pub fn parse(data: &'a [u8]) -> Result<Self, ParseError> {
+        if data.len() < BLOCK_LEN {
+            return Err(ParseError::InvalidLength)
+        }
+
+        let data = &data[..BLOCK_LEN];
+        if data[0] != TAG {
+            return Err(ParseError::InvalidHeader)
+        }
+
+        if !checksum_valid(data) {
+            return Err(ParseError::InvalidChecksum)
+        }
+
+        // the section is a header, its payload and a checksum byte
+        let len = data[2] as usize;
+        let section = match data.get(1..1 + 4 + len + 1) {
+            Some(section) if 1 + section.len() < BLOCK_LEN => section,
+            _ => return Err(ParseError::InvalidLength),
+        };
+
+        if section.iter().fold(0u8, |sum, &v| sum.wrapping_add(v)) != 0 {
+            return Err(ParseError::InvalidChecksum)
+        }
+
+        Ok(DisplayIdExtension {
+            version: section[0],
+            product_type: section[2],
+            extension_count: section[3],
+            data_blocks: &section[4..4 + len],
+        })
+    }
+
+    /// Whether this is a DisplayID 2.x section.
+    pub fn is_v2(&self) -> bool {
+        self.version >= 0x20
+    }
+
+    /// The data blocks of this section.
+    pub fn data_blocks(&self) -> DisplayIdBlocks<'a> {
+        DisplayIdBlocks { data: self.data_blocks }
+    }
+
+    /// The type I and type VII detailed timings of this section.
+    pub fn timings(&self) -> impl Iterator<Item = DisplayIdTiming> + 'a {
+        self.data_blocks()
+            .filter_map(|block| match block.tag {
+                TAG_TIMING_TYPE_I => Some((block.payload, 10)),
+                TAG_TIMING_TYPE_VII => Some((block.payload, 1)),
+                _ => None,
+            })
+            .flat_map(|(payload, clock_unit_khz)| {
+                payload
+                    .chunks_exact(TIMING_LEN)
+                    .map(move |data| DisplayIdTiming::parse(data, clock_unit_khz))
+            })
+    }
+
+    /// The CTA-861 data blocks embedded in this section.
+    pub fn cta_data_blocks(&self) -> impl Iterator<Item = super::cta::DataBlock<'a>> + 'a {
+        self.data_blocks()
+            .filter(|block| block.tag == TAG_CTA)
+            .flat_map(|block| DataBlocks::new(block.payload))
+    }
+}
+
+/// An iterator over the data blocks of a DisplayID section.
+#[derive(Debug, Clone)]
+pub struct DisplayIdBlocks<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Iterator for DisplayIdBlocks<'a> {
+    type Item = DisplayIdBlock<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // the remainder of a section is zero padded
+        if self.data.iter().all(|&v| v == 0) {
+            self.data = &[];
+            return None
+        }
+
+        let (payload, rest) = match *self.data {
+            [_, _, len, ref rest @ ..] if rest.len() >= len as usize => rest.split_at(len as usize),
+            _ => {
+                self.data = &[];
+                return None
+            },
+        };
+
+        let block = DisplayIdBlock {
+            tag: self.data[0],
+            revision: self.data[1],
+            payload,
+        };
+        self.data = rest;
+        Some(block)
+    }
+}
+
+impl iter::FusedIterator for DisplayIdBlocks<'_> {}
+
+/// A DisplayID data block.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct DisplayIdBlock<'a> {
+    /// The block tag.
+    pub tag: u8,
+    /// The block revision and flags.
+    pub revision: u8,
+    /// The block payload.
+    pub payload: &'a [u8],
+}
+
+/// A DisplayID type I or type VII detailed timing.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct DisplayIdTiming {
+    /// Pixel clock in kHz.
+    pub pixel_clock_khz: u32,
+    /// The timing options, including the preferred and interlaced flags.
+    pub options: u8,
+    /// Horizontal addressable pixels.
+    pub horizontal_active: u16,
+    /// Horizontal blanking pixels.
+    pub horizontal_blanking: u16,
+    /// Horizontal front porch in pixels.
+    pub horizontal_front_porch: u16,
+    /// Horizontal sync pulse width in pixels.
+    pub horizontal_sync_width: u16,
+    /// Vertical addressable lines.
+    pub vertical_active: u16,
+    /// Vertical blanking lines.
+    pub vertical_blanking: u16,
+    /// Vertical front porch in lines.
+    pub vertical_front_porch: u16,
+    /// Vertical sync pulse width in lines.
+    pub vertical_sync_width: u16,
+}
+
+impl DisplayIdTiming {
+    /// Parses a 20 byte detailed timing descriptor, given the pixel clock
+    /// unit of its block type.
+    pub fn parse(data: &[u8], clock_unit_khz: u32) -> Self {
+        // every field is stored as its value minus one
+        let field =
+            |offset: usize, mask: u16| (u16::from_le_bytes([data[offset], data[offset + 1]]) & mask).saturating_add(1);
+        DisplayIdTiming {
+            pixel_clock_khz: (u32::from_le_bytes([data[0], data[1], data[2], 0]) + 1) * clock_unit_khz,
+            options: data[3],
+            horizontal_active: field(4, 0xffff),
+            horizontal_blanking: field(6, 0xffff),
+            horizontal_front_porch: field(8, 0x7fff),
+            horizontal_sync_width: field(10, 0xffff),
+            vertical_active: field(12, 0xffff),
+            vertical_blanking: field(14, 0xffff),
+            vertical_front_porch: field(16, 0x7fff),
+            vertical_sync_width: field(18, 0xffff),
+        }
+    }
+
+    /// Whether this is the display's preferred timing.
+    pub fn is_preferred(&self) -> bool {
+        self.options & 0x80 != 0
+    }
+
+    /// Whether the mode is interlaced.
+    pub fn is_interlaced(&self) -> bool {
+        self.options & 0x10 != 0
+    }
+
+    /// The vertical refresh rate in millihertz.
+    pub fn refresh_rate_millihertz(&self) -> u32 {
+        let total = (self.horizontal_active as u64 + self.horizontal_blanking as u64)
+            * (self.vertical_active as u64 + self.vertical_blanking as u64);
+        (self.pixel_clock_khz as u64 * 1_000_000 / total) as u32
+    }
+
+    /// The display mode described by this timing, with the refresh rate
+    /// rounded to the nearest Hz.
+    pub fn mode(&self) -> Mode {
+        Mode {
+            width: self.horizontal_active,
+            height: self.vertical_active,
+            refresh_rate: ((self.refresh_rate_millihertz() + 500) / 1000) as u16,
+        }
+    }
+}
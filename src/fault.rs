@@ -0,0 +1,316 @@
+#[cfg(feature = "std")]
+use crate::StdTimer;
+use {
+    crate::{
+        framing, responder, DdcCommandMarker, DdcCommandRaw, DdcCommandRawMarker, DdcHost, Delay, Eddc, Edid,
+        ErrorCode, Timer,
+    },
+    core::time::Duration,
+};
+
+/// The ways a reply may be corrupted by `FaultInjector`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Fault {
+    /// The checksum of the framed reply is wrong.
+    CorruptChecksum,
+    /// The bus read ends partway through the framed reply.
+    Truncate,
+    /// Random bytes are appended to the reply.
+    Pad,
+    /// The display replies with the null message.
+    NullMessage,
+    /// The reply has the wrong opcode.
+    WrongOpcode,
+    /// A capabilities or table fragment is answered with the previous
+    /// fragment again.
+    RepeatFragment,
+}
+
+/// How often each `Fault` is injected, as probabilities between 0 and 1.
+///
+/// At most one fault is injected into each reply, checked in the order of
+/// the fields. Faults that don't apply to a reply, such as repeating a
+/// fragment of a `GetVcpFeature` reply, leave it untouched.
+#[derive(Debug, Copy, Clone, Default)]
+pub struct FaultPolicy {
+    /// Seeds the random choices, so that a run can be reproduced.
+    pub seed: u64,
+    /// The probability of `Fault::CorruptChecksum`.
+    pub corrupt_checksum: f32,
+    /// The probability of `Fault::Truncate`.
+    pub truncate: f32,
+    /// The probability of `Fault::Pad`.
+    pub pad: f32,
+    /// The probability of `Fault::NullMessage`.
+    pub null_message: f32,
+    /// The probability of `Fault::WrongOpcode`.
+    pub wrong_opcode: f32,
+    /// The probability of `Fault::RepeatFragment`.
+    pub repeat_fragment: f32,
+    /// The probability of delaying a reply, independently of other faults.
+    pub latency: f32,
+    /// The upper limit of injected delays.
+    pub max_latency: Duration,
+}
+
+impl FaultPolicy {
+    /// A policy that never injects faults.
+    pub fn never() -> Self {
+        Default::default()
+    }
+
+    /// A policy that injects every fault with the same probability.
+    pub fn uniform(seed: u64, probability: f32) -> Self {
+        FaultPolicy {
+            seed,
+            corrupt_checksum: probability,
+            truncate: probability,
+            pad: probability,
+            null_message: probability,
+            wrong_opcode: probability,
+            repeat_fragment: probability,
+            ..Default::default()
+        }
+    }
+
+    /// The probability of a fault.
+    pub fn probability(&self, fault: Fault) -> f32 {
+        match fault {
+            Fault::CorruptChecksum => self.corrupt_checksum,
+            Fault::Truncate => self.truncate,
+            Fault::Pad => self.pad,
+            Fault::NullMessage => self.null_message,
+            Fault::WrongOpcode => self.wrong_opcode,
+            Fault::RepeatFragment => self.repeat_fragment,
+        }
+    }
+}
+
+/// A small deterministic generator (SplitMix64), as fault injection doesn't
+/// need statistical quality.
+#[derive(Debug, Clone)]
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e3779b97f4a7c15);
+        let z = self.0;
+        let z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        let z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+
+    fn chance(&mut self, probability: f32) -> bool {
+        // 24 bits is the precision of an f32 mantissa
+        probability > 0.0 && ((self.next() >> 40) as f32 / (1u32 << 24) as f32) < probability
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+
+    fn byte(&mut self) -> u8 {
+        self.next() as u8
+    }
+}
+
+/// Corrupts the replies of a DDC implementation according to a
+/// `FaultPolicy`, for testing how errors are handled.
+///
+/// Replies are re-framed and decoded again after corruption, so the faults
+/// surface as the same errors a misbehaving display would cause.
+#[derive(Clone, Debug)]
+pub struct FaultInjector<D, T> {
+    inner: D,
+    timer: T,
+    policy: FaultPolicy,
+    rng: Rng,
+    last_fault: Option<Fault>,
+    fragment: [u8; 0x7f],
+    fragment_len: usize,
+}
+
+#[cfg(feature = "std")]
+impl<D> FaultInjector<D, StdTimer> {
+    /// Wraps a DDC implementation.
+    pub fn new(inner: D, policy: FaultPolicy) -> Self {
+        Self::with_timer(inner, policy, StdTimer)
+    }
+}
+
+impl<D, T> FaultInjector<D, T> {
+    /// Wraps a DDC implementation, using `timer` for injected delays.
+    pub fn with_timer(inner: D, policy: FaultPolicy, timer: T) -> Self {
+        FaultInjector {
+            inner,
+            timer,
+            policy,
+            rng: Rng(policy.seed),
+            last_fault: None,
+            fragment: [0u8; 0x7f],
+            fragment_len: 0,
+        }
+    }
+
+    /// The fault policy.
+    pub fn policy(&self) -> &FaultPolicy {
+        &self.policy
+    }
+
+    /// Replaces the fault policy, restarting from its seed.
+    pub fn set_policy(&mut self, policy: FaultPolicy) {
+        self.policy = policy;
+        self.rng = Rng(policy.seed);
+    }
+
+    /// The fault injected into the most recent reply, if any.
+    pub fn last_fault(&self) -> Option<Fault> {
+        self.last_fault
+    }
+
+    /// Consumes the wrapper, returning the underlying implementation and
+    /// timer.
+    pub fn into_inner(self) -> (D, T) {
+        (self.inner, self.timer)
+    }
+
+    /// Borrows the underlying implementation.
+    pub fn inner_ref(&self) -> &D {
+        &self.inner
+    }
+
+    /// Mutably borrows the underlying implementation.
+    pub fn inner_mut(&mut self) -> &mut D {
+        &mut self.inner
+    }
+
+    /// Picks the fault to inject into a reply.
+    fn choose(&mut self, payload: &[u8]) -> Option<Fault> {
+        let is_fragment = matches!(payload.first(), Some(0xe3 | 0xe4))
+            && payload.first() == self.fragment[..self.fragment_len].first();
+        [
+            Fault::CorruptChecksum,
+            Fault::Truncate,
+            Fault::Pad,
+            Fault::NullMessage,
+            Fault::WrongOpcode,
+            Fault::RepeatFragment,
+        ]
+        .into_iter()
+        .find(|&fault| self.rng.chance(self.policy.probability(fault)))
+        .filter(|&fault| fault != Fault::RepeatFragment || is_fragment)
+    }
+}
+
+impl<D: DdcHost, T> DdcHost for FaultInjector<D, T> {
+    type Error = D::Error;
+
+    fn sleep(&mut self) {
+        self.inner.sleep()
+    }
+}
+
+impl<D: DdcCommandRaw, T: Timer> DdcCommandRaw for FaultInjector<D, T>
+where
+    D::Error: From<ErrorCode>,
+{
+    fn execute_raw<'a>(
+        &mut self,
+        data: &[u8],
+        out: &'a mut [u8],
+        response_delay: Duration,
+    ) -> Result<&'a mut [u8], Self::Error> {
+        self.last_fault = None;
+        let mut payload = [0u8; 0x7f];
+        let mut len = {
+            let reply = self.inner.execute_raw(data, out, response_delay)?;
+            payload[..reply.len()].copy_from_slice(reply);
+            reply.len()
+        };
+
+        if self.rng.chance(self.policy.latency) {
+            let max = self.policy.max_latency.as_micros() as usize;
+            self.timer.sleep(Duration::from_micros(self.rng.below(max + 1) as u64));
+        }
+
+        // nothing to corrupt for commands without a reply
+        if len == 0 {
+            return Ok(&mut out[..0])
+        }
+        let original = (payload, len);
+
+        let mut fault = self.choose(&payload[..len]);
+        match fault {
+            Some(Fault::Pad) => {
+                // padding must still fit in the reply buffer along with the framing
+                let end = (len + 1 + self.rng.below(8))
+                    .min(out.len().saturating_sub(3))
+                    .min(payload.len());
+                match end > len {
+                    true => {
+                        payload[len..end].iter_mut().for_each(|b| *b = self.rng.byte());
+                        len = end;
+                    },
+                    false => fault = None,
+                }
+            },
+            Some(Fault::NullMessage) => len = 0,
+            Some(Fault::WrongOpcode) => payload[0] ^= self.rng.byte() | 1,
+            Some(Fault::RepeatFragment) => {
+                // the display didn't advance past the previous fragment
+                len = self.fragment_len;
+                payload[..len].copy_from_slice(&self.fragment[..len]);
+            },
+            Some(Fault::CorruptChecksum | Fault::Truncate) | None => (),
+        }
+        self.last_fault = fault;
+
+        if let ([0xe3 | 0xe4, ..], len) = original {
+            self.fragment = original.0;
+            self.fragment_len = len;
+        }
+
+        let mut packet = [0u8; 0x7f + 3];
+        let packet_len = responder::encode_reply(&payload[..len], &mut packet)?.len();
+        if fault == Some(Fault::CorruptChecksum) {
+            packet[packet_len - 1] ^= self.rng.byte() | 1;
+        }
+
+        // a bus read is limited to the size of the reply buffer
+        let mut read_len = packet_len.min(out.len());
+        if fault == Some(Fault::Truncate) {
+            // the reply buffer has room for the header and checksum along
+            // with at least one payload byte, so at least the checksum is cut
+            read_len = 3 + self.rng.below(read_len - 3);
+        }
+        out[..read_len].copy_from_slice(&packet[..read_len]);
+        let len = framing::REPLY.payload_len(&out[..read_len])?;
+
+        Ok(&mut out[2..2 + len])
+    }
+}
+
+impl<D: DdcCommandRawMarker, T: Timer> DdcCommandRawMarker for FaultInjector<D, T>
+where
+    D::Error: From<ErrorCode>,
+{
+    fn set_sleep_delay(&mut self, delay: Delay) {
+        self.inner.set_sleep_delay(delay)
+    }
+}
+
+impl<D: DdcCommandRawMarker, T: Timer> DdcCommandMarker for FaultInjector<D, T> where D::Error: From<ErrorCode> {}
+
+impl<D: Edid, T> Edid for FaultInjector<D, T> {
+    type EdidError = D::EdidError;
+
+    fn read_edid(&mut self, offset: u8, data: &mut [u8]) -> Result<usize, Self::EdidError> {
+        self.inner.read_edid(offset, data)
+    }
+}
+
+impl<D: Eddc, T> Eddc for FaultInjector<D, T> {
+    fn read_eddc_edid(&mut self, segment: u8, offset: u8, data: &mut [u8]) -> Result<usize, Self::EdidError> {
+        self.inner.read_eddc_edid(segment, offset, data)
+    }
+}
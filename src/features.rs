@@ -0,0 +1,277 @@
+use crate::{FeatureCode, VcpValue};
+
+/// A VCP feature with a known encoding.
+///
+/// Use with `Ddc::get_feature` and `Ddc::set_feature`.
+pub trait Feature {
+    /// The VCP feature code.
+    const CODE: FeatureCode;
+
+    /// The decoded value read from the display.
+    type Value;
+
+    /// The value written to the display.
+    type Setting;
+
+    /// Decodes a value read from the display.
+    fn decode(value: &VcpValue) -> Self::Value;
+
+    /// Encodes a setting for `SetVcpFeature`.
+    fn encode(setting: Self::Setting) -> u16;
+}
+
+/// The current and maximum values of a continuous feature.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Continuous {
+    /// The current value.
+    pub value: u16,
+    /// The maximum value supported by the display.
+    pub maximum: u16,
+}
+
+impl Continuous {
+    /// Reads the current and maximum values of a `VcpValue`.
+    pub fn new(value: &VcpValue) -> Self {
+        Continuous {
+            value: value.value(),
+            maximum: value.maximum(),
+        }
+    }
+
+    /// The current value as a percentage of the maximum.
+    pub fn percent(&self) -> u16 {
+        match self.maximum {
+            0 => 0,
+            maximum => (self.value as u32 * 100 / maximum as u32) as u16,
+        }
+    }
+
+    /// The value corresponding to a percentage of the maximum.
+    pub fn from_percent(&self, percent: u16) -> u16 {
+        (self.maximum as u32 * percent.min(100) as u32 / 100) as u16
+    }
+}
+
+macro_rules! continuous {
+    ($(#[$meta:meta])* $name:ident = $code:expr) => {
+        $(#[$meta])*
+        #[derive(Debug, Copy, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+        pub struct $name;
+
+        impl Feature for $name {
+            type Setting = u16;
+            type Value = Continuous;
+
+            const CODE: FeatureCode = $code;
+
+            fn decode(value: &VcpValue) -> Self::Value {
+                Continuous::new(value)
+            }
+
+            fn encode(setting: Self::Setting) -> u16 {
+                setting
+            }
+        }
+    };
+}
+
+continuous! {
+    /// Luminance of the image (0x10).
+    Brightness = 0x10
+}
+
+continuous! {
+    /// Contrast of the image (0x12).
+    Contrast = 0x12
+}
+
+continuous! {
+    /// Red video gain (0x16).
+    RedGain = 0x16
+}
+
+continuous! {
+    /// Green video gain (0x18).
+    GreenGain = 0x18
+}
+
+continuous! {
+    /// Blue video gain (0x1A).
+    BlueGain = 0x1a
+}
+
+continuous! {
+    /// Audio speaker volume (0x62).
+    Volume = 0x62
+}
+
+macro_rules! non_continuous {
+    (
+        $(#[$meta:meta])* $name:ident = $code:expr;
+        $($(#[$variant_meta:meta])* $variant:ident = $value:literal => $value_name:expr,)*
+    ) => {
+        $(#[$meta])*
+        #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+        pub enum $name {
+            $($(#[$variant_meta])* $variant,)*
+            /// A value not defined by MCCS, as used by some manufacturers.
+            Other(u8),
+        }
+
+        impl $name {
+            /// The values defined by MCCS and their names.
+            pub const VALUES: &'static [(u8, &'static str)] = &[$(($value, $value_name),)*];
+
+            /// Replaces an `Other` value that MCCS defines with its variant.
+            ///
+            /// Values decoded from the display are always normalized.
+            pub fn normalize(self) -> Self {
+                u8::from(self).into()
+            }
+
+            /// The MCCS name of this value.
+            pub fn name(&self) -> Option<&'static str> {
+                let value = u8::from(*self);
+                Self::VALUES.iter().find(|&&(v, _)| v == value).map(|&(_, name)| name)
+            }
+        }
+
+        impl From<u8> for $name {
+            fn from(value: u8) -> Self {
+                match value {
+                    $($value => $name::$variant,)*
+                    value => $name::Other(value),
+                }
+            }
+        }
+
+        impl From<$name> for u8 {
+            fn from(value: $name) -> Self {
+                match value {
+                    $($name::$variant => $value,)*
+                    $name::Other(value) => value,
+                }
+            }
+        }
+
+        impl Feature for $name {
+            type Setting = Self;
+            type Value = Self;
+
+            const CODE: FeatureCode = $code;
+
+            fn decode(value: &VcpValue) -> Self::Value {
+                value.sl.into()
+            }
+
+            fn encode(setting: Self::Setting) -> u16 {
+                u8::from(setting) as u16
+            }
+        }
+    };
+}
+
+non_continuous! {
+    /// The active video input (0x60).
+    InputSource = 0x60;
+    /// Analog video (R/G/B) 1
+    Vga1 = 0x01 => "VGA-1",
+    /// Analog video (R/G/B) 2
+    Vga2 = 0x02 => "VGA-2",
+    /// Digital video (TMDS) 1, DVI
+    Dvi1 = 0x03 => "DVI-1",
+    /// Digital video (TMDS) 2, DVI
+    Dvi2 = 0x04 => "DVI-2",
+    /// Composite video 1
+    Composite1 = 0x05 => "Composite-1",
+    /// Composite video 2
+    Composite2 = 0x06 => "Composite-2",
+    /// S-Video 1
+    SVideo1 = 0x07 => "S-Video-1",
+    /// S-Video 2
+    SVideo2 = 0x08 => "S-Video-2",
+    /// Tuner 1
+    Tuner1 = 0x09 => "Tuner-1",
+    /// Tuner 2
+    Tuner2 = 0x0a => "Tuner-2",
+    /// Tuner 3
+    Tuner3 = 0x0b => "Tuner-3",
+    /// Component video (YPrPb/YCrCb) 1
+    Component1 = 0x0c => "Component-1",
+    /// Component video (YPrPb/YCrCb) 2
+    Component2 = 0x0d => "Component-2",
+    /// Component video (YPrPb/YCrCb) 3
+    Component3 = 0x0e => "Component-3",
+    /// DisplayPort 1
+    DisplayPort1 = 0x0f => "DisplayPort-1",
+    /// DisplayPort 2
+    DisplayPort2 = 0x10 => "DisplayPort-2",
+    /// Digital video (TMDS) 3, HDMI 1
+    Hdmi1 = 0x11 => "HDMI-1",
+    /// Digital video (TMDS) 4, HDMI 2
+    Hdmi2 = 0x12 => "HDMI-2",
+}
+
+non_continuous! {
+    /// The display power state (0xD6).
+    PowerMode = 0xd6;
+    /// DPM: On, DPMS: Off
+    On = 0x01 => "On",
+    /// DPM: Off, DPMS: Standby
+    Standby = 0x02 => "Standby",
+    /// DPM: Off, DPMS: Suspend
+    Suspend = 0x03 => "Suspend",
+    /// DPM: Off, DPMS: Off
+    Off = 0x04 => "Off",
+    /// Powered off with the power button.
+    OffHard = 0x05 => "Off (power button)",
+}
+
+non_continuous! {
+    /// The selected color temperature preset (0x14).
+    ColorPreset = 0x14;
+    /// sRGB
+    Srgb = 0x01 => "sRGB",
+    /// The display's native white point.
+    Native = 0x02 => "Display Native",
+    /// 4000 K
+    K4000 = 0x03 => "4000 K",
+    /// 5000 K
+    K5000 = 0x04 => "5000 K",
+    /// 6500 K
+    K6500 = 0x05 => "6500 K",
+    /// 7500 K
+    K7500 = 0x06 => "7500 K",
+    /// 8200 K
+    K8200 = 0x07 => "8200 K",
+    /// 9300 K
+    K9300 = 0x08 => "9300 K",
+    /// 10000 K
+    K10000 = 0x09 => "10000 K",
+    /// 11500 K
+    K11500 = 0x0a => "11500 K",
+    /// User preset 1
+    User1 = 0x0b => "User 1",
+    /// User preset 2
+    User2 = 0x0c => "User 2",
+    /// User preset 3
+    User3 = 0x0d => "User 3",
+}
+
+non_continuous! {
+    /// Audio mute (0x8D).
+    Mute = 0x8d;
+    /// The speakers are muted.
+    Muted = 0x01 => "Mute",
+    /// The speakers are not muted.
+    Unmuted = 0x02 => "Unmute",
+}
+
+impl From<bool> for Mute {
+    fn from(muted: bool) -> Self {
+        match muted {
+            true => Mute::Muted,
+            false => Mute::Unmuted,
+        }
+    }
+}
@@ -0,0 +1,498 @@
+#[cfg(feature = "async")]
+use crate::asynchronous::{AsyncDdcCommand, SendAsyncDdcCommand};
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+use {
+    crate::{
+        commands::{CapabilitiesReply, CapabilitiesRequest, Command, CommandResult, TableRead, TableResponse},
+        DdcCommand, ErrorCode, FeatureCode,
+    },
+    core::fmt,
+};
+
+/// The multi-part value being read.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Source {
+    /// The capabilities string, read with `CapabilitiesRequest`.
+    Capabilities,
+    /// A table VCP feature, read with `TableRead`.
+    Table(FeatureCode),
+}
+
+/// Bounds on a multi-part read, protecting against displays that never send
+/// the terminating empty fragment.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Limits {
+    /// The maximum length of the value, after which the read fails with
+    /// `ErrorCode::ReplyTooLong`.
+    pub max_len: usize,
+    /// Whether a NUL byte terminates the value.
+    pub stop_at_nul: bool,
+}
+
+impl Limits {
+    /// The default limits for capabilities strings, which are NUL terminated
+    /// by some displays.
+    pub fn capabilities() -> Self {
+        Limits {
+            max_len: 0x1000,
+            stop_at_nul: true,
+        }
+    }
+
+    /// The default limits for table values, which may contain NUL bytes and
+    /// span the entire 16-bit offset range.
+    pub fn table() -> Self {
+        Limits {
+            max_len: 0x10000,
+            stop_at_nul: false,
+        }
+    }
+
+    /// The default limits for a source.
+    pub fn for_source(source: Source) -> Self {
+        match source {
+            Source::Capabilities => Self::capabilities(),
+            Source::Table(..) => Self::table(),
+        }
+    }
+}
+
+/// One fragment of a multi-part value.
+#[derive(Copy, Clone, Default)]
+pub struct Fragment {
+    /// The offset of this fragment within the value.
+    pub offset: u16,
+    data: [u8; 32],
+    len: u8,
+}
+
+impl Fragment {
+    fn new(offset: u16, bytes: &[u8]) -> Self {
+        let mut fragment = Fragment {
+            offset,
+            ..Default::default()
+        };
+        fragment.len = bytes.len() as u8;
+        fragment.data[..bytes.len()].copy_from_slice(bytes);
+        fragment
+    }
+
+    /// The contents of the fragment.
+    pub fn bytes(&self) -> &[u8] {
+        &self.data[..self.len as usize]
+    }
+
+    /// The offset of the byte following this fragment.
+    pub fn end(&self) -> usize {
+        self.offset as usize + self.len as usize
+    }
+}
+
+impl fmt::Debug for Fragment {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Fragment")
+            .field("offset", &self.offset)
+            .field("bytes", &self.bytes())
+            .finish()
+    }
+}
+
+impl From<CapabilitiesReply> for Fragment {
+    fn from(reply: CapabilitiesReply) -> Self {
+        Fragment::new(reply.offset, reply.bytes())
+    }
+}
+
+impl From<TableResponse> for Fragment {
+    fn from(table: TableResponse) -> Self {
+        Fragment::new(table.offset, table.bytes())
+    }
+}
+
+/// Decodes either a capabilities or a table reply.
+impl CommandResult for Fragment {
+    type Buffer = [u8; 35 + 3];
+
+    const MAX_LEN: usize = 35;
+
+    fn decode(data: &[u8]) -> Result<Self, ErrorCode> {
+        match data.first() {
+            Some(0xe4) => TableResponse::decode(data).map(From::from),
+            _ => CapabilitiesReply::decode(data).map(From::from),
+        }
+    }
+}
+
+/// Requests the fragment at an offset of a multi-part value.
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct FragmentRequest {
+    source: Source,
+    offset: u16,
+}
+
+impl Command for FragmentRequest {
+    type Buffer = [u8; 4];
+    type Ok = Fragment;
+
+    const DELAY_COMMAND_MS: u64 = TableRead::DELAY_COMMAND_MS;
+    const DELAY_RESPONSE_MS: u64 = TableRead::DELAY_RESPONSE_MS;
+    const MAX_LEN: usize = TableRead::MAX_LEN;
+    const MIN_LEN: usize = CapabilitiesRequest::MIN_LEN;
+
+    fn len(&self) -> usize {
+        match self.source {
+            Source::Capabilities => CapabilitiesRequest::new(self.offset).len(),
+            Source::Table(code) => TableRead::new(code, self.offset).len(),
+        }
+    }
+
+    fn encode(&self, data: &mut [u8]) -> Result<usize, ErrorCode> {
+        match self.source {
+            Source::Capabilities => CapabilitiesRequest::new(self.offset).encode(data),
+            Source::Table(code) => TableRead::new(code, self.offset).encode(data),
+        }
+    }
+
+    fn decode(&self, data: &[u8]) -> Result<Fragment, ErrorCode> {
+        match self.source {
+            Source::Capabilities => CapabilitiesRequest::new(self.offset).decode(data).map(From::from),
+            Source::Table(code) => TableRead::new(code, self.offset).decode(data).map(From::from),
+        }
+    }
+}
+
+/// The progress of a multi-part read, shared by the blocking and
+/// asynchronous readers so that they only differ in how requests are
+/// executed.
+#[derive(Debug, Copy, Clone)]
+struct State {
+    source: Source,
+    limits: Limits,
+    start: u16,
+    offset: usize,
+    done: bool,
+    failed: bool,
+}
+
+impl State {
+    fn new(source: Source) -> Self {
+        State {
+            source,
+            limits: Limits::for_source(source),
+            start: 0,
+            offset: 0,
+            done: false,
+            failed: false,
+        }
+    }
+
+    /// The next fragment to request, if any.
+    fn request(&self) -> Option<Result<FragmentRequest, ErrorCode>> {
+        match self.done || self.failed {
+            true => None,
+            // offsets past the 16-bit range mean the display wrapped around
+            false => Some(
+                u16::try_from(self.offset)
+                    .map(|offset| FragmentRequest {
+                        source: self.source,
+                        offset,
+                    })
+                    .map_err(|_| ErrorCode::InvalidOffset),
+            ),
+        }
+    }
+
+    /// Validates a fragment received from the display.
+    fn accept(&mut self, fragment: Fragment) -> Result<Option<Fragment>, ErrorCode> {
+        if fragment.offset as usize != self.offset {
+            return Err(ErrorCode::OffsetMismatch {
+                expected: self.offset as u16,
+                actual: fragment.offset,
+            })
+        }
+
+        let mut fragment = fragment;
+        if self.limits.stop_at_nul {
+            if let Some(nul) = fragment.bytes().iter().position(|&b| b == 0) {
+                fragment.len = nul as u8;
+                self.done = true;
+            }
+        }
+
+        if fragment.bytes().is_empty() {
+            self.done = true;
+            return Ok(None)
+        }
+
+        if fragment.end() > self.limits.max_len {
+            return Err(ErrorCode::ReplyTooLong {
+                limit: self.limits.max_len,
+            })
+        }
+
+        self.offset = fragment.end();
+        Ok(Some(fragment))
+    }
+
+    fn fail(&mut self) {
+        self.failed = true;
+    }
+
+    /// Fails the read such that resuming it requests `fragment` again.
+    fn fail_at(&mut self, fragment: &Fragment) {
+        self.offset = fragment.offset as usize;
+        self.done = false;
+        self.fail();
+    }
+}
+
+/// Copies a fragment into the caller's buffer, relative to `start`.
+fn copy_fragment(buf: &mut [u8], start: u16, fragment: &Fragment) -> Result<(), ErrorCode> {
+    let offset = (fragment.offset - start) as usize;
+    match buf.get_mut(offset..offset + fragment.bytes().len()) {
+        Some(buf) => {
+            buf.copy_from_slice(fragment.bytes());
+            Ok(())
+        },
+        None => Err(ErrorCode::BufferTooSmall),
+    }
+}
+
+macro_rules! reader_methods {
+    ($progress:ty) => {
+        /// Replaces the limits applied to the read.
+        pub fn limits(mut self, limits: Limits) -> Self {
+            self.state.limits = limits;
+            self
+        }
+
+        /// Starts reading at `offset` rather than the beginning of the value.
+        pub fn start_at(mut self, offset: u16) -> Self {
+            self.state.start = offset;
+            self.state.offset = offset as usize;
+            self
+        }
+
+        /// Calls `progress` with the number of bytes read so far after each
+        /// fragment.
+        pub fn on_progress(mut self, progress: &'a mut $progress) -> Self {
+            self.progress = Some(progress);
+            self
+        }
+
+        /// The multi-part value being read.
+        pub fn source(&self) -> Source {
+            self.state.source
+        }
+
+        /// The offset of the next fragment to be requested.
+        ///
+        /// After an error, this is the offset that a resumed read retries.
+        pub fn offset(&self) -> usize {
+            self.state.offset
+        }
+
+        /// Whether the entire value has been read.
+        pub fn is_done(&self) -> bool {
+            self.state.done
+        }
+
+        /// Continues reading after an error, retrying the fragment that
+        /// failed.
+        pub fn resume(&mut self) {
+            self.state.failed = false;
+        }
+
+        /// Applies the result of requesting the next fragment.
+        fn complete<E: From<ErrorCode>>(&mut self, res: Result<Fragment, E>) -> Option<Result<Fragment, E>> {
+            match res.and_then(|fragment| self.state.accept(fragment).map_err(From::from)) {
+                Ok(Some(fragment)) => {
+                    if let Some(ref mut progress) = self.progress {
+                        progress(self.state.offset);
+                    }
+                    Some(Ok(fragment))
+                },
+                Ok(None) => None,
+                Err(e) => {
+                    self.state.fail();
+                    Some(Err(e))
+                },
+            }
+        }
+
+        /// Copies a fragment into `buf`, relative to the starting offset.
+        fn copy_into(&mut self, buf: &mut [u8], fragment: &Fragment) -> Result<(), ErrorCode> {
+            let res = copy_fragment(buf, self.state.start, fragment);
+            if res.is_err() {
+                self.state.fail_at(fragment);
+            }
+            res
+        }
+    };
+}
+
+/// Reads a capabilities string or table value one fragment at a time.
+///
+/// Iteration stops after the terminating empty fragment, a NUL terminator if
+/// enabled by the `Limits`, or the first error. A failed read can be
+/// continued from the fragment that failed with `resume`, or a new read can
+/// be started from a known offset with `start_at`.
+pub struct Fragments<'a, D: ?Sized> {
+    ddc: &'a mut D,
+    state: State,
+    progress: Option<&'a mut dyn FnMut(usize)>,
+}
+
+impl<'a, D: DdcCommand + ?Sized> Fragments<'a, D>
+where
+    D::Error: From<ErrorCode>,
+{
+    reader_methods! { dyn FnMut(usize) }
+
+    /// Reads the capabilities string.
+    pub fn capabilities(ddc: &'a mut D) -> Self {
+        Self::new(ddc, Source::Capabilities)
+    }
+
+    /// Reads a table value.
+    pub fn table(ddc: &'a mut D, code: FeatureCode) -> Self {
+        Self::new(ddc, Source::Table(code))
+    }
+
+    /// Reads a multi-part value with the default limits for its source.
+    pub fn new(ddc: &'a mut D, source: Source) -> Self {
+        Fragments {
+            ddc,
+            state: State::new(source),
+            progress: None,
+        }
+    }
+
+    /// Reads the remainder of the value into `buf`, returning the number of
+    /// bytes from the starting offset to the end of the value.
+    ///
+    /// Fails with `ErrorCode::BufferTooSmall` if the value does not fit.
+    pub fn read_into(&mut self, buf: &mut [u8]) -> Result<usize, D::Error> {
+        while let Some(fragment) = self.next() {
+            self.copy_into(buf, &fragment?)?;
+        }
+
+        Ok(self.state.offset - self.state.start as usize)
+    }
+
+    /// Appends the remainder of the value to `out`.
+    ///
+    /// On error, `out` retains the fragments read so far.
+    #[cfg(feature = "alloc")]
+    pub fn read_to_end(&mut self, out: &mut Vec<u8>) -> Result<(), D::Error> {
+        for fragment in self {
+            out.extend_from_slice(fragment?.bytes());
+        }
+
+        Ok(())
+    }
+}
+
+impl<'a, D: DdcCommand + ?Sized> Iterator for Fragments<'a, D>
+where
+    D::Error: From<ErrorCode>,
+{
+    type Item = Result<Fragment, D::Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let res = match self.state.request()? {
+            Ok(request) => self.ddc.execute(request),
+            Err(e) => Err(e.into()),
+        };
+
+        self.complete(res)
+    }
+}
+
+macro_rules! async_reader {
+    ($(#[$attr:meta])* $name:ident, $command:ident, $progress:ty, $($bounds:tt)*) => {
+        $(#[$attr])*
+        #[cfg(feature = "async")]
+        pub struct $name<'a, D: ?Sized> {
+            ddc: &'a mut D,
+            state: State,
+            progress: Option<&'a mut $progress>,
+        }
+
+        #[cfg(feature = "async")]
+        impl<'a, D: $command $($bounds)* + ?Sized> $name<'a, D>
+        where
+            D::Error: From<ErrorCode>,
+        {
+            reader_methods! { $progress }
+
+            /// Reads the capabilities string.
+            pub fn capabilities(ddc: &'a mut D) -> Self {
+                Self::new(ddc, Source::Capabilities)
+            }
+
+            /// Reads a table value.
+            pub fn table(ddc: &'a mut D, code: FeatureCode) -> Self {
+                Self::new(ddc, Source::Table(code))
+            }
+
+            /// Reads a multi-part value with the default limits for its source.
+            pub fn new(ddc: &'a mut D, source: Source) -> Self {
+                $name {
+                    ddc,
+                    state: State::new(source),
+                    progress: None,
+                }
+            }
+
+            /// Reads the next fragment, or `None` once the read has finished.
+            pub async fn next(&mut self) -> Option<Result<Fragment, D::Error>> {
+                let res = match self.state.request()? {
+                    Ok(request) => $command::execute(self.ddc, request).await,
+                    Err(e) => Err(e.into()),
+                };
+
+                self.complete(res)
+            }
+
+            /// Reads the remainder of the value into `buf`, returning the number of
+            /// bytes from the starting offset to the end of the value.
+            ///
+            /// Fails with `ErrorCode::BufferTooSmall` if the value does not fit.
+            pub async fn read_into(&mut self, buf: &mut [u8]) -> Result<usize, D::Error> {
+                while let Some(fragment) = self.next().await {
+                    self.copy_into(buf, &fragment?)?;
+                }
+
+                Ok(self.state.offset - self.state.start as usize)
+            }
+
+            /// Appends the remainder of the value to `out`.
+            ///
+            /// On error, `out` retains the fragments read so far.
+            #[cfg(feature = "alloc")]
+            pub async fn read_to_end(&mut self, out: &mut Vec<u8>) -> Result<(), D::Error> {
+                while let Some(fragment) = self.next().await {
+                    out.extend_from_slice(fragment?.bytes());
+                }
+
+                Ok(())
+            }
+        }
+    };
+}
+
+async_reader! {
+    /// Asynchronously reads a capabilities string or table value one fragment
+    /// at a time.
+    ///
+    /// See `Fragments` for details.
+    AsyncFragments, AsyncDdcCommand, dyn FnMut(usize),
+}
+
+async_reader! {
+    /// A `Send` variant of `AsyncFragments`, for reads on a multithreaded
+    /// executor.
+    SendAsyncFragments, SendAsyncDdcCommand, dyn FnMut(usize) + Send, + Send
+}
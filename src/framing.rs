@@ -0,0 +1,77 @@
+use {
+    crate::{ErrorCode, I2C_ADDRESS_DDC_CI, SUB_ADDRESS_DDC_CI, VIRTUAL_HOST_ADDRESS},
+    core::iter,
+};
+
+/// The framing of DDC/CI packets sent in one direction.
+///
+/// A packet is the source address, the payload length with its high bit set,
+/// the payload, and an XOR checksum seeded with the destination address.
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct Framing {
+    address: u8,
+    seed: u8,
+}
+
+/// Host to display requests.
+pub(crate) const REQUEST: Framing = Framing {
+    address: SUB_ADDRESS_DDC_CI,
+    seed: (I2C_ADDRESS_DDC_CI as u8) << 1,
+};
+
+/// Display to host replies.
+pub(crate) const REPLY: Framing = Framing {
+    address: (I2C_ADDRESS_DDC_CI as u8) << 1,
+    seed: VIRTUAL_HOST_ADDRESS,
+};
+
+/// Computes a DDC/CI packet checksum.
+pub(crate) fn checksum<II: IntoIterator<Item = u8>>(iter: II) -> u8 {
+    iter.into_iter().fold(0u8, |sum, v| sum ^ v)
+}
+
+impl Framing {
+    fn checksum(self, header_and_payload: &[u8]) -> u8 {
+        checksum(iter::once(self.seed).chain(header_and_payload.iter().cloned()))
+    }
+
+    /// Frames `payload` into `packet`, which must be at least 3 bytes larger.
+    pub(crate) fn encode<'a>(self, payload: &[u8], packet: &'a mut [u8]) -> &'a [u8] {
+        let len = payload.len();
+        packet[0] = self.address;
+        packet[1] = 0x80 | len as u8;
+        packet[2..2 + len].copy_from_slice(payload);
+        packet[2 + len] = self.checksum(&packet[..2 + len]);
+
+        &packet[..3 + len]
+    }
+
+    /// Validates the address, length and checksum of a packet, returning the
+    /// length of its payload. Any trailing bytes after the checksum are
+    /// ignored.
+    pub(crate) fn payload_len(self, packet: &[u8]) -> Result<usize, ErrorCode> {
+        if packet.len() < 3 {
+            return Err(ErrorCode::InvalidLength)
+        }
+
+        if packet[0] != self.address {
+            return Err(ErrorCode::InvalidData)
+        }
+
+        let len = (packet[1] & 0x7f) as usize;
+        if packet[1] & 0x80 == 0 {
+            return Err(ErrorCode::InvalidLength)
+        } else if packet.len() < len + 3 {
+            return Err(ErrorCode::LengthMismatch {
+                expected: len + 3,
+                actual: packet.len(),
+            })
+        }
+
+        if self.checksum(&packet[..2 + len]) != packet[2 + len] {
+            return Err(ErrorCode::InvalidChecksum)
+        }
+
+        Ok(len)
+    }
+}
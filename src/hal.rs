@@ -0,0 +1,79 @@
+use {
+    crate::{
+        i2c::{I2cBus, I2cDdc, I2cError},
+        Timer,
+    },
+    core::time::Duration,
+    embedded_hal::{
+        delay::DelayNs,
+        i2c::{I2c, Operation},
+    },
+};
+
+/// An `I2cBus` backed by an `embedded_hal::i2c::I2c` implementation.
+///
+/// Only 7-bit addresses are supported, and EDID segments beyond the first
+/// can't be read as `embedded-hal` can't address the E-DDC segment pointer
+/// and the EDID in a single transfer.
+#[derive(Clone, Debug)]
+pub struct HalI2c<I>(pub I);
+
+impl<I: I2c> I2cBus for HalI2c<I> {
+    type Error = I::Error;
+
+    fn write(&mut self, address: u16, data: &[u8]) -> Result<(), Self::Error> {
+        self.0.write(address as u8, data)
+    }
+
+    fn read(&mut self, address: u16, data: &mut [u8]) -> Result<usize, Self::Error> {
+        self.0.read(address as u8, data).map(|()| data.len())
+    }
+
+    fn write_read(&mut self, address: u16, data: &[u8], out: &mut [u8]) -> Result<usize, Self::Error> {
+        self.0.write_read(address as u8, data, out).map(|()| out.len())
+    }
+
+    /// `embedded-hal` transactions are limited to a single address, so the
+    /// writes can only be combined when both go to the same device. E-DDC
+    /// segment reads fail with `I2cError::Unsupported`.
+    fn write_write_read(
+        &mut self,
+        first_address: u16,
+        first: &[u8],
+        address: u16,
+        data: &[u8],
+        out: &mut [u8],
+    ) -> Result<usize, I2cError<Self::Error>> {
+        if first_address != address {
+            return Err(I2cError::Unsupported)
+        }
+
+        let mut operations = [Operation::Write(first), Operation::Write(data), Operation::Read(out)];
+        self.0
+            .transaction(address as u8, &mut operations)
+            .map_err(I2cError::I2c)?;
+
+        Ok(out.len())
+    }
+}
+
+/// A `Timer` backed by an `embedded_hal::delay::DelayNs` implementation.
+#[derive(Clone, Debug)]
+pub struct HalDelay<D>(pub D);
+
+impl<D: DelayNs> Timer for HalDelay<D> {
+    fn sleep(&mut self, duration: Duration) {
+        self.0.delay_us(duration.as_micros().try_into().unwrap_or(u32::MAX))
+    }
+}
+
+/// DDC/CI over an `embedded-hal` I2C bus and delay provider.
+pub type HalDdc<I, D> = I2cDdc<HalI2c<I>, HalDelay<D>>;
+
+impl<I: I2c, D: DelayNs> HalDdc<I, D> {
+    /// Wraps an I2C bus that the display is connected to, using `delay` for
+    /// the specification delays.
+    pub fn from_hal(i2c: I, delay: D) -> Self {
+        Self::with_timer(HalI2c(i2c), HalDelay(delay))
+    }
+}
@@ -0,0 +1,236 @@
+#[cfg(feature = "std")]
+use {crate::StdTimer, std::error};
+use {
+    crate::{
+        DdcCommand, DdcCommandMarker, DdcCommandRaw, DdcCommandRawMarker, DdcHost, Delay, Eddc, Edid, ErrorCode, Timer,
+        I2C_ADDRESS_DDC_CI, I2C_ADDRESS_EDID, I2C_ADDRESS_EDID_SEGMENT,
+    },
+    core::{fmt, time::Duration},
+};
+
+/// A bus capable of I2C master transfers.
+///
+/// Addresses are 7-bit I2C addresses.
+pub trait I2cBus {
+    /// An error that can occur during a transfer.
+    type Error;
+
+    /// Writes `data` to the device at `address`.
+    fn write(&mut self, address: u16, data: &[u8]) -> Result<(), Self::Error>;
+
+    /// Reads from the device at `address`, returning the number of bytes read
+    /// into `data`.
+    fn read(&mut self, address: u16, data: &mut [u8]) -> Result<usize, Self::Error>;
+
+    /// Writes `data` to the device at `address`, then reads the reply into
+    /// `out`.
+    ///
+    /// Buses that support a repeated start condition should override this to
+    /// perform a combined transfer.
+    fn write_read(&mut self, address: u16, data: &[u8], out: &mut [u8]) -> Result<usize, Self::Error> {
+        self.write(address, data)?;
+        self.read(address, out)
+    }
+
+    /// Writes `first` to the device at `first_address` and `data` to the
+    /// device at `address`, then reads the reply into `out`.
+    ///
+    /// This is used for E-DDC reads, which must select the segment and offset
+    /// in a single transfer with repeated start conditions, as the segment
+    /// pointer is reset by a stop condition. Buses that support this should
+    /// override this method, as by default transfers to two different
+    /// addresses fail with `I2cError::Unsupported`.
+    fn write_write_read(
+        &mut self,
+        first_address: u16,
+        first: &[u8],
+        address: u16,
+        data: &[u8],
+        out: &mut [u8],
+    ) -> Result<usize, I2cError<Self::Error>> {
+        if first_address != address {
+            return Err(I2cError::Unsupported)
+        }
+
+        self.write(first_address, first).map_err(I2cError::I2c)?;
+        self.write_read(address, data, out).map_err(I2cError::I2c)
+    }
+}
+
+impl<I: I2cBus + ?Sized> I2cBus for &mut I {
+    type Error = I::Error;
+
+    fn write(&mut self, address: u16, data: &[u8]) -> Result<(), Self::Error> {
+        (**self).write(address, data)
+    }
+
+    fn read(&mut self, address: u16, data: &mut [u8]) -> Result<usize, Self::Error> {
+        (**self).read(address, data)
+    }
+
+    fn write_read(&mut self, address: u16, data: &[u8], out: &mut [u8]) -> Result<usize, Self::Error> {
+        (**self).write_read(address, data, out)
+    }
+
+    fn write_write_read(
+        &mut self,
+        first_address: u16,
+        first: &[u8],
+        address: u16,
+        data: &[u8],
+        out: &mut [u8],
+    ) -> Result<usize, I2cError<Self::Error>> {
+        (**self).write_write_read(first_address, first, address, data, out)
+    }
+}
+
+/// An error that can occur when communicating over an `I2cBus`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum I2cError<E> {
+    /// The underlying bus failed.
+    I2c(E),
+    /// The DDC/CI protocol failed.
+    Ddc(ErrorCode),
+    /// The bus can't perform the transfer, such as an E-DDC read that
+    /// addresses two devices in a single transfer.
+    Unsupported,
+}
+
+impl<E> From<ErrorCode> for I2cError<E> {
+    fn from(e: ErrorCode) -> Self {
+        I2cError::Ddc(e)
+    }
+}
+
+impl<E: fmt::Display> fmt::Display for I2cError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            I2cError::I2c(ref e) => write!(f, "I2C error: {}", e),
+            I2cError::Ddc(ref e) => fmt::Display::fmt(e, f),
+            I2cError::Unsupported => f.write_str("transfer not supported by the I2C bus"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<E: error::Error + 'static> error::Error for I2cError<E> {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            I2cError::I2c(ref e) => Some(e),
+            I2cError::Ddc(ref e) => Some(e),
+            I2cError::Unsupported => None,
+        }
+    }
+}
+
+/// Implements the DDC traits on top of an `I2cBus`.
+///
+/// The specification delays are waited out using the `Timer`.
+#[derive(Clone, Debug)]
+pub struct I2cDdc<I, T> {
+    inner: I,
+    timer: T,
+    delay: Delay,
+}
+
+#[cfg(feature = "std")]
+impl<I> I2cDdc<I, StdTimer> {
+    /// Wraps an I2C bus that the display is connected to.
+    pub fn new(inner: I) -> Self {
+        Self::with_timer(inner, StdTimer)
+    }
+}
+
+impl<I, T> I2cDdc<I, T> {
+    /// Wraps an I2C bus that the display is connected to, using `timer` for
+    /// delays.
+    pub fn with_timer(inner: I, timer: T) -> Self {
+        I2cDdc {
+            inner,
+            timer,
+            delay: Default::default(),
+        }
+    }
+
+    /// Consumes the adapter, returning the underlying bus and timer.
+    pub fn into_inner(self) -> (I, T) {
+        (self.inner, self.timer)
+    }
+
+    /// Borrows the underlying bus.
+    pub fn inner_ref(&self) -> &I {
+        &self.inner
+    }
+
+    /// Mutably borrows the underlying bus.
+    pub fn inner_mut(&mut self) -> &mut I {
+        &mut self.inner
+    }
+}
+
+impl<I: I2cBus, T: Timer> DdcHost for I2cDdc<I, T> {
+    type Error = I2cError<I::Error>;
+
+    fn sleep(&mut self) {
+        self.delay.sleep_with(&mut self.timer)
+    }
+}
+
+impl<I: I2cBus, T: Timer> DdcCommandRaw for I2cDdc<I, T> {
+    fn execute_raw<'a>(
+        &mut self,
+        data: &[u8],
+        out: &'a mut [u8],
+        response_delay: Duration,
+    ) -> Result<&'a mut [u8], Self::Error> {
+        if data.len() > 0x7f {
+            return Err(ErrorCode::InvalidLength.into())
+        }
+
+        let mut packet = [0u8; 0x7f + 3];
+        let packet = Self::encode_command(data, &mut packet);
+
+        self.sleep();
+        // the sub-address is sent as the first byte of the write
+        self.inner.write(I2C_ADDRESS_DDC_CI, packet).map_err(I2cError::I2c)?;
+
+        if out.is_empty() {
+            return Ok(out)
+        }
+
+        self.timer.sleep(response_delay);
+        let len = self.inner.read(I2C_ADDRESS_DDC_CI, out).map_err(I2cError::I2c)?;
+
+        Ok(Self::decode_response(&mut out[..len])?.unwrap_or_default())
+    }
+}
+
+impl<I: I2cBus, T: Timer> DdcCommandRawMarker for I2cDdc<I, T> {
+    fn set_sleep_delay(&mut self, delay: Delay) {
+        self.delay = delay;
+    }
+}
+
+impl<I: I2cBus, T: Timer> DdcCommandMarker for I2cDdc<I, T> {}
+
+impl<I: I2cBus, T> Edid for I2cDdc<I, T> {
+    type EdidError = I2cError<I::Error>;
+
+    fn read_edid(&mut self, offset: u8, data: &mut [u8]) -> Result<usize, Self::EdidError> {
+        self.inner
+            .write_read(I2C_ADDRESS_EDID, &[offset], data)
+            .map_err(I2cError::I2c)
+    }
+}
+
+impl<I: I2cBus, T> Eddc for I2cDdc<I, T> {
+    fn read_eddc_edid(&mut self, segment: u8, offset: u8, data: &mut [u8]) -> Result<usize, Self::EdidError> {
+        match segment {
+            // displays without E-DDC support don't acknowledge the segment pointer
+            0 => self.read_edid(offset, data),
+            _ => self
+                .inner
+                .write_write_read(I2C_ADDRESS_EDID_SEGMENT, &[segment], I2C_ADDRESS_EDID, &[offset], data),
+        }
+    }
+}
@@ -1,26 +1,99 @@
 #![deny(missing_docs)]
-#![doc(html_root_url = "https://docs.rs/ddc/0.3.0/")]
+#![doc(html_root_url = "https://docs.rs/ddc/0.4.0/")]
+#![cfg_attr(not(feature = "std"), no_std)]
 
 //! Control displays using the DDC/CI protocol.
 //!
 //! Provides generic traits and utilities for working with DDC. See [downstream
 //! crates](https://crates.io/crates/ddc/reverse_dependencies) for usable
 //! concrete implementations.
-
+//!
+//! The `std` feature is enabled by default. Without it the crate is
+//! `no_std`, and the `alloc` feature enables the parts of the API that
+//! allocate.
+//!
+//! # Migrating from 0.3
+//!
+//! `VcpValue`, `VcpValueType` and `FeatureCode` are no longer re-exports of
+//! the `mccs` crate's types, so that they are the same whichever features
+//! are enabled. The `mccs` dependency is now optional, and enabled by the
+//! `std` feature. With it, values convert to and from their `mccs`
+//! counterparts using `From`:
+//!
+//! ```
+//! # #[cfg(feature = "mccs")] {
+//! let value = ddc::VcpValue::from_value(50);
+//! let value: mccs::Value = value.into();
+//! assert_eq!(ddc::VcpValue::from(value).value(), 50);
+//! # }
+//! ```
+
+#[cfg(feature = "alloc")]
+#[macro_use]
+extern crate alloc;
+#[cfg(feature = "mccs")]
 extern crate mccs;
 
-use std::{error, fmt, iter, time::Duration};
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+use core::{fmt, time::Duration};
+#[cfg(feature = "std")]
+pub use delay::StdTimer;
+#[cfg(feature = "std")]
+use std::error;
 pub use {
     self::{
-        commands::{Command, CommandResult, TimingMessage},
-        delay::Delay,
+        commands::{Buffer, Command, CommandResult, TimingMessage},
+        delay::{Delay, Timer},
     },
-    mccs::{FeatureCode, Value as VcpValue, ValueType as VcpValueType},
+    value::{FeatureCode, Value as VcpValue, ValueType as VcpValueType},
 };
 
+/// Decoding of captured DDC/CI and EDID bus traffic.
+#[cfg(feature = "alloc")]
+pub mod analyzer;
+/// Asynchronous versions of the DDC traits, with `Send` variants for
+/// multithreaded executors.
+#[cfg(feature = "async")]
+pub mod asynchronous;
+/// MCCS capabilities string parsing.
+#[cfg(feature = "alloc")]
+pub mod caps;
 /// DDC/CI command request and response types.
 pub mod commands;
+/// MCCS VCP feature definitions.
+#[cfg(feature = "db")]
+pub mod db;
 mod delay;
+/// Object-safe DDC device trait.
+#[cfg(feature = "std")]
+pub mod device;
+/// EDID reading and parsing.
+pub mod edid;
+/// Fault injection for testing error handling.
+#[cfg(feature = "mock")]
+pub mod fault;
+/// Strongly typed VCP features.
+pub mod features;
+/// Bounded multi-part capabilities and table reads.
+pub mod fragments;
+mod framing;
+/// DDC/CI over an `embedded-hal` I2C bus.
+#[cfg(feature = "embedded-hal")]
+pub mod hal;
+/// DDC/CI over a generic I2C bus.
+pub mod i2c;
+/// An emulated display for testing without hardware.
+#[cfg(feature = "mock")]
+pub mod mock;
+/// Display-side DDC/CI protocol handling.
+pub mod responder;
+/// Automatic retries of failed commands.
+pub mod retry;
+/// Recording and replaying of display sessions.
+#[cfg(feature = "std")]
+pub mod trace;
+mod value;
 
 /// EDID EEPROM I2C address
 pub const I2C_ADDRESS_EDID: u16 = 0x50;
@@ -34,6 +107,9 @@ pub const I2C_ADDRESS_DDC_CI: u16 = 0x37;
 /// DDC sub-address command prefix
 pub const SUB_ADDRESS_DDC_CI: u8 = 0x51;
 
+/// DDC/CI virtual host address, used to seed display to host packet checksums
+pub const VIRTUAL_HOST_ADDRESS: u8 = 0x50;
+
 /// DDC delay required before retrying a request
 pub const DELAY_COMMAND_FAILED_MS: u64 = 40;
 
@@ -45,6 +121,18 @@ pub trait Edid {
 
     /// Read up to 256 bytes of the monitor's EDID.
     fn read_edid(&mut self, offset: u8, data: &mut [u8]) -> Result<usize, Self::EdidError>;
+
+    /// Read and validate the EDID base block and its first extension block.
+    ///
+    /// Only the first 256 bytes of the EDID are addressable without E-DDC, so
+    /// any further extension blocks are ignored. Use `Eddc::read_full_edid`
+    /// to read the complete EDID.
+    #[cfg(feature = "alloc")]
+    fn read_edid_blocks(&mut self) -> Result<Vec<u8>, edid::EdidReadError<Self::EdidError>> {
+        edid::read_blocks(2, |block, offset, data| {
+            self.read_edid((block * edid::BLOCK_LEN + offset) as u8, data)
+        })
+    }
 }
 
 /// E-DDC allows reading extensions of Enhanced EDID.
@@ -52,6 +140,16 @@ pub trait Eddc: Edid {
     /// Read part of the EDID using the segments added in the Enhanced Display
     /// Data Channel (E-DDC) protocol.
     fn read_eddc_edid(&mut self, segment: u8, offset: u8, data: &mut [u8]) -> Result<usize, Self::EdidError>;
+
+    /// Read and validate the entire EDID, including all extension blocks.
+    #[cfg(feature = "alloc")]
+    fn read_full_edid(&mut self) -> Result<Vec<u8>, edid::EdidReadError<Self::EdidError>> {
+        edid::read_blocks(0x100, |block, offset, data| {
+            let segment = (block / 2) as u8;
+            let offset = ((block % 2) * edid::BLOCK_LEN + offset) as u8;
+            self.read_eddc_edid(segment, offset, data)
+        })
+    }
 }
 
 /// A DDC host is able to communicate with a DDC device such as a display.
@@ -108,20 +206,27 @@ pub trait DdcCommand: DdcHost {
 
     /// Computes a DDC/CI packet checksum
     fn checksum<II: IntoIterator<Item = u8>>(iter: II) -> u8 {
-        iter.into_iter().fold(0u8, |sum, v| sum ^ v)
+        framing::checksum(iter)
     }
 
     /// Encodes a DDC/CI command into a packet.
     ///
     /// `packet.len()` must be 3 bytes larger than `data.len()`
     fn encode_command<'a>(data: &[u8], packet: &'a mut [u8]) -> &'a [u8] {
-        packet[0] = SUB_ADDRESS_DDC_CI;
-        packet[1] = 0x80 | data.len() as u8;
-        packet[2..2 + data.len()].copy_from_slice(data);
-        packet[2 + data.len()] =
-            Self::checksum(iter::once((I2C_ADDRESS_DDC_CI as u8) << 1).chain(packet[..2 + data.len()].iter().cloned()));
+        framing::REQUEST.encode(data, packet)
+    }
 
-        &packet[..3 + data.len()]
+    /// Decodes a display to host DDC/CI packet, as read from the device.
+    ///
+    /// Validates the source address, length and checksum of the packet, and
+    /// returns the subslice of `packet` containing the payload. Any trailing
+    /// bytes after the checksum are ignored. The DDC/CI null message is
+    /// returned as `None`.
+    fn decode_response(packet: &mut [u8]) -> Result<Option<&mut [u8]>, ErrorCode> {
+        Ok(match framing::REPLY.payload_len(packet)? {
+            0 => None,
+            len => Some(&mut packet[2..2 + len]),
+        })
     }
 }
 
@@ -138,8 +243,31 @@ pub trait Ddc: DdcHost {
     /// Retrieve the capability string from the device.
     ///
     /// This executes multiple `CapabilitiesRequest` commands to construct the entire string.
+    #[cfg(feature = "alloc")]
     fn capabilities_string(&mut self) -> Result<Vec<u8>, Self::Error>;
 
+    /// Retrieve the capability string from the device into `buf` without
+    /// allocating, returning its length.
+    ///
+    /// Fails with `ErrorCode::BufferTooSmall` if the string does not fit.
+    fn capabilities_string_into(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error>
+    where
+        Self: DdcCommand,
+        Self::Error: From<ErrorCode>,
+    {
+        fragments::Fragments::capabilities(self).read_into(buf)
+    }
+
+    /// Retrieve and parse the capability string from the device.
+    #[cfg(feature = "alloc")]
+    fn capabilities(&mut self) -> Result<caps::Capabilities, Self::Error>
+    where
+        Self::Error: From<ErrorCode>,
+    {
+        self.capabilities_string()
+            .and_then(|caps| caps::Capabilities::parse(&caps).map_err(|e| ErrorCode::InvalidCapabilities(e).into()))
+    }
+
     /// Gets the current value of an MCCS VCP feature.
     fn get_vcp_feature(&mut self, code: FeatureCode) -> Result<VcpValue, Self::Error>;
 
@@ -151,6 +279,31 @@ pub trait Ddc: DdcHost {
 
     /// Retrieves a timing report from the device.
     fn get_timing_report(&mut self) -> Result<TimingMessage, Self::Error>;
+
+    /// Requests the device's identification data.
+    fn identification(&mut self) -> Result<commands::IdentificationReply, Self::Error>;
+
+    /// Instructs the device to run its self-test, returning the result.
+    fn self_test(&mut self) -> Result<commands::SelfTestReply, Self::Error>;
+
+    /// Enables or disables application reports from the device.
+    fn enable_application_report(&mut self, enable: bool) -> Result<(), Self::Error>;
+
+    /// Gets the current value of a typed VCP feature.
+    fn get_feature<F: features::Feature>(&mut self) -> Result<F::Value, Self::Error>
+    where
+        Self: Sized,
+    {
+        self.get_vcp_feature(F::CODE).map(|value| F::decode(&value))
+    }
+
+    /// Sets a typed VCP feature to the specified value.
+    fn set_feature<F: features::Feature>(&mut self, setting: F::Setting) -> Result<(), Self::Error>
+    where
+        Self: Sized,
+    {
+        self.set_vcp_feature(F::CODE, F::encode(setting))
+    }
 }
 
 /// Table commands can read and write arbitrary binary data to a VCP feature.
@@ -158,14 +311,28 @@ pub trait Ddc: DdcHost {
 /// Tables were introduced in MCCS specification versions 3.0 and 2.2.
 pub trait DdcTable: DdcHost {
     /// Read a table value from the device.
+    #[cfg(feature = "alloc")]
     fn table_read(&mut self, code: FeatureCode) -> Result<Vec<u8>, Self::Error>;
 
+    /// Read a table value from the device into `buf` without allocating,
+    /// returning its length.
+    ///
+    /// Fails with `ErrorCode::BufferTooSmall` if the value does not fit.
+    fn table_read_into(&mut self, code: FeatureCode, buf: &mut [u8]) -> Result<usize, Self::Error>
+    where
+        Self: DdcCommand,
+        Self::Error: From<ErrorCode>,
+    {
+        fragments::Fragments::table(self, code).read_into(buf)
+    }
+
     /// Write a table value to the device.
     fn table_write(&mut self, code: FeatureCode, offset: u16, value: &[u8]) -> Result<(), Self::Error>;
 }
 
 /// DDC/CI protocol errors
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[non_exhaustive]
 pub enum ErrorCode {
     /// Expected matching offset from DDC/CI
     InvalidOffset,
@@ -173,26 +340,127 @@ pub enum ErrorCode {
     InvalidLength,
     /// Checksum mismatch
     InvalidChecksum,
-    /// Expected opcode mismatch
-    InvalidOpcode,
     /// Expected data mismatch
     InvalidData,
+    /// Expected opcode mismatch
+    #[deprecated(note = "replies with the wrong opcode are reported as `ErrorCode::OpcodeMismatch`")]
+    InvalidOpcode,
+    /// The reply had an unexpected length
+    LengthMismatch {
+        /// The length of a valid reply
+        expected: usize,
+        /// The length of the reply received
+        actual: usize,
+    },
+    /// The reply had an unexpected opcode
+    OpcodeMismatch {
+        /// The opcode of a valid reply
+        expected: u8,
+        /// The opcode of the reply received
+        actual: u8,
+    },
+    /// The reply was for a different offset than requested
+    OffsetMismatch {
+        /// The requested offset
+        expected: u16,
+        /// The offset of the reply received
+        actual: u16,
+    },
+    /// The display does not support the VCP feature
+    UnsupportedFeature(FeatureCode),
+    /// The display replied with a result code not defined by MCCS
+    UnknownResultCode(u8),
+    /// The display replied with the null message, usually because it was busy
+    NullResponse,
+    /// The reply was for a different VCP feature than requested
+    WrongFeature {
+        /// The requested feature
+        expected: FeatureCode,
+        /// The feature of the reply received
+        actual: FeatureCode,
+    },
+    /// The caller's buffer is too small to hold the reply
+    BufferTooSmall,
+    /// A multi-part reply exceeded the configured maximum length
+    ReplyTooLong {
+        /// The maximum length
+        limit: usize,
+    },
     /// Custom unspecified error
-    Invalid(String),
+    #[cfg(feature = "alloc")]
+    Invalid(alloc::string::String),
+    /// The capabilities string could not be parsed
+    #[cfg(feature = "alloc")]
+    InvalidCapabilities(caps::CapabilitiesError),
 }
 
+impl ErrorCode {
+    /// Whether the error is likely transient, such that repeating the command
+    /// may succeed.
+    #[allow(deprecated)]
+    pub fn is_retryable(&self) -> bool {
+        match *self {
+            ErrorCode::InvalidOffset
+            | ErrorCode::InvalidLength
+            | ErrorCode::InvalidChecksum
+            | ErrorCode::InvalidData
+            | ErrorCode::InvalidOpcode
+            | ErrorCode::LengthMismatch { .. }
+            | ErrorCode::OpcodeMismatch { .. }
+            | ErrorCode::OffsetMismatch { .. }
+            | ErrorCode::NullResponse
+            | ErrorCode::WrongFeature { .. } => true,
+            ErrorCode::UnsupportedFeature(..)
+            | ErrorCode::UnknownResultCode(..)
+            | ErrorCode::BufferTooSmall
+            | ErrorCode::ReplyTooLong { .. } => false,
+            #[cfg(feature = "alloc")]
+            ErrorCode::Invalid(..) | ErrorCode::InvalidCapabilities(..) => false,
+        }
+    }
+
+    /// Whether the display reported that it does not support the feature.
+    pub fn is_unsupported(&self) -> bool {
+        matches!(*self, ErrorCode::UnsupportedFeature(..))
+    }
+}
+
+#[cfg(feature = "std")]
 impl error::Error for ErrorCode {}
 
 impl fmt::Display for ErrorCode {
+    #[allow(deprecated)]
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        f.write_str(match *self {
-            ErrorCode::InvalidOffset => "invalid offset returned from DDC/CI",
-            ErrorCode::InvalidLength => "invalid DDC/CI length",
-            ErrorCode::InvalidChecksum => "DDC/CI checksum mismatch",
-            ErrorCode::InvalidOpcode => "DDC/CI VCP opcode mismatch",
-            ErrorCode::InvalidData => "invalid DDC/CI data",
-            ErrorCode::Invalid(ref s) => s,
-        })
+        match *self {
+            ErrorCode::InvalidOffset => f.write_str("invalid offset returned from DDC/CI"),
+            ErrorCode::InvalidLength => f.write_str("invalid DDC/CI length"),
+            ErrorCode::InvalidChecksum => f.write_str("DDC/CI checksum mismatch"),
+            ErrorCode::InvalidData => f.write_str("invalid DDC/CI data"),
+            ErrorCode::InvalidOpcode => f.write_str("DDC/CI VCP opcode mismatch"),
+            ErrorCode::LengthMismatch { expected, actual } =>
+                write!(f, "DDC/CI length mismatch: expected {}, got {}", expected, actual),
+            ErrorCode::OpcodeMismatch { expected, actual } => write!(
+                f,
+                "DDC/CI opcode mismatch: expected 0x{:02x}, got 0x{:02x}",
+                expected, actual
+            ),
+            ErrorCode::OffsetMismatch { expected, actual } =>
+                write!(f, "DDC/CI offset mismatch: expected {}, got {}", expected, actual),
+            ErrorCode::UnsupportedFeature(code) => write!(f, "unsupported VCP code 0x{:02x}", code),
+            ErrorCode::UnknownResultCode(rc) => write!(f, "unrecognized VCP result code 0x{:02x}", rc),
+            ErrorCode::NullResponse => f.write_str("DDC/CI null response"),
+            ErrorCode::BufferTooSmall => f.write_str("buffer too small for DDC/CI reply"),
+            ErrorCode::ReplyTooLong { limit } => write!(f, "DDC/CI reply exceeded {} bytes", limit),
+            ErrorCode::WrongFeature { expected, actual } => write!(
+                f,
+                "DDC/CI VCP code mismatch: expected 0x{:02x}, got 0x{:02x}",
+                expected, actual
+            ),
+            #[cfg(feature = "alloc")]
+            ErrorCode::Invalid(ref s) => f.write_str(s),
+            #[cfg(feature = "alloc")]
+            ErrorCode::InvalidCapabilities(ref e) => fmt::Display::fmt(e, f),
+        }
     }
 }
 
@@ -200,22 +468,10 @@ impl<D: DdcCommandMarker> Ddc for D
 where
     D::Error: From<ErrorCode>,
 {
+    #[cfg(feature = "alloc")]
     fn capabilities_string(&mut self) -> Result<Vec<u8>, Self::Error> {
         let mut string = Vec::new();
-        let mut offset = 0;
-        loop {
-            let caps = self.execute(commands::CapabilitiesRequest::new(offset))?;
-            if caps.offset != offset {
-                return Err(ErrorCode::InvalidOffset.into())
-            } else if caps.data.is_empty() {
-                break
-            }
-
-            string.extend(caps.data.iter());
-
-            offset += caps.data.len() as u16;
-        }
-
+        fragments::Fragments::capabilities(self).read_to_end(&mut string)?;
         Ok(string)
     }
 
@@ -234,28 +490,28 @@ where
     fn get_timing_report(&mut self) -> Result<TimingMessage, Self::Error> {
         self.execute(commands::GetTimingReport)
     }
+
+    fn identification(&mut self) -> Result<commands::IdentificationReply, Self::Error> {
+        self.execute(commands::IdentificationRequest)
+    }
+
+    fn self_test(&mut self) -> Result<commands::SelfTestReply, Self::Error> {
+        self.execute(commands::SelfTestRequest)
+    }
+
+    fn enable_application_report(&mut self, enable: bool) -> Result<(), Self::Error> {
+        self.execute(commands::EnableApplicationReport::new(enable))
+    }
 }
 
 impl<D: DdcCommandMarker> DdcTable for D
 where
     D::Error: From<ErrorCode>,
 {
+    #[cfg(feature = "alloc")]
     fn table_read(&mut self, code: FeatureCode) -> Result<Vec<u8>, Self::Error> {
         let mut value = Vec::new();
-        let mut offset = 0;
-        loop {
-            let table = self.execute(commands::TableRead::new(code, offset))?;
-            if table.offset != offset {
-                return Err(ErrorCode::InvalidOffset.into())
-            } else if table.bytes().is_empty() {
-                break
-            }
-
-            value.extend(table.bytes().iter());
-
-            offset += table.bytes().len() as u16;
-        }
-
+        fragments::Fragments::table(self, code).read_to_end(&mut value)?;
         Ok(value)
     }
 
@@ -274,41 +530,43 @@ where
     D::Error: From<ErrorCode>,
 {
     fn execute<C: Command>(&mut self, command: C) -> Result<C::Ok, Self::Error> {
-        // TODO: once associated consts work...
-        //let mut data = [0u8; C::MAX_LEN];
-        let mut data = [0u8; 36];
-        command.encode(&mut data)?;
-
-        // TODO: once associated consts work...
-        //let mut out = [0u8; C::Ok::MAX_LEN + 3];
-        let mut out = [0u8; 36 + 3];
-        let out = if C::Ok::MAX_LEN > 0 {
-            &mut out[..C::Ok::MAX_LEN + 3]
-        } else {
-            &mut []
-        };
-        let res = self.execute_raw(
-            &data[..command.len()],
-            out,
-            Duration::from_millis(C::DELAY_RESPONSE_MS as _),
-        );
-        let res = match res {
-            Ok(res) => {
-                self.set_sleep_delay(Delay::new(Duration::from_millis(C::DELAY_COMMAND_MS)));
-                res
-            },
-            Err(e) => {
-                self.set_sleep_delay(Delay::new(Duration::from_millis(DELAY_COMMAND_FAILED_MS)));
-                return Err(e)
-            },
-        };
-
-        let res = C::Ok::decode(res);
-
-        if res.is_err() {
-            self.set_sleep_delay(Delay::new(Duration::from_millis(DELAY_COMMAND_FAILED_MS)));
-        }
+        let (mut data, len) = commands::encode_command(&command)?;
+
+        execute_encoded(self, &command, &data.as_mut()[..len])
+    }
+}
+
+/// Executes an encoded command, setting the appropriate delay afterwards.
+pub(crate) fn execute_encoded<D: DdcCommandRawMarker + ?Sized, C: Command>(
+    ddc: &mut D,
+    command: &C,
+    data: &[u8],
+) -> Result<C::Ok, D::Error>
+where
+    D::Error: From<ErrorCode>,
+{
+    let mut out = commands::reply_buffer(command);
+    let out = commands::reply_slice(out.as_mut(), command.reply_len())?;
+    let res = ddc.execute_raw(data, out, Duration::from_millis(command.delay_response_ms()));
+    let (delay, res) = complete_command(command, res);
+    ddc.set_sleep_delay(delay);
+
+    res
+}
 
-        res.map_err(From::from)
+/// Decodes the outcome of executing a command, returning the delay that must
+/// be waited out before the next command along with the reply.
+pub(crate) fn complete_command<C: Command + ?Sized, E: From<ErrorCode>>(
+    command: &C,
+    res: Result<&mut [u8], E>,
+) -> (Delay, Result<C::Ok, E>) {
+    let failed = Delay::new(Duration::from_millis(DELAY_COMMAND_FAILED_MS));
+    match res {
+        Ok(res) if res.is_empty() && command.reply_len() > 0 => (failed, Err(ErrorCode::NullResponse.into())),
+        Ok(res) => match command.decode(res) {
+            Ok(res) => (Delay::new(Duration::from_millis(command.delay_command_ms())), Ok(res)),
+            Err(e) => (failed, Err(e.into())),
+        },
+        Err(e) => (failed, Err(e)),
     }
 }
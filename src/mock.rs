@@ -0,0 +1,247 @@
+use {
+    crate::{
+        responder, DdcCommand, DdcCommandMarker, DdcCommandRaw, DdcCommandRawMarker, DdcHost, Delay, Eddc, Edid,
+        ErrorCode, FeatureCode, TimingMessage, VcpValue,
+    },
+    alloc::{collections::BTreeMap, vec::Vec},
+    core::{cmp, time::Duration},
+};
+
+/// A VCP feature emulated by a `MockDisplay`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum MockFeature {
+    /// A continuous value ranging from zero to `maximum`.
+    ///
+    /// Attempts to set a value larger than `maximum` are ignored.
+    Continuous {
+        /// The current value.
+        value: u16,
+        /// The maximum value.
+        maximum: u16,
+    },
+    /// A non-continuous value.
+    ///
+    /// `maximum` is reported as-is, and any value may be set.
+    NonContinuous {
+        /// The current value.
+        value: u16,
+        /// The maximum value reported to the host.
+        maximum: u16,
+    },
+    /// A table value that can be accessed with table read and write commands.
+    Table(Vec<u8>),
+}
+
+/// An in-memory emulated display.
+///
+/// `MockDisplay` implements `DdcCommandRaw` and `Eddc`, so the blanket `Ddc`
+/// and `DdcTable` implementations are exercised all the way down to the
+/// packet framing that a real I2C backend would see.
+#[derive(Clone, Debug)]
+pub struct MockDisplay {
+    /// The VCP features supported by the display.
+    pub features: BTreeMap<FeatureCode, MockFeature>,
+    /// The capabilities string served by `CapabilitiesRequest`.
+    pub capabilities: Vec<u8>,
+    /// The raw EDID, consisting of 128 byte blocks.
+    ///
+    /// Each E-DDC segment addresses 256 bytes of this buffer.
+    pub edid: Vec<u8>,
+    /// The reply to `GetTimingReport`.
+    pub timing: TimingMessage,
+    /// The number of `SaveCurrentSettings` commands received.
+    pub save_count: usize,
+    /// The payload of the reply to `IdentificationRequest`.
+    pub identification: Vec<u8>,
+    /// The payload of the reply to `SelfTestRequest`.
+    pub self_test: Vec<u8>,
+    /// Whether application reports were enabled by the host.
+    pub application_report: bool,
+    delay: Delay,
+}
+
+impl MockDisplay {
+    /// Creates a display with no features, capabilities or EDID.
+    pub fn new() -> Self {
+        MockDisplay {
+            features: Default::default(),
+            capabilities: Default::default(),
+            edid: Default::default(),
+            timing: TimingMessage {
+                timing_status: 0,
+                horizontal_frequency: 0,
+                vertical_frequency: 0,
+            },
+            save_count: 0,
+            identification: Default::default(),
+            self_test: Default::default(),
+            application_report: false,
+            delay: Default::default(),
+        }
+    }
+
+    /// Adds or replaces a VCP feature.
+    pub fn set_feature(&mut self, code: FeatureCode, feature: MockFeature) {
+        self.features.insert(code, feature);
+    }
+
+    /// The delay that the host requested be waited out before the next
+    /// command.
+    ///
+    /// The emulated display does not enforce this delay.
+    pub fn pending_delay(&self) -> Duration {
+        self.delay.remaining()
+    }
+
+    /// Copies up to `buf.len()` bytes of `data` starting at `offset`.
+    fn fragment(data: &[u8], offset: u16, buf: &mut [u8]) -> usize {
+        let start = cmp::min(offset as usize, data.len());
+        let end = cmp::min(start + buf.len(), data.len());
+        buf[..end - start].copy_from_slice(&data[start..end]);
+        end - start
+    }
+}
+
+impl Default for MockDisplay {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DdcHost for MockDisplay {
+    type Error = ErrorCode;
+}
+
+impl responder::Handler for MockDisplay {
+    fn get_vcp_feature(&mut self, code: FeatureCode) -> Option<VcpValue> {
+        match self.features.get(&code) {
+            Some(&MockFeature::Continuous { value, maximum })
+            | Some(&MockFeature::NonContinuous { value, maximum }) => Some(VcpValue {
+                ty: 0,
+                mh: (maximum >> 8) as u8,
+                ml: maximum as u8,
+                sh: (value >> 8) as u8,
+                sl: value as u8,
+            }),
+            _ => None,
+        }
+    }
+
+    fn set_vcp_feature(&mut self, code: FeatureCode, new_value: u16) {
+        match self.features.get_mut(&code) {
+            Some(MockFeature::Continuous { value, maximum }) if new_value <= *maximum => *value = new_value,
+            Some(MockFeature::NonContinuous { value, .. }) => *value = new_value,
+            _ => (),
+        }
+    }
+
+    fn save_current_settings(&mut self) {
+        self.save_count += 1;
+    }
+
+    fn capabilities(&mut self, offset: u16, buf: &mut [u8]) -> usize {
+        Self::fragment(&self.capabilities, offset, buf)
+    }
+
+    fn table_read(&mut self, code: FeatureCode, offset: u16, buf: &mut [u8]) -> Option<usize> {
+        match self.features.get(&code) {
+            Some(MockFeature::Table(table)) => Some(Self::fragment(table, offset, buf)),
+            _ => None,
+        }
+    }
+
+    fn table_write(&mut self, code: FeatureCode, offset: u16, data: &[u8]) {
+        if let Some(MockFeature::Table(table)) = self.features.get_mut(&code) {
+            let offset = offset as usize;
+            let end = offset + data.len();
+            if table.len() < end {
+                table.resize(end, 0);
+            }
+            table[offset..end].copy_from_slice(data);
+        }
+    }
+
+    fn timing_report(&mut self) -> Option<TimingMessage> {
+        Some(self.timing.clone())
+    }
+
+    fn identification(&mut self, buf: &mut [u8]) -> Option<usize> {
+        Some(Self::fragment(&self.identification, 0, buf))
+    }
+
+    fn self_test(&mut self, buf: &mut [u8]) -> Option<usize> {
+        Some(Self::fragment(&self.self_test, 0, buf))
+    }
+
+    fn enable_application_report(&mut self, enable: bool) {
+        self.application_report = enable;
+    }
+}
+
+impl DdcCommandRaw for MockDisplay {
+    fn execute_raw<'a>(
+        &mut self,
+        data: &[u8],
+        out: &'a mut [u8],
+        _response_delay: Duration,
+    ) -> Result<&'a mut [u8], Self::Error> {
+        if data.len() > 0x7f {
+            return Err(ErrorCode::InvalidLength)
+        }
+
+        let mut packet = [0u8; 0x7f + 3];
+        let packet = Self::encode_command(data, &mut packet);
+        let mut reply = [0u8; 0x7f + 3];
+        let reply = responder::respond(self, packet, &mut reply)?;
+
+        if out.is_empty() {
+            return Ok(out)
+        }
+
+        // commands without a reply read back the null message
+        let mut null = [0u8; 3];
+        let reply = match reply {
+            [] => responder::encode_reply(&[], &mut null)?,
+            reply => reply,
+        };
+        if out.len() < reply.len() {
+            return Err(ErrorCode::InvalidLength)
+        }
+        out[..reply.len()].copy_from_slice(reply);
+
+        Ok(Self::decode_response(out)?.unwrap_or_default())
+    }
+}
+
+impl DdcCommandRawMarker for MockDisplay {
+    fn set_sleep_delay(&mut self, delay: Delay) {
+        self.delay = delay;
+    }
+}
+
+impl DdcCommandMarker for MockDisplay {}
+
+impl Edid for MockDisplay {
+    type EdidError = ErrorCode;
+
+    fn read_edid(&mut self, offset: u8, data: &mut [u8]) -> Result<usize, Self::EdidError> {
+        self.read_eddc_edid(0, offset, data)
+    }
+}
+
+impl Eddc for MockDisplay {
+    fn read_eddc_edid(&mut self, segment: u8, offset: u8, data: &mut [u8]) -> Result<usize, Self::EdidError> {
+        let start = segment as usize * 0x100 + offset as usize;
+        if start >= self.edid.len() {
+            return Err(ErrorCode::InvalidOffset)
+        }
+
+        let end = cmp::min(
+            cmp::min(start + data.len(), (segment as usize + 1) * 0x100),
+            self.edid.len(),
+        );
+        data[..end - start].copy_from_slice(&self.edid[start..end]);
+
+        Ok(end - start)
+    }
+}
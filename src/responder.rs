@@ -0,0 +1,153 @@
+use crate::{
+    commands::{EncodeResult, GetVcpFeature},
+    framing, ErrorCode, FeatureCode, TimingMessage, VcpValue,
+};
+
+/// The display side of the DDC/CI commands, called by `respond`.
+///
+/// Only `get_vcp_feature` and `set_vcp_feature` must be implemented. The
+/// remaining commands are unsupported by default, and are answered with the
+/// null message where the protocol expects a reply.
+pub trait Handler {
+    /// Reads a VCP feature, or `None` if the feature is unsupported.
+    fn get_vcp_feature(&mut self, code: FeatureCode) -> Option<VcpValue>;
+
+    /// Sets a VCP feature.
+    fn set_vcp_feature(&mut self, code: FeatureCode, value: u16);
+
+    /// Saves the current settings.
+    fn save_current_settings(&mut self) {}
+
+    /// Copies the capabilities string starting at `offset` into `buf`,
+    /// returning the number of bytes copied.
+    ///
+    /// Returning 0 signals the end of the string.
+    fn capabilities(&mut self, offset: u16, buf: &mut [u8]) -> usize {
+        let _ = (offset, buf);
+        0
+    }
+
+    /// Copies a table value starting at `offset` into `buf`, returning the
+    /// number of bytes copied, or `None` if the feature is unsupported.
+    fn table_read(&mut self, code: FeatureCode, offset: u16, buf: &mut [u8]) -> Option<usize> {
+        let _ = (code, offset, buf);
+        None
+    }
+
+    /// Writes part of a table value.
+    fn table_write(&mut self, code: FeatureCode, offset: u16, data: &[u8]) {
+        let _ = (code, offset, data);
+    }
+
+    /// The current timing report, or `None` if unsupported.
+    fn timing_report(&mut self) -> Option<TimingMessage> {
+        None
+    }
+
+    /// Copies the identification data into `buf`, returning the number of
+    /// bytes copied, or `None` if unsupported.
+    fn identification(&mut self, buf: &mut [u8]) -> Option<usize> {
+        let _ = buf;
+        None
+    }
+
+    /// Runs the display self-test, copying the result into `buf` and
+    /// returning the number of bytes copied, or `None` if unsupported.
+    fn self_test(&mut self, buf: &mut [u8]) -> Option<usize> {
+        let _ = buf;
+        None
+    }
+
+    /// Enables or disables application reports.
+    fn enable_application_report(&mut self, enable: bool) {
+        let _ = enable;
+    }
+}
+
+/// Validates a host to display packet, as produced by
+/// `DdcCommand::encode_command`, and returns its payload.
+pub fn decode_request(packet: &[u8]) -> Result<&[u8], ErrorCode> {
+    let len = framing::REQUEST.payload_len(packet)?;
+    Ok(&packet[2..2 + len])
+}
+
+/// Frames a display to host reply into `out`.
+///
+/// An empty `payload` produces the null message.
+pub fn encode_reply<'a>(payload: &[u8], out: &'a mut [u8]) -> Result<&'a [u8], ErrorCode> {
+    if payload.len() > 0x7f {
+        return Err(ErrorCode::InvalidLength)
+    } else if out.len() < payload.len() + 3 {
+        return Err(ErrorCode::BufferTooSmall)
+    }
+
+    Ok(framing::REPLY.encode(payload, out))
+}
+
+/// Handles a host to display packet, writing the framed reply into `out`.
+///
+/// Returns the reply to be read by the host, which is empty for commands that
+/// have no reply. Unsupported and unrecognized commands are answered with the
+/// null message, except for unsupported VCP features which are reported with
+/// the "unsupported" result code.
+pub fn respond<'a, H: Handler + ?Sized>(
+    handler: &mut H,
+    packet: &[u8],
+    out: &'a mut [u8],
+) -> Result<&'a [u8], ErrorCode> {
+    let data = decode_request(packet)?;
+
+    let mut reply = [0u8; 0x7f];
+    let len = match *data {
+        [0x01, code] => GetVcpFeature::new(code).encode_reply(handler.get_vcp_feature(code).as_ref(), &mut reply)?,
+        [0x03, code, vh, vl] => {
+            handler.set_vcp_feature(code, ((vh as u16) << 8) | vl as u16);
+            return Ok(&[])
+        },
+        [0x07] => match handler.timing_report() {
+            Some(timing) => timing.encode_reply(&mut reply)?,
+            None => 0,
+        },
+        [0x0c] => {
+            handler.save_current_settings();
+            return Ok(&[])
+        },
+        [0xb1] => match handler.self_test(&mut reply[1..33]) {
+            Some(len) => {
+                reply[0] = 0xa1;
+                1 + len
+            },
+            None => 0,
+        },
+        [0xe2, code, oh, ol] => match handler.table_read(code, ((oh as u16) << 8) | ol as u16, &mut reply[3..35]) {
+            Some(len) => {
+                reply[..3].copy_from_slice(&[0xe4, oh, ol]);
+                3 + len
+            },
+            None => 0,
+        },
+        [0xe7, code, oh, ol, ref value @ ..] => {
+            handler.table_write(code, ((oh as u16) << 8) | ol as u16, value);
+            return Ok(&[])
+        },
+        [0xf1] => match handler.identification(&mut reply[1..33]) {
+            Some(len) => {
+                reply[0] = 0xe1;
+                1 + len
+            },
+            None => 0,
+        },
+        [0xf3, oh, ol] => {
+            let len = handler.capabilities(((oh as u16) << 8) | ol as u16, &mut reply[3..35]);
+            reply[..3].copy_from_slice(&[0xe3, oh, ol]);
+            3 + len
+        },
+        [0xf5, enable] => {
+            handler.enable_application_report(enable != 0);
+            return Ok(&[])
+        },
+        _ => 0,
+    };
+
+    encode_reply(reply.get(..len).ok_or(ErrorCode::InvalidLength)?, out)
+}
@@ -0,0 +1,263 @@
+#[cfg(feature = "std")]
+use crate::StdTimer;
+use {
+    crate::{
+        commands::encode_command, execute_encoded, i2c::I2cError, Command, DdcCommand, DdcCommandMarker, DdcCommandRaw,
+        DdcCommandRawMarker, DdcHost, Eddc, Edid, ErrorCode, Timer,
+    },
+    core::time::Duration,
+};
+
+/// Errors that may wrap a DDC/CI protocol error.
+pub trait RetryableError {
+    /// The protocol error, or `None` if the transport itself failed.
+    fn error_code(&self) -> Option<&ErrorCode>;
+}
+
+impl RetryableError for ErrorCode {
+    fn error_code(&self) -> Option<&ErrorCode> {
+        Some(self)
+    }
+}
+
+impl<E> RetryableError for I2cError<E> {
+    fn error_code(&self) -> Option<&ErrorCode> {
+        match *self {
+            I2cError::I2c(..) | I2cError::Unsupported => None,
+            I2cError::Ddc(ref e) => Some(e),
+        }
+    }
+}
+
+/// The kinds of commands that a `RetryPolicy` distinguishes between.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum CommandClass {
+    /// Commands that read a value, such as `GetVcpFeature`.
+    Read,
+    /// Commands that change the display's state, such as `SetVcpFeature`.
+    Write,
+    /// Commands that read one fragment of a multi-part reply, such as
+    /// `CapabilitiesRequest` and `TableRead`.
+    Fragment,
+}
+
+impl CommandClass {
+    /// Classifies a command by its opcode.
+    pub fn from_opcode(opcode: u8) -> Self {
+        match opcode {
+            0x03 | 0x0c | 0xe7 | 0xf5 => CommandClass::Write,
+            0xe2 | 0xf3 => CommandClass::Fragment,
+            _ => CommandClass::Read,
+        }
+    }
+}
+
+/// Determines which failed commands are retried, and how long to wait
+/// between attempts.
+#[derive(Debug, Copy, Clone)]
+pub struct RetryPolicy {
+    /// The maximum number of attempts for read commands.
+    pub read_attempts: u32,
+    /// The maximum number of attempts for write commands.
+    ///
+    /// A write that failed may still have reached the display, so retrying it
+    /// can apply it twice. This defaults to a single attempt.
+    pub write_attempts: u32,
+    /// The maximum number of attempts for each fragment of a capabilities or
+    /// table read.
+    pub fragment_attempts: u32,
+    /// Additional time to wait before the first retry, on top of the
+    /// `DELAY_COMMAND_FAILED_MS` delay.
+    pub backoff: Duration,
+    /// The factor the backoff grows by after each retry.
+    pub backoff_factor: u32,
+    /// The upper limit of the backoff.
+    pub max_backoff: Duration,
+    /// Whether transport errors that don't carry an `ErrorCode` are retried.
+    pub retry_transport: bool,
+    /// Whether an `ErrorCode` is worth retrying.
+    pub retryable: fn(&ErrorCode) -> bool,
+}
+
+impl RetryPolicy {
+    /// A policy that never retries.
+    pub fn never() -> Self {
+        RetryPolicy {
+            read_attempts: 1,
+            write_attempts: 1,
+            fragment_attempts: 1,
+            ..Default::default()
+        }
+    }
+
+    /// The maximum number of attempts for a class of command.
+    pub fn attempts(&self, class: CommandClass) -> u32 {
+        match class {
+            CommandClass::Read => self.read_attempts,
+            CommandClass::Write => self.write_attempts,
+            CommandClass::Fragment => self.fragment_attempts,
+        }
+    }
+
+    /// The backoff before the specified retry, starting at 1.
+    pub fn backoff(&self, retry: u32) -> Duration {
+        let factor = self.backoff_factor.saturating_pow(retry.saturating_sub(1));
+        self.backoff
+            .checked_mul(factor)
+            .unwrap_or(self.max_backoff)
+            .min(self.max_backoff)
+    }
+
+    /// Whether an error is worth retrying.
+    pub fn is_retryable<E: RetryableError + ?Sized>(&self, error: &E) -> bool {
+        match error.error_code() {
+            Some(code) => (self.retryable)(code),
+            None => self.retry_transport,
+        }
+    }
+
+    /// The default `retryable` predicate, which accepts the corrupted,
+    /// truncated and mismatched replies that displays tend to produce.
+    pub fn default_retryable(code: &ErrorCode) -> bool {
+        code.is_retryable()
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            read_attempts: 3,
+            write_attempts: 1,
+            fragment_attempts: 5,
+            backoff: Duration::from_millis(10),
+            backoff_factor: 2,
+            max_backoff: Duration::from_millis(500),
+            retry_transport: true,
+            retryable: Self::default_retryable,
+        }
+    }
+}
+
+/// Retries failed commands according to a `RetryPolicy`.
+///
+/// Each command is retried individually, so a multi-part capabilities or
+/// table read resumes from the fragment that failed.
+#[derive(Clone, Debug)]
+pub struct Retry<D, T> {
+    inner: D,
+    timer: T,
+    policy: RetryPolicy,
+}
+
+#[cfg(feature = "std")]
+impl<D> Retry<D, StdTimer> {
+    /// Wraps a DDC implementation with the default policy.
+    pub fn new(inner: D) -> Self {
+        Self::with_timer(inner, StdTimer)
+    }
+}
+
+impl<D, T> Retry<D, T> {
+    /// Wraps a DDC implementation with the default policy, using `timer` for
+    /// backoff delays.
+    pub fn with_timer(inner: D, timer: T) -> Self {
+        Retry {
+            inner,
+            timer,
+            policy: Default::default(),
+        }
+    }
+
+    /// The retry policy.
+    pub fn policy(&self) -> &RetryPolicy {
+        &self.policy
+    }
+
+    /// Mutably borrows the retry policy.
+    pub fn policy_mut(&mut self) -> &mut RetryPolicy {
+        &mut self.policy
+    }
+
+    /// Replaces the retry policy.
+    pub fn set_policy(&mut self, policy: RetryPolicy) {
+        self.policy = policy;
+    }
+
+    /// Consumes the wrapper, returning the underlying implementation and
+    /// timer.
+    pub fn into_inner(self) -> (D, T) {
+        (self.inner, self.timer)
+    }
+
+    /// Borrows the underlying implementation.
+    pub fn inner_ref(&self) -> &D {
+        &self.inner
+    }
+
+    /// Mutably borrows the underlying implementation.
+    pub fn inner_mut(&mut self) -> &mut D {
+        &mut self.inner
+    }
+}
+
+impl<D: DdcHost, T> DdcHost for Retry<D, T> {
+    type Error = D::Error;
+
+    fn sleep(&mut self) {
+        self.inner.sleep()
+    }
+}
+
+impl<D: DdcCommandRaw, T> DdcCommandRaw for Retry<D, T> {
+    fn execute_raw<'a>(
+        &mut self,
+        data: &[u8],
+        out: &'a mut [u8],
+        response_delay: Duration,
+    ) -> Result<&'a mut [u8], Self::Error> {
+        self.inner.execute_raw(data, out, response_delay)
+    }
+}
+
+impl<D: DdcCommandRawMarker, T: Timer> DdcCommand for Retry<D, T>
+where
+    D::Error: From<ErrorCode> + RetryableError,
+{
+    fn execute<C: Command>(&mut self, command: C) -> Result<C::Ok, Self::Error> {
+        let (mut data, len) = encode_command(&command)?;
+        let data = &data.as_mut()[..len];
+
+        // an empty command has no opcode, so it's treated as a write and not
+        // retried in case it changes the display's state
+        let class = data
+            .first()
+            .map_or(CommandClass::Write, |&opcode| CommandClass::from_opcode(opcode));
+        let attempts = self.policy.attempts(class);
+        let mut retry = 0;
+        loop {
+            match execute_encoded(&mut self.inner, &command, data) {
+                Err(e) if retry + 1 < attempts && self.policy.is_retryable(&e) => {
+                    retry += 1;
+                    self.timer.sleep(self.policy.backoff(retry));
+                },
+                res => return res,
+            }
+        }
+    }
+}
+
+impl<D: DdcCommandRawMarker, T: Timer> DdcCommandMarker for Retry<D, T> where D::Error: From<ErrorCode> + RetryableError {}
+
+impl<D: Edid, T> Edid for Retry<D, T> {
+    type EdidError = D::EdidError;
+
+    fn read_edid(&mut self, offset: u8, data: &mut [u8]) -> Result<usize, Self::EdidError> {
+        self.inner.read_edid(offset, data)
+    }
+}
+
+impl<D: Eddc, T> Eddc for Retry<D, T> {
+    fn read_eddc_edid(&mut self, segment: u8, offset: u8, data: &mut [u8]) -> Result<usize, Self::EdidError> {
+        self.inner.read_eddc_edid(segment, offset, data)
+    }
+}
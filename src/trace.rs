@@ -0,0 +1,665 @@
+use {
+    crate::{
+        commands::Hex, retry::RetryableError, DdcCommandMarker, DdcCommandRaw, DdcCommandRawMarker, DdcHost, Delay,
+        Eddc, Edid, ErrorCode,
+    },
+    std::{
+        error, fmt, mem,
+        str::FromStr,
+        time::{Duration, Instant},
+    },
+};
+
+/// A request made to the display.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Request {
+    /// A DDC/CI command passed to `DdcCommandRaw::execute_raw`.
+    Command {
+        /// The command payload.
+        data: Vec<u8>,
+        /// The size of the reply buffer.
+        reply_len: usize,
+        /// The delay before the reply was read.
+        response_delay: Duration,
+    },
+    /// An EDID read.
+    Edid {
+        /// The E-DDC segment, or `None` for a plain `Edid::read_edid`.
+        segment: Option<u8>,
+        /// The offset within the segment.
+        offset: u8,
+        /// The size of the read buffer.
+        len: usize,
+    },
+}
+
+impl Request {
+    /// Whether a replayed request is equivalent to this recorded one.
+    ///
+    /// Response delays are ignored, as they don't affect what the display
+    /// replies with.
+    pub fn matches(&self, other: &Request) -> bool {
+        match (self, other) {
+            (
+                &Request::Command {
+                    ref data, reply_len, ..
+                },
+                &Request::Command {
+                    data: ref other_data,
+                    reply_len: other_reply_len,
+                    ..
+                },
+            ) => data == other_data && reply_len == other_reply_len,
+            _ => self == other,
+        }
+    }
+}
+
+impl fmt::Display for Request {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Request::Command {
+                ref data,
+                reply_len,
+                response_delay,
+            } => write!(f, "command {} {} {}", response_delay.as_micros(), reply_len, Hex(data)),
+            Request::Edid { segment, offset, len } => match segment {
+                Some(segment) => write!(f, "edid {} {} {}", segment, offset, len),
+                None => write!(f, "edid - {} {}", offset, len),
+            },
+        }
+    }
+}
+
+/// A request and the display's response.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Record {
+    /// When the request was made, relative to the start of the trace.
+    pub time: Duration,
+    /// How long the request took to complete.
+    pub elapsed: Duration,
+    /// The request.
+    pub request: Request,
+    /// The bytes returned by the display, or the error that occurred.
+    pub result: Result<Vec<u8>, RecordedError>,
+}
+
+/// An error returned by a recorded display.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct RecordedError {
+    /// The DDC/CI protocol error, if the error was one.
+    ///
+    /// Errors that carry text of their own, such as `ErrorCode::Invalid`,
+    /// are only recorded as a message.
+    pub code: Option<ErrorCode>,
+    /// The error message.
+    pub message: String,
+}
+
+impl RecordedError {
+    fn new<E: fmt::Display>(e: &E, code: Option<&ErrorCode>) -> Self {
+        RecordedError {
+            code: code.filter(|code| format_code(code).is_some()).cloned(),
+            // keep the trace to one record per line
+            message: e.to_string().replace('\n', " "),
+        }
+    }
+}
+
+impl fmt::Display for RecordedError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(code) = self.code.as_ref().and_then(format_code) {
+            write!(f, "[{}] ", code)?;
+        }
+        f.write_str(&self.message)
+    }
+}
+
+#[allow(deprecated)]
+fn format_code(code: &ErrorCode) -> Option<String> {
+    Some(match *code {
+        ErrorCode::InvalidOffset => "InvalidOffset".into(),
+        ErrorCode::InvalidLength => "InvalidLength".into(),
+        ErrorCode::InvalidChecksum => "InvalidChecksum".into(),
+        ErrorCode::InvalidData => "InvalidData".into(),
+        ErrorCode::InvalidOpcode => "InvalidOpcode".into(),
+        ErrorCode::LengthMismatch { expected, actual } => format!("LengthMismatch {} {}", expected, actual),
+        ErrorCode::OpcodeMismatch { expected, actual } => format!("OpcodeMismatch {:02x} {:02x}", expected, actual),
+        ErrorCode::OffsetMismatch { expected, actual } => format!("OffsetMismatch {} {}", expected, actual),
+        ErrorCode::UnsupportedFeature(code) => format!("UnsupportedFeature {:02x}", code),
+        ErrorCode::UnknownResultCode(rc) => format!("UnknownResultCode {:02x}", rc),
+        ErrorCode::NullResponse => "NullResponse".into(),
+        ErrorCode::WrongFeature { expected, actual } => format!("WrongFeature {:02x} {:02x}", expected, actual),
+        ErrorCode::BufferTooSmall => "BufferTooSmall".into(),
+        ErrorCode::ReplyTooLong { limit } => format!("ReplyTooLong {}", limit),
+        ErrorCode::Invalid(..) | ErrorCode::InvalidCapabilities(..) => return None,
+    })
+}
+
+#[allow(deprecated)]
+fn parse_code(s: &str) -> Option<ErrorCode> {
+    let fields = s.split_whitespace().collect::<Vec<_>>();
+    let arg = |i: usize| fields.get(i + 1).copied();
+    let hex = |i: usize| u8::from_str_radix(arg(i)?, 16).ok();
+    let code = match (*fields.first()?, fields.len() - 1) {
+        ("InvalidOffset", 0) => ErrorCode::InvalidOffset,
+        ("InvalidLength", 0) => ErrorCode::InvalidLength,
+        ("InvalidChecksum", 0) => ErrorCode::InvalidChecksum,
+        ("InvalidData", 0) => ErrorCode::InvalidData,
+        ("InvalidOpcode", 0) => ErrorCode::InvalidOpcode,
+        ("LengthMismatch", 2) => ErrorCode::LengthMismatch {
+            expected: arg(0)?.parse().ok()?,
+            actual: arg(1)?.parse().ok()?,
+        },
+        ("OpcodeMismatch", 2) => ErrorCode::OpcodeMismatch {
+            expected: hex(0)?,
+            actual: hex(1)?,
+        },
+        ("OffsetMismatch", 2) => ErrorCode::OffsetMismatch {
+            expected: arg(0)?.parse().ok()?,
+            actual: arg(1)?.parse().ok()?,
+        },
+        ("UnsupportedFeature", 1) => ErrorCode::UnsupportedFeature(hex(0)?),
+        ("UnknownResultCode", 1) => ErrorCode::UnknownResultCode(hex(0)?),
+        ("NullResponse", 0) => ErrorCode::NullResponse,
+        ("WrongFeature", 2) => ErrorCode::WrongFeature {
+            expected: hex(0)?,
+            actual: hex(1)?,
+        },
+        ("BufferTooSmall", 0) => ErrorCode::BufferTooSmall,
+        ("ReplyTooLong", 1) => ErrorCode::ReplyTooLong {
+            limit: arg(0)?.parse().ok()?,
+        },
+        _ => return None,
+    };
+    Some(code)
+}
+
+fn parse_error(s: &str) -> RecordedError {
+    let code = s
+        .strip_prefix('[')
+        .and_then(|s| s.split_once(']'))
+        .and_then(|(code, message)| Some((parse_code(code)?, message.trim_start())));
+    match code {
+        Some((code, message)) => RecordedError {
+            code: Some(code),
+            message: message.into(),
+        },
+        None => RecordedError {
+            code: None,
+            message: s.into(),
+        },
+    }
+}
+
+impl fmt::Display for Record {
+    /// Formats the record as a line of a trace file.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} {} {} ",
+            self.time.as_micros(),
+            self.elapsed.as_micros(),
+            self.request
+        )?;
+        match self.result {
+            Ok(ref data) if data.is_empty() => f.write_str("="),
+            Ok(ref data) => write!(f, "= {}", Hex(data)),
+            Err(ref e) => write!(f, "! {}", e),
+        }
+    }
+}
+
+/// A recorded session with a display.
+///
+/// Traces are stored as text, with one record per line:
+///
+/// ```text
+/// # time_us elapsed_us command response_delay_us reply_len request = reply
+/// 0 41210 command 40000 11 01 10 = 02 00 10 00 00 64 00 32
+/// 91372 40954 command 40000 11 01 12 ! I2C error: timed out
+/// 132448 40830 command 40000 11 01 12 ! [InvalidChecksum] DDC/CI checksum mismatch
+/// 173391 1733 edid - 0 128 = 00 ff ff ff ff ff ff 00 ...
+/// ```
+///
+/// DDC/CI protocol errors are prefixed with their `ErrorCode` in brackets, so
+/// that they can be returned again by `Replay`. Blank lines and lines
+/// starting with `#` are ignored.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Trace {
+    /// The records, in the order that the requests were made.
+    pub records: Vec<Record>,
+}
+
+impl fmt::Display for Trace {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("# ddc trace\n")?;
+        for record in &self.records {
+            writeln!(f, "{}", record)?;
+        }
+        Ok(())
+    }
+}
+
+/// An error parsing a trace file.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    /// The line the error occurred on, starting at 1.
+    pub line: usize,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid trace record on line {}", self.line)
+    }
+}
+
+impl error::Error for ParseError {}
+
+fn parse_record(line: &str) -> Option<Record> {
+    let split = line.find(['=', '!'])?;
+    let (fields, result) = line.split_at(split);
+    let mut fields = fields.split_whitespace();
+
+    let micros = |field: Option<&str>| field?.parse().ok().map(Duration::from_micros);
+    let time = micros(fields.next())?;
+    let elapsed = micros(fields.next())?;
+    let request = match fields.next()? {
+        "command" => Request::Command {
+            response_delay: micros(fields.next())?,
+            reply_len: fields.next()?.parse().ok()?,
+            data: fields
+                .by_ref()
+                .map(|b| u8::from_str_radix(b, 16).ok())
+                .collect::<Option<_>>()?,
+        },
+        "edid" => Request::Edid {
+            segment: match fields.next()? {
+                "-" => None,
+                segment => Some(segment.parse().ok()?),
+            },
+            offset: fields.next()?.parse().ok()?,
+            len: fields.next()?.parse().ok()?,
+        },
+        _ => return None,
+    };
+    if fields.next().is_some() {
+        return None
+    }
+
+    let result = match result.split_at(1) {
+        ("=", data) => Ok(data
+            .split_whitespace()
+            .map(|b| u8::from_str_radix(b, 16).ok())
+            .collect::<Option<_>>()?),
+        (_, e) => Err(parse_error(e.trim())),
+    };
+
+    Some(Record {
+        time,
+        elapsed,
+        request,
+        result,
+    })
+}
+
+impl FromStr for Trace {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let records = s
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty() && !line.trim_start().starts_with('#'))
+            .map(|(line, text)| parse_record(text).ok_or(ParseError { line: line + 1 }))
+            .collect::<Result<_, _>>()?;
+
+        Ok(Trace { records })
+    }
+}
+
+/// Records every request made to a display, along with its response and
+/// timing, so that the session can later be reproduced with `Replay`.
+#[derive(Clone, Debug)]
+pub struct Recorder<D> {
+    inner: D,
+    trace: Trace,
+    start: Instant,
+}
+
+impl<D> Recorder<D> {
+    /// Wraps a DDC implementation, starting an empty trace.
+    pub fn new(inner: D) -> Self {
+        Recorder {
+            inner,
+            trace: Default::default(),
+            start: Instant::now(),
+        }
+    }
+
+    /// The trace recorded so far.
+    pub fn trace(&self) -> &Trace {
+        &self.trace
+    }
+
+    /// Takes the trace recorded so far, leaving an empty one in its place.
+    pub fn take_trace(&mut self) -> Trace {
+        mem::take(&mut self.trace)
+    }
+
+    /// Consumes the wrapper, returning the underlying implementation and the
+    /// recorded trace.
+    pub fn into_inner(self) -> (D, Trace) {
+        (self.inner, self.trace)
+    }
+
+    /// Borrows the underlying implementation.
+    pub fn inner_ref(&self) -> &D {
+        &self.inner
+    }
+
+    /// Mutably borrows the underlying implementation.
+    ///
+    /// Requests made directly through the underlying implementation are not
+    /// recorded.
+    pub fn inner_mut(&mut self) -> &mut D {
+        &mut self.inner
+    }
+
+    fn record(&mut self, started: Instant, request: Request, result: Result<&[u8], RecordedError>) {
+        // truncated to the precision of the trace file
+        let micros = |duration: Duration| Duration::from_micros(duration.as_micros() as u64);
+        self.trace.records.push(Record {
+            time: micros(started.duration_since(self.start)),
+            elapsed: micros(started.elapsed()),
+            request,
+            result: result.map(|data| data.to_vec()),
+        });
+    }
+}
+
+impl<D: DdcHost> DdcHost for Recorder<D> {
+    type Error = D::Error;
+
+    fn sleep(&mut self) {
+        self.inner.sleep()
+    }
+}
+
+impl<D: DdcCommandRaw> DdcCommandRaw for Recorder<D>
+where
+    D::Error: RetryableError + fmt::Display,
+{
+    fn execute_raw<'a>(
+        &mut self,
+        data: &[u8],
+        out: &'a mut [u8],
+        response_delay: Duration,
+    ) -> Result<&'a mut [u8], Self::Error> {
+        let request = Request::Command {
+            data: data.to_vec(),
+            reply_len: out.len(),
+            response_delay,
+        };
+        let started = Instant::now();
+        let res = self.inner.execute_raw(data, out, response_delay);
+        self.record(
+            started,
+            request,
+            res.as_deref().map_err(|e| RecordedError::new(e, e.error_code())),
+        );
+        res
+    }
+}
+
+impl<D: DdcCommandRawMarker> DdcCommandRawMarker for Recorder<D>
+where
+    D::Error: From<ErrorCode> + RetryableError + fmt::Display,
+{
+    fn set_sleep_delay(&mut self, delay: Delay) {
+        self.inner.set_sleep_delay(delay)
+    }
+}
+
+impl<D: DdcCommandRawMarker> DdcCommandMarker for Recorder<D> where
+    D::Error: From<ErrorCode> + RetryableError + fmt::Display
+{
+}
+
+impl<D: Edid> Edid for Recorder<D>
+where
+    D::EdidError: fmt::Display,
+{
+    type EdidError = D::EdidError;
+
+    fn read_edid(&mut self, offset: u8, data: &mut [u8]) -> Result<usize, Self::EdidError> {
+        let request = Request::Edid {
+            segment: None,
+            offset,
+            len: data.len(),
+        };
+        let started = Instant::now();
+        let res = self.inner.read_edid(offset, data);
+        self.record(
+            started,
+            request,
+            res.as_ref()
+                .map(|&len| &data[..len])
+                .map_err(|e| RecordedError::new(e, None)),
+        );
+        res
+    }
+}
+
+impl<D: Eddc> Eddc for Recorder<D>
+where
+    D::EdidError: fmt::Display,
+{
+    fn read_eddc_edid(&mut self, segment: u8, offset: u8, data: &mut [u8]) -> Result<usize, Self::EdidError> {
+        let request = Request::Edid {
+            segment: Some(segment),
+            offset,
+            len: data.len(),
+        };
+        let started = Instant::now();
+        let res = self.inner.read_eddc_edid(segment, offset, data);
+        self.record(
+            started,
+            request,
+            res.as_ref()
+                .map(|&len| &data[..len])
+                .map_err(|e| RecordedError::new(e, None)),
+        );
+        res
+    }
+}
+
+/// Errors returned by `Replay`.
+#[derive(Clone, Debug)]
+pub enum ReplayError {
+    /// A request didn't match the next record of the trace.
+    Diverged {
+        /// The index of the record.
+        index: usize,
+        /// The recorded request, or `None` if the trace has ended.
+        expected: Option<Request>,
+        /// The request that was made.
+        actual: Request,
+    },
+    /// Records were left over when the replay finished.
+    Unconsumed {
+        /// The number of records that were never requested.
+        remaining: usize,
+    },
+    /// The recorded display returned an error other than a DDC/CI protocol
+    /// error.
+    Recorded(String),
+    /// DDC/CI protocol error, including those returned by the recorded
+    /// display
+    Ddc(ErrorCode),
+}
+
+impl From<ErrorCode> for ReplayError {
+    fn from(e: ErrorCode) -> Self {
+        ReplayError::Ddc(e)
+    }
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ReplayError::Diverged {
+                index,
+                expected: Some(ref expected),
+                ref actual,
+            } => write!(
+                f,
+                "replay diverged at record {}: expected `{}`, got `{}`",
+                index, expected, actual
+            ),
+            ReplayError::Diverged {
+                index,
+                expected: None,
+                ref actual,
+            } => write!(f, "replay diverged at record {}: trace ended, got `{}`", index, actual),
+            ReplayError::Unconsumed { remaining } => write!(f, "replay finished with {} records left over", remaining),
+            ReplayError::Recorded(ref e) => write!(f, "recorded error: {}", e),
+            ReplayError::Ddc(ref e) => write!(f, "DDC/CI error: {}", e),
+        }
+    }
+}
+
+impl error::Error for ReplayError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            ReplayError::Ddc(ref e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl RetryableError for ReplayError {
+    fn error_code(&self) -> Option<&ErrorCode> {
+        match *self {
+            ReplayError::Ddc(ref e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+/// Serves the responses of a recorded `Trace`, for reproducing a session
+/// without the display.
+///
+/// Each request must match the next record of the trace, otherwise
+/// `ReplayError::Diverged` is returned. Delays are skipped entirely.
+#[derive(Clone, Debug)]
+pub struct Replay {
+    trace: Trace,
+    position: usize,
+}
+
+impl Replay {
+    /// Replays a trace from the beginning.
+    pub fn new(trace: Trace) -> Self {
+        Replay { trace, position: 0 }
+    }
+
+    /// The index of the next record to be replayed.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// The records that have yet to be replayed.
+    pub fn remaining(&self) -> &[Record] {
+        &self.trace.records[self.position..]
+    }
+
+    /// Checks that the entire trace was replayed.
+    pub fn finish(&self) -> Result<(), ReplayError> {
+        match self.remaining().len() {
+            0 => Ok(()),
+            remaining => Err(ReplayError::Unconsumed { remaining }),
+        }
+    }
+
+    fn next(&mut self, request: Request) -> Result<&[u8], ReplayError> {
+        let index = self.position;
+        let record = match self.trace.records.get(index) {
+            Some(record) if record.request.matches(&request) => record,
+            expected =>
+                return Err(ReplayError::Diverged {
+                    index,
+                    expected: expected.map(|record| record.request.clone()),
+                    actual: request,
+                }),
+        };
+        self.position += 1;
+
+        match record.result {
+            Ok(ref data) => Ok(data),
+            Err(RecordedError {
+                code: Some(ref code), ..
+            }) => Err(ReplayError::Ddc(code.clone())),
+            Err(RecordedError { ref message, .. }) => Err(ReplayError::Recorded(message.clone())),
+        }
+    }
+
+    fn copy(data: &[u8], out: &mut [u8]) -> Result<usize, ReplayError> {
+        out.get_mut(..data.len())
+            .ok_or(ReplayError::Ddc(ErrorCode::BufferTooSmall))?
+            .copy_from_slice(data);
+        Ok(data.len())
+    }
+}
+
+impl DdcHost for Replay {
+    type Error = ReplayError;
+}
+
+impl DdcCommandRaw for Replay {
+    fn execute_raw<'a>(
+        &mut self,
+        data: &[u8],
+        out: &'a mut [u8],
+        response_delay: Duration,
+    ) -> Result<&'a mut [u8], Self::Error> {
+        let reply = self.next(Request::Command {
+            data: data.to_vec(),
+            reply_len: out.len(),
+            response_delay,
+        })?;
+        let len = Self::copy(reply, out)?;
+
+        Ok(&mut out[..len])
+    }
+}
+
+impl DdcCommandRawMarker for Replay {
+    fn set_sleep_delay(&mut self, _delay: Delay) {}
+}
+
+impl DdcCommandMarker for Replay {}
+
+impl Edid for Replay {
+    type EdidError = ReplayError;
+
+    fn read_edid(&mut self, offset: u8, data: &mut [u8]) -> Result<usize, Self::EdidError> {
+        let reply = self.next(Request::Edid {
+            segment: None,
+            offset,
+            len: data.len(),
+        })?;
+        Self::copy(reply, data)
+    }
+}
+
+impl Eddc for Replay {
+    fn read_eddc_edid(&mut self, segment: u8, offset: u8, data: &mut [u8]) -> Result<usize, Self::EdidError> {
+        let reply = self.next(Request::Edid {
+            segment: Some(segment),
+            offset,
+            len: data.len(),
+        })?;
+        Self::copy(reply, data)
+    }
+}
@@ -0,0 +1,112 @@
+//! VCP value types, mirroring those of the `mccs` crate.
+//!
+//! They are defined here so that enabling the `mccs` feature doesn't change
+//! the types used by the rest of the API. With the feature enabled they can
+//! be converted to and from their `mccs` counterparts.
+
+/// VCP feature code
+pub type FeatureCode = u8;
+
+/// VCP Value
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Value {
+    /// Specifies the type of the value, continuous or non-continuous.
+    pub ty: u8,
+    /// The high byte of the maximum allowed value.
+    pub mh: u8,
+    /// The low byte of the maximum allowed value.
+    pub ml: u8,
+    /// The high byte of the value.
+    pub sh: u8,
+    /// The low byte of the value.
+    pub sl: u8,
+}
+
+impl Value {
+    /// Create a new `Value` from a scalar value.
+    ///
+    /// Other fields are left as default.
+    pub fn from_value(v: u16) -> Self {
+        Value {
+            sh: (v >> 8) as u8,
+            sl: v as u8,
+            ..Default::default()
+        }
+    }
+
+    /// Combines the value bytes into a single value.
+    pub fn value(&self) -> u16 {
+        ((self.sh as u16) << 8) | self.sl as u16
+    }
+
+    /// Combines the maximum value bytes into a single value.
+    pub fn maximum(&self) -> u16 {
+        ((self.mh as u16) << 8) | self.ml as u16
+    }
+
+    /// VCP value type.
+    pub fn ty(&self) -> Result<ValueType, u8> {
+        match self.ty {
+            0 => Ok(ValueType::SetParameter),
+            1 => Ok(ValueType::Momentary),
+            ty => Err(ty),
+        }
+    }
+}
+
+/// VCP feature type.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ValueType {
+    /// Sending a command of this type changes some aspect of the monitor's
+    /// operation.
+    SetParameter,
+    /// Sending a command of this type causes the monitor to initiate a
+    /// self-timed operation and then revert to its original state.
+    Momentary,
+}
+
+#[cfg(feature = "mccs")]
+impl From<mccs::Value> for Value {
+    fn from(value: mccs::Value) -> Self {
+        Value {
+            ty: value.ty,
+            mh: value.mh,
+            ml: value.ml,
+            sh: value.sh,
+            sl: value.sl,
+        }
+    }
+}
+
+#[cfg(feature = "mccs")]
+impl From<Value> for mccs::Value {
+    fn from(value: Value) -> Self {
+        mccs::Value {
+            ty: value.ty,
+            mh: value.mh,
+            ml: value.ml,
+            sh: value.sh,
+            sl: value.sl,
+        }
+    }
+}
+
+#[cfg(feature = "mccs")]
+impl From<mccs::ValueType> for ValueType {
+    fn from(ty: mccs::ValueType) -> Self {
+        match ty {
+            mccs::ValueType::SetParameter => ValueType::SetParameter,
+            mccs::ValueType::Momentary => ValueType::Momentary,
+        }
+    }
+}
+
+#[cfg(feature = "mccs")]
+impl From<ValueType> for mccs::ValueType {
+    fn from(ty: ValueType) -> Self {
+        match ty {
+            ValueType::SetParameter => mccs::ValueType::SetParameter,
+            ValueType::Momentary => mccs::ValueType::Momentary,
+        }
+    }
+}
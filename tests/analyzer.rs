@@ -0,0 +1,263 @@
+use {
+    core::time::Duration,
+    ddc::{
+        analyzer::{analyze, parse, parse_hex, parse_sigrok, Direction, Event, ParseErrorKind, Transfer, Warning},
+        commands::{AnyCommand, AnyResponse},
+        ErrorCode, VcpValue,
+    },
+};
+
+/// Reading the brightness and then setting it, as logged by a bus analyzer.
+const HEX_CAPTURE: &str = "\
+# get VCP feature 0x10
+0.000000 W 37 51 82 01 10 ac
+0.045000 R 37 6e 88 02 00 10 00 00 64 00 32 f2
+
+# set VCP feature 0x10 to 70
+0.100000 W 37 51 84 03 10 00 46 ee
+";
+
+/// The same request and reply, as printed by
+/// `sigrok-cli --protocol-decoder-samplenum -P i2c -A i2c` at 1 MHz.
+const SIGROK_CAPTURE: &str = "\
+0-10 i2c-1: Start
+10-90 i2c-1: Address write: 37
+90-100 i2c-1: Write
+100-110 i2c-1: ACK
+110-190 i2c-1: Data write: 51
+190-200 i2c-1: ACK
+200-280 i2c-1: Data write: 82
+280-290 i2c-1: ACK
+290-370 i2c-1: Data write: 01
+370-380 i2c-1: ACK
+380-460 i2c-1: Data write: 10
+460-470 i2c-1: ACK
+470-550 i2c-1: Data write: AC
+550-560 i2c-1: ACK
+560-570 i2c-1: Stop
+45000-45010 i2c-1: Start
+45010-45090 i2c-1: Address read: 37
+45090-45100 i2c-1: Read
+45100-45110 i2c-1: ACK
+45110-45190 i2c-1: Data read: 6E
+45190-45200 i2c-1: ACK
+45200-45280 i2c-1: Data read: 88
+45280-45290 i2c-1: ACK
+45290-45370 i2c-1: Data read: 02
+45370-45380 i2c-1: ACK
+45380-45460 i2c-1: Data read: 00
+45460-45470 i2c-1: ACK
+45470-45550 i2c-1: Data read: 10
+45550-45560 i2c-1: ACK
+45560-45640 i2c-1: Data read: 00
+45640-45650 i2c-1: ACK
+45650-45730 i2c-1: Data read: 00
+45730-45740 i2c-1: ACK
+45740-45820 i2c-1: Data read: 64
+45820-45830 i2c-1: ACK
+45830-45910 i2c-1: Data read: 00
+45910-45920 i2c-1: ACK
+45920-46000 i2c-1: Data read: 32
+46000-46010 i2c-1: ACK
+46010-46090 i2c-1: Data read: F2
+46090-46100 i2c-1: NACK
+46100-46110 i2c-1: Stop
+";
+
+fn transfer(secs: f64, address: u16, direction: Direction, data: &[u8]) -> Transfer {
+    Transfer {
+        time: Some(Duration::from_secs_f64(secs)),
+        address,
+        direction,
+        data: data.to_vec(),
+    }
+}
+
+fn brightness() -> VcpValue {
+    VcpValue {
+        ty: 0,
+        mh: 0,
+        ml: 100,
+        sh: 0,
+        sl: 50,
+    }
+}
+
+#[test]
+fn hex_capture() {
+    let transfers = parse_hex(HEX_CAPTURE).unwrap();
+    assert_eq!(transfers.len(), 3);
+    assert_eq!(
+        transfers[0],
+        transfer(0.0, 0x37, Direction::Write, &[0x51, 0x82, 0x01, 0x10, 0xac])
+    );
+    assert_eq!(transfers[1].time, Some(Duration::from_millis(45)));
+    assert_eq!(transfers[1].direction, Direction::Read);
+    assert_eq!(transfers[1].data.len(), 11);
+
+    let entries = analyze(transfers);
+    assert_eq!(
+        entries[0].event,
+        Event::Request(Ok(AnyCommand::GetVcpFeature { code: 0x10 }))
+    );
+    assert_eq!(
+        entries[1].event,
+        Event::Reply(Ok(Some(AnyResponse::VcpValue(brightness()))))
+    );
+    assert_eq!(
+        entries[2].event,
+        Event::Request(Ok(AnyCommand::SetVcpFeature { code: 0x10, value: 70 }))
+    );
+    assert!(entries.iter().all(|entry| entry.warnings.is_empty()));
+    assert_eq!(
+        entries[1].to_string(),
+        "    0.045000 37 R VCP value 50 of 100 (type 0x00)"
+    );
+
+    // timestamps are optional and values may be prefixed
+    let transfers = parse_hex("w 0x37 0x51 0x80 0xbe").unwrap();
+    assert_eq!(transfers[0].time, None);
+    assert_eq!(transfers[0].data, [0x51, 0x80, 0xbe]);
+}
+
+#[test]
+fn hex_errors() {
+    let error = |log| parse_hex(log).unwrap_err();
+    assert_eq!(error("0.0 X 37 51").kind, ParseErrorKind::InvalidDirection);
+    assert_eq!(error("W 80 51").kind, ParseErrorKind::InvalidAddress);
+    assert_eq!(error("W 37 511").kind, ParseErrorKind::InvalidByte);
+    assert_eq!(error("0.x W 37 51").kind, ParseErrorKind::InvalidTime);
+
+    let e = error("W 37 51\n\nW 37 zz");
+    assert_eq!(e.line, 3);
+    assert_eq!(e.to_string(), "line 3: invalid data byte");
+}
+
+#[test]
+fn sigrok_capture() {
+    let transfers = parse_sigrok(SIGROK_CAPTURE, Some(1_000_000)).unwrap();
+    assert_eq!(parse(SIGROK_CAPTURE, Some(1_000_000)).unwrap(), transfers);
+    // transfers are timed from their address
+    assert_eq!(transfers[0].time, Some(Duration::from_micros(10)));
+    assert_eq!(transfers[1].time, Some(Duration::from_micros(45_010)));
+
+    // without a sample rate the transfers aren't timestamped
+    let untimed = parse_sigrok(SIGROK_CAPTURE, None).unwrap();
+    assert!(untimed.iter().all(|transfer| transfer.time.is_none()));
+    let mut expected = parse_hex(HEX_CAPTURE).unwrap();
+    expected.truncate(2);
+    expected.iter_mut().for_each(|transfer| transfer.time = None);
+    assert_eq!(untimed, expected);
+
+    // PulseView annotation exports quote every form of the annotation
+    let pulseview = "\
+1-2 I²C: Address/data: \"Address write: 37\" \"AW: 37\" \"37\"
+3-4 I²C: Address/data: \"Data write: 51\" \"DW: 51\" \"51\"
+";
+    let transfers = parse_sigrok(pulseview, None).unwrap();
+    assert_eq!((transfers[0].address, &transfers[0].data[..]), (0x37, &[0x51][..]));
+
+    let e = parse_sigrok("i2c-1: Data read: 6E", None).unwrap_err();
+    assert_eq!((e.line, e.kind), (1, ParseErrorKind::MissingAddress));
+}
+
+#[test]
+fn response_too_soon() {
+    let entries = analyze([
+        transfer(0.0, 0x37, Direction::Write, &[0x51, 0x82, 0x01, 0x10, 0xac]),
+        transfer(0.01, 0x37, Direction::Read, &[
+            0x6e, 0x88, 0x02, 0x00, 0x10, 0x00, 0x00, 0x64, 0x00, 0x32, 0xf2,
+        ]),
+    ]);
+    match entries[1].warnings[..] {
+        [Warning::ResponseTooSoon { elapsed, required }] => {
+            assert_eq!(elapsed.as_millis(), 10);
+            assert_eq!(required, Duration::from_millis(40));
+        },
+        ref warnings => panic!("unexpected warnings {:?}", warnings),
+    }
+}
+
+#[test]
+fn command_too_soon() {
+    let get = [0x51, 0x82, 0x01, 0x10, 0xac];
+    let entries = analyze([
+        transfer(0.0, 0x37, Direction::Write, &get),
+        transfer(0.04, 0x37, Direction::Read, &[
+            0x6e, 0x88, 0x02, 0x00, 0x10, 0x00, 0x00, 0x64, 0x00, 0x32, 0xf2,
+        ]),
+        transfer(0.06, 0x37, Direction::Write, &get),
+    ]);
+    assert!(entries[1].warnings.is_empty());
+    match entries[2].warnings[..] {
+        [Warning::CommandTooSoon { elapsed, required }] => {
+            assert_eq!(elapsed.as_millis(), 20);
+            assert_eq!(required, Duration::from_millis(50));
+        },
+        ref warnings => panic!("unexpected warnings {:?}", warnings),
+    }
+
+    // commands without a reply are timed from the request
+    let entries = analyze([
+        transfer(0.0, 0x37, Direction::Write, &[0x51, 0x81, 0x0c, 0xb2]),
+        transfer(0.1, 0x37, Direction::Write, &get),
+    ]);
+    assert!(matches!(
+        entries[1].warnings[..],
+        [Warning::CommandTooSoon { required, .. }] if required == Duration::from_millis(200)
+    ));
+}
+
+#[test]
+fn missing_reply() {
+    let get = [0x51, 0x82, 0x01, 0x10, 0xac];
+    let entries = analyze([
+        transfer(0.0, 0x37, Direction::Write, &get),
+        transfer(0.1, 0x37, Direction::Write, &get),
+        transfer(0.2, 0x37, Direction::Read, &[0x6e, 0x80, 0xbe]),
+        transfer(0.3, 0x37, Direction::Read, &[0x6e, 0x80, 0xbe]),
+    ]);
+    assert_eq!(entries[1].warnings, [Warning::MissingReply]);
+    assert_eq!(entries[2].event, Event::Reply(Ok(None)));
+    assert!(entries[2].warnings.is_empty());
+    assert_eq!(entries[3].warnings, [Warning::UnexpectedReply]);
+
+    // corrupted requests are reported rather than waiting for a reply
+    let entries = analyze([transfer(0.0, 0x37, Direction::Write, &[0x51, 0x82, 0x01, 0x10, 0xad])]);
+    assert_eq!(entries[0].event, Event::Request(Err(ErrorCode::InvalidChecksum)));
+}
+
+#[test]
+fn edid_checksum() {
+    let mut block = [0u8; 128];
+    block[..8].copy_from_slice(&[0x00, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x00]);
+    block[127] = block[..127]
+        .iter()
+        .fold(0u8, |sum, &b| sum.wrapping_add(b))
+        .wrapping_neg();
+    let mut corrupt = block;
+    corrupt[20] ^= 1;
+
+    let entries = analyze([
+        transfer(0.0, 0x50, Direction::Write, &[0x00]),
+        transfer(0.1, 0x50, Direction::Read, &block),
+        transfer(0.2, 0x50, Direction::Read, &corrupt),
+        // a segment pointer write selects the third and fourth blocks
+        transfer(0.3, 0x30, Direction::Write, &[0x01]),
+        transfer(0.3, 0x50, Direction::Write, &[0x80]),
+        transfer(0.4, 0x50, Direction::Read, &corrupt),
+        // partial blocks aren't checked
+        transfer(0.5, 0x50, Direction::Write, &[0x10]),
+        transfer(0.6, 0x50, Direction::Read, &corrupt[..64]),
+    ]);
+    assert!(entries[1].warnings.is_empty());
+    assert_eq!(entries[2].event, Event::EdidRead {
+        segment: 0,
+        offset: 0x80,
+        data: corrupt.to_vec(),
+    });
+    assert_eq!(entries[2].warnings, [Warning::EdidChecksum { block: 1 }]);
+    assert_eq!(entries[3].event, Event::EdidSegment(1));
+    assert_eq!(entries[5].warnings, [Warning::EdidChecksum { block: 3 }]);
+    assert!(entries[7].warnings.is_empty());
+}
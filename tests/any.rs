@@ -0,0 +1,142 @@
+use ddc::{
+    commands::{AnyCommand, AnyResponse, Command, RawCommand, RawResponse, TimingMessage},
+    mock::{MockDisplay, MockFeature},
+    DdcCommand, VcpValue,
+};
+
+fn display() -> MockDisplay {
+    let mut display = MockDisplay::new();
+    display.set_feature(0x10, MockFeature::Continuous {
+        value: 50,
+        maximum: 100,
+    });
+    display.set_feature(0x73, MockFeature::Table(vec![1, 2, 3]));
+    display.capabilities = b"(vcp(10 73))".to_vec();
+    display
+}
+
+#[test]
+fn execute() {
+    let mut ddc = display();
+
+    let value = ddc.execute(&AnyCommand::GetVcpFeature { code: 0x10 }).unwrap();
+    assert!(matches!(value, AnyResponse::VcpValue(value) if value.value() == 50));
+    assert_eq!(
+        ddc.execute(&AnyCommand::SetVcpFeature { code: 0x10, value: 70 }),
+        Ok(AnyResponse::Empty)
+    );
+    assert_eq!(
+        ddc.execute(&AnyCommand::TableRead { code: 0x73, offset: 1 }),
+        Ok(AnyResponse::Table {
+            offset: 1,
+            data: vec![2, 3],
+        })
+    );
+    assert_eq!(
+        ddc.execute(&AnyCommand::CapabilitiesRequest { offset: 0 }),
+        Ok(AnyResponse::Capabilities {
+            offset: 0,
+            data: b"(vcp(10 73))".to_vec(),
+        })
+    );
+
+    // unknown commands are sent as is and their reply returned undecoded
+    let raw = AnyCommand::Raw(RawCommand::new(vec![0x01, 0x10], 8));
+    assert_eq!(
+        ddc.execute(&raw),
+        Ok(AnyResponse::Raw(RawResponse(vec![
+            0x02, 0x00, 0x10, 0x00, 0x00, 100, 0x00, 70
+        ])))
+    );
+}
+
+#[test]
+fn dispatch() {
+    let get = AnyCommand::GetVcpFeature { code: 0x10 };
+    let save = AnyCommand::SaveCurrentSettings;
+    assert_eq!((get.len(), get.reply_len()), (2, 8));
+    assert_eq!((save.len(), save.reply_len()), (1, 0));
+    assert_eq!((get.delay_response_ms(), get.delay_command_ms()), (40, 50));
+    assert_eq!((save.delay_response_ms(), save.delay_command_ms()), (0, 200));
+
+    let mut data = [0u8; 8];
+    let write = AnyCommand::TableWrite {
+        code: 0x73,
+        offset: 0x0102,
+        data: vec![0xaa, 0xbb],
+    };
+    assert_eq!(write.encode(&mut data), Ok(6));
+    assert_eq!(&data[..6], &[0xe7, 0x73, 0x01, 0x02, 0xaa, 0xbb]);
+    assert_eq!(AnyCommand::decode_request(&data[..6]), Ok(write));
+}
+
+#[test]
+fn serde_commands() {
+    let commands = [
+        AnyCommand::GetVcpFeature { code: 0x10 },
+        AnyCommand::SetVcpFeature { code: 0x10, value: 70 },
+        AnyCommand::SaveCurrentSettings,
+        AnyCommand::TableRead { code: 0x73, offset: 32 },
+        AnyCommand::TableWrite {
+            code: 0x73,
+            offset: 0,
+            data: vec![1, 2, 3],
+        },
+        AnyCommand::CapabilitiesRequest { offset: 64 },
+        AnyCommand::GetTimingReport,
+        AnyCommand::IdentificationRequest,
+        AnyCommand::SelfTestRequest,
+        AnyCommand::EnableApplicationReport { enable: true },
+        AnyCommand::Raw(RawCommand::new(vec![0xc8, 0x01], 0)),
+    ];
+
+    for command in commands {
+        let json = serde_json::to_string(&command).unwrap();
+        assert_eq!(serde_json::from_str::<AnyCommand>(&json).unwrap(), command, "{}", json);
+    }
+
+    assert_eq!(
+        serde_json::from_str::<AnyCommand>(r#"{"SetVcpFeature":{"code":16,"value":70}}"#).unwrap(),
+        AnyCommand::SetVcpFeature { code: 0x10, value: 70 }
+    );
+}
+
+#[test]
+fn serde_responses() {
+    let responses = [
+        AnyResponse::Empty,
+        AnyResponse::VcpValue(VcpValue {
+            ty: 0,
+            mh: 0,
+            ml: 100,
+            sh: 0,
+            sl: 50,
+        }),
+        AnyResponse::Table {
+            offset: 32,
+            data: vec![1, 2, 3],
+        },
+        AnyResponse::Capabilities {
+            offset: 0,
+            data: b"(vcp(10))".to_vec(),
+        },
+        AnyResponse::Timing(TimingMessage {
+            timing_status: 0x02,
+            horizontal_frequency: 0x1234,
+            vertical_frequency: 0x0258,
+        }),
+        AnyResponse::Identification(vec![0x01, 0x02]),
+        AnyResponse::SelfTest(vec![]),
+        AnyResponse::Raw(RawResponse(vec![0xc8, 0x00])),
+    ];
+
+    for response in responses {
+        let json = serde_json::to_string(&response).unwrap();
+        assert_eq!(
+            serde_json::from_str::<AnyResponse>(&json).unwrap(),
+            response,
+            "{}",
+            json
+        );
+    }
+}
@@ -0,0 +1,206 @@
+use {
+    core::{
+        future::Future,
+        pin::pin,
+        task::{Context, Poll, Waker},
+        time::Duration,
+    },
+    ddc::{
+        asynchronous::{
+            AsyncDdc, AsyncDdcCommandMarker, AsyncDdcCommandRawMarker, AsyncDdcTable, AsyncTimer, SendAsyncDdc,
+            SendAsyncDdcCommandRaw, SendAsyncDdcTable,
+        },
+        fragments::SendAsyncFragments,
+        mock::{MockDisplay, MockFeature},
+        DdcCommandRaw, DdcHost, Delay, ErrorCode,
+    },
+};
+
+/// Polls a future that never waits to completion.
+fn block_on<F: Future>(future: F) -> F::Output {
+    let mut future = pin!(future);
+    let mut cx = Context::from_waker(Waker::noop());
+    match future.as_mut().poll(&mut cx) {
+        Poll::Ready(output) => output,
+        Poll::Pending => panic!("future is pending"),
+    }
+}
+
+/// Exposes a `MockDisplay` through the asynchronous traits.
+struct AsyncMock {
+    display: MockDisplay,
+    delay: Delay,
+}
+
+impl AsyncMock {
+    fn new() -> Self {
+        let mut display = MockDisplay::new();
+        display.set_feature(0x10, MockFeature::Continuous {
+            value: 50,
+            maximum: 100,
+        });
+        display.set_feature(0x73, MockFeature::Table((0..70).collect()));
+        display.capabilities =
+            b"(prot(monitor)type(lcd)model(TEST)cmds(01 02 03 0C E3 F3)vcp(10 73)mccs_ver(2.1))".to_vec();
+        AsyncMock {
+            display,
+            delay: Default::default(),
+        }
+    }
+}
+
+impl DdcHost for AsyncMock {
+    type Error = ErrorCode;
+}
+
+impl SendAsyncDdcCommandRaw for AsyncMock {
+    async fn execute_raw<'a>(
+        &mut self,
+        data: &[u8],
+        out: &'a mut [u8],
+        response_delay: Duration,
+    ) -> Result<&'a mut [u8], Self::Error> {
+        self.display.execute_raw(data, out, response_delay)
+    }
+}
+
+impl AsyncDdcCommandRawMarker for AsyncMock {
+    fn set_sleep_delay(&mut self, delay: Delay) {
+        self.delay = delay;
+    }
+}
+
+impl AsyncDdcCommandMarker for AsyncMock {}
+
+#[test]
+fn vcp_feature() {
+    let mut ddc = AsyncMock::new();
+
+    let value = block_on(AsyncDdc::get_vcp_feature(&mut ddc, 0x10)).unwrap();
+    assert_eq!((value.value(), value.maximum()), (50, 100));
+    assert!(ddc.delay.remaining() > Duration::default());
+
+    block_on(AsyncDdc::set_vcp_feature(&mut ddc, 0x10, 70)).unwrap();
+    assert_eq!(
+        block_on(SendAsyncDdc::get_vcp_feature(&mut ddc, 0x10)).unwrap().value(),
+        70
+    );
+
+    assert_eq!(
+        block_on(AsyncDdc::get_vcp_feature(&mut ddc, 0x99)),
+        Err(ErrorCode::UnsupportedFeature(0x99))
+    );
+    assert_eq!(
+        block_on(SendAsyncDdc::get_vcp_feature(&mut ddc, 0x99)),
+        Err(ErrorCode::UnsupportedFeature(0x99))
+    );
+
+    block_on(SendAsyncDdc::save_current_settings(&mut ddc)).unwrap();
+    assert_eq!(ddc.display.save_count, 1);
+}
+
+#[test]
+fn identification_and_self_test() {
+    let mut ddc = AsyncMock::new();
+    ddc.display.identification = b"TEST".to_vec();
+    ddc.display.self_test = vec![0x00];
+
+    assert_eq!(block_on(AsyncDdc::identification(&mut ddc)).unwrap().bytes(), b"TEST");
+    assert_eq!(block_on(SendAsyncDdc::self_test(&mut ddc)).unwrap().bytes(), &[0x00]);
+
+    block_on(AsyncDdc::enable_application_report(&mut ddc, true)).unwrap();
+    assert!(ddc.display.application_report);
+    block_on(SendAsyncDdc::enable_application_report(&mut ddc, false)).unwrap();
+    assert!(!ddc.display.application_report);
+}
+
+#[test]
+fn capabilities() {
+    let mut ddc = AsyncMock::new();
+    let expected = ddc.display.capabilities.clone();
+
+    assert_eq!(block_on(AsyncDdc::capabilities_string(&mut ddc)).unwrap(), expected);
+    assert_eq!(block_on(SendAsyncDdc::capabilities_string(&mut ddc)).unwrap(), expected);
+
+    let mut buf = [0u8; 0x100];
+    let len = block_on(AsyncDdc::capabilities_string_into(&mut ddc, &mut buf)).unwrap();
+    assert_eq!(&buf[..len], &expected[..]);
+    assert_eq!(
+        block_on(SendAsyncDdc::capabilities_string_into(&mut ddc, &mut buf[..16])),
+        Err(ErrorCode::BufferTooSmall)
+    );
+
+    let caps = block_on(SendAsyncDdc::capabilities(&mut ddc)).unwrap();
+    assert_eq!(caps.model.as_deref(), Some("TEST"));
+}
+
+#[test]
+fn table() {
+    let mut ddc = AsyncMock::new();
+
+    assert_eq!(
+        block_on(AsyncDdcTable::table_read(&mut ddc, 0x73)).unwrap(),
+        (0..70).collect::<Vec<u8>>()
+    );
+
+    block_on(SendAsyncDdcTable::table_write(&mut ddc, 0x73, 2, &[0xaa; 40])).unwrap();
+    let mut buf = [0u8; 0x100];
+    let len = block_on(SendAsyncDdcTable::table_read_into(&mut ddc, 0x73, &mut buf)).unwrap();
+    assert_eq!(len, 70);
+    assert_eq!(&buf[2..42], &[0xaa; 40][..]);
+    assert_eq!(buf[42], 42);
+}
+
+#[test]
+fn send_fragments() {
+    let mut ddc = AsyncMock::new();
+    let mut offsets = Vec::new();
+    let mut progress = |offset| offsets.push(offset);
+
+    let mut value = Vec::new();
+    block_on(
+        SendAsyncFragments::table(&mut ddc, 0x73)
+            .on_progress(&mut progress)
+            .read_to_end(&mut value),
+    )
+    .unwrap();
+    assert_eq!(value.len(), 70);
+    assert_eq!(offsets, [32, 64, 70]);
+}
+
+#[test]
+fn send_futures() {
+    fn assert_send<F: Future + Send>(future: F) -> F::Output {
+        block_on(future)
+    }
+
+    // only the trait bounds are known here, so the futures must be declared `Send`
+    fn generic<D: SendAsyncDdc + SendAsyncDdcTable + Send>(ddc: &mut D) -> Result<usize, D::Error> {
+        let value = assert_send(ddc.get_vcp_feature(0x10))?;
+        assert_send(ddc.set_vcp_feature(0x10, value.value()))?;
+        assert_send(ddc.table_read(0x73)).map(|table| table.len())
+    }
+
+    assert_eq!(generic(&mut AsyncMock::new()), Ok(70));
+}
+
+#[test]
+fn sleep_async() {
+    struct Recorder(Vec<Duration>);
+
+    impl AsyncTimer for Recorder {
+        async fn sleep(&mut self, duration: Duration) {
+            self.0.push(duration)
+        }
+    }
+
+    let mut timer = Recorder(Vec::new());
+    let mut delay = Delay::new(Duration::from_secs(60));
+    block_on(delay.sleep_async(&mut timer));
+    assert_eq!(timer.0.len(), 1);
+    assert!(timer.0[0] > Duration::from_secs(59));
+
+    // the delay has been waited out
+    block_on(delay.sleep_async(&mut timer));
+    assert_eq!(timer.0.len(), 1);
+}
@@ -0,0 +1,124 @@
+use {
+    core::time::Duration,
+    ddc::{
+        commands::{GetVcpFeature, SaveCurrentSettings, TableWrite},
+        Buffer, Command, CommandResult, DdcCommand, DdcCommandRaw, DdcCommandRawMarker, DdcHost, Delay, ErrorCode,
+    },
+};
+
+/// A manufacturer command longer than any standard one.
+struct Vendor(usize);
+
+/// Its reply, longer than any standard reply.
+#[derive(Debug)]
+struct VendorReply(Vec<u8>);
+
+impl CommandResult for VendorReply {
+    type Buffer = Vec<u8>;
+
+    const MAX_LEN: usize = 100;
+
+    fn decode(data: &[u8]) -> Result<Self, ErrorCode> {
+        Ok(VendorReply(data.to_vec()))
+    }
+}
+
+impl Command for Vendor {
+    type Buffer = Vec<u8>;
+    type Ok = VendorReply;
+
+    const DELAY_COMMAND_MS: u64 = 0;
+    const DELAY_RESPONSE_MS: u64 = 0;
+    const MAX_LEN: usize = 60;
+    const MIN_LEN: usize = 1;
+
+    fn len(&self) -> usize {
+        self.0
+    }
+
+    fn encode(&self, data: &mut [u8]) -> Result<usize, ErrorCode> {
+        data.fill(0xaa);
+        Ok(data.len())
+    }
+}
+
+/// A command whose buffer can't hold it.
+struct Overflow;
+
+impl Command for Overflow {
+    type Buffer = [u8; 2];
+    type Ok = ();
+
+    const DELAY_COMMAND_MS: u64 = 0;
+    const DELAY_RESPONSE_MS: u64 = 0;
+    const MAX_LEN: usize = 4;
+    const MIN_LEN: usize = 4;
+
+    fn len(&self) -> usize {
+        4
+    }
+
+    fn encode(&self, _: &mut [u8]) -> Result<usize, ErrorCode> {
+        unreachable!()
+    }
+}
+
+/// Records the sizes of the buffers it's given, replying with `reply` bytes.
+#[derive(Default)]
+struct Sizes {
+    sizes: Vec<(usize, usize)>,
+    reply: usize,
+}
+
+impl DdcHost for Sizes {
+    type Error = ErrorCode;
+}
+
+impl DdcCommandRaw for Sizes {
+    fn execute_raw<'a>(&mut self, data: &[u8], out: &'a mut [u8], _: Duration) -> Result<&'a mut [u8], ErrorCode> {
+        self.sizes.push((data.len(), out.len()));
+        let len = self.reply.min(out.len());
+        Ok(&mut out[..len])
+    }
+}
+
+impl DdcCommandRawMarker for Sizes {
+    fn set_sleep_delay(&mut self, _: Delay) {}
+}
+
+#[test]
+fn array_buffers() {
+    let buffer = <[u8; 4]>::with_len(100);
+    assert_eq!(buffer, [0; 4]);
+    assert_eq!(Vec::<u8>::with_len(100).len(), 100);
+
+    let mut ddc = Sizes::default();
+    assert_eq!(ddc.execute(SaveCurrentSettings), Ok(()));
+    assert_eq!(ddc.execute(TableWrite::new(0x73, 0, &[1; 32])), Ok(()));
+    assert_eq!(ddc.execute(GetVcpFeature::new(0x10)), Err(ErrorCode::NullResponse));
+    // commands without a reply don't read one, and replies are read with room
+    // for the framing around the payload
+    assert_eq!(ddc.sizes, [(1, 0), (36, 0), (2, 11)]);
+}
+
+#[test]
+fn vec_buffers() {
+    let mut ddc = Sizes {
+        reply: 90,
+        ..Default::default()
+    };
+    let reply = ddc.execute(Vendor(60)).unwrap();
+    assert_eq!(reply.0.len(), 90);
+    assert_eq!(ddc.sizes, [(60, 103)]);
+
+    // the buffer is sized for the command rather than its maximum
+    ddc.execute(Vendor(5)).unwrap();
+    assert_eq!(ddc.sizes[1], (5, 103));
+}
+
+#[test]
+fn buffer_too_small() {
+    let mut ddc = Sizes::default();
+    assert_eq!(ddc.execute(Overflow), Err(ErrorCode::InvalidLength));
+    assert!(ddc.sizes.is_empty());
+}
@@ -0,0 +1,154 @@
+use ddc::caps::{Capabilities, CapabilitiesError, CapabilitiesErrorKind, EntryValue, Version};
+
+/// As reported by a Dell U2415.
+const DELL: &[u8] = b"(prot(monitor)type(LCD)model(U2415)cmds(01 02 03 07 0C E3 F3)vcp(02 04 05 08 10 12 14(01 04 05 06 08 09 0B 0C) 16 18 1A 52 60(01 0F 11 ) AA(01 02) AC AE B2 B6 C6 C8 C9 D6(01 04 05) DC(00 02 03 05 ) DF E0 E1 E2(00 01 02 04 0E 12 14 19) F0(00 08) F1(01 02) F2 FD)mswhql(1)asset_eep(40)mccs_ver(2.1))";
+
+/// Displays that omit the spaces between codes, and pad the string with NULs
+/// after an unbalanced final parenthesis.
+const PACKED: &[u8] =
+    b"(prot(monitor)type(lcd)model(S24D300)cmds(01020307 0CE3F3)vcp(0204050810121416(0105060B)60(0103)D6(0104)\0\0\0";
+
+fn error(position: usize, kind: CapabilitiesErrorKind) -> Result<Capabilities, CapabilitiesError> {
+    Err(CapabilitiesError { position, kind })
+}
+
+#[test]
+fn dell() {
+    let caps = Capabilities::parse(DELL).unwrap();
+    assert_eq!(caps.protocol.as_deref(), Some("monitor"));
+    assert_eq!(caps.ty.as_deref(), Some("LCD"));
+    assert_eq!(caps.model.as_deref(), Some("U2415"));
+    assert_eq!(caps.commands, [0x01, 0x02, 0x03, 0x07, 0x0c, 0xe3, 0xf3]);
+    assert_eq!(caps.ms_whql, Some(1));
+    assert_eq!(caps.mccs_version, Some(Version { major: 2, minor: 1 }));
+
+    assert_eq!(caps.vcp_features.len(), 30);
+    assert_eq!(caps.vcp_features[&0x14].values, [
+        0x01, 0x04, 0x05, 0x06, 0x08, 0x09, 0x0b, 0x0c
+    ]);
+    // trailing whitespace inside a value list
+    assert_eq!(caps.vcp_features[&0x60].values, [0x01, 0x0f, 0x11]);
+    assert!(caps.vcp_features[&0x10].values.is_empty());
+    assert_eq!(caps.vcp_features[&0x10].name, None);
+
+    assert_eq!(caps.unknown.len(), 1);
+    assert_eq!(caps.unknown[0].name, "asset_eep");
+    assert_eq!(caps.unknown[0].value, EntryValue::String("40".into()));
+}
+
+#[test]
+fn packed() {
+    let caps = Capabilities::parse(PACKED).unwrap();
+    assert_eq!(caps.ty.as_deref(), Some("lcd"));
+    assert_eq!(caps.commands, [0x01, 0x02, 0x03, 0x07, 0x0c, 0xe3, 0xf3]);
+    assert_eq!(caps.vcp_features.keys().cloned().collect::<Vec<_>>(), [
+        0x02, 0x04, 0x05, 0x08, 0x10, 0x12, 0x14, 0x16, 0x60, 0xd6
+    ]);
+    assert_eq!(caps.vcp_features[&0x16].values, [0x01, 0x05, 0x06, 0x0b]);
+    assert_eq!(caps.vcp_features[&0xd6].values, [0x01, 0x04]);
+    assert_eq!(caps.mccs_version, None);
+}
+
+#[test]
+fn names_windows_binary() {
+    let caps = Capabilities::parse(
+        b"(prot(monitor)vcp(10 14(05 08) E0)vcpname(14(Color Preset) E0(Custom Feature))mccs_ver(3.0)\
+          window1(type(PIP) area(25 25 1895 1175) max(640 480) window(10))edid bin(4(\x00()\xff))vdif bin(2(ab)))",
+    )
+    .unwrap();
+    assert_eq!(caps.mccs_version, Some(Version { major: 3, minor: 0 }));
+    assert_eq!(caps.mccs_version.unwrap().to_string(), "3.0");
+    assert_eq!(caps.vcp_features[&0x14].name.as_deref(), Some("Color Preset"));
+    assert_eq!(caps.vcp_features[&0x14].values, [0x05, 0x08]);
+    assert_eq!(caps.vcp_features[&0xe0].name.as_deref(), Some("Custom Feature"));
+
+    assert_eq!(caps.windows.len(), 1);
+    assert_eq!(caps.windows[0].index, 1);
+    let entries: Vec<_> = caps.windows[0].entries.iter().map(|e| e.name.as_str()).collect();
+    assert_eq!(entries, ["type", "area", "max", "window"]);
+    assert_eq!(
+        caps.windows[0].entries[1].value,
+        EntryValue::String("25 25 1895 1175".into())
+    );
+
+    // binary data may contain parentheses
+    assert_eq!(caps.edid.as_deref(), Some(&b"\x00()\xff"[..]));
+    assert_eq!(caps.vdif, [b"ab".to_vec()]);
+}
+
+#[test]
+fn lenient() {
+    // without the outer parentheses, and a version without a minor number
+    let caps = Capabilities::parse(b"prot(monitor) vcp(10 12) mccs_ver(2)").unwrap();
+    assert_eq!(caps.vcp_features.len(), 2);
+    assert_eq!(caps.mccs_version, Some(Version { major: 2, minor: 0 }));
+
+    // stray closing parentheses at the end
+    let caps = Capabilities::parse(b"(prot(monitor)vcp(10)))) \0").unwrap();
+    assert_eq!(caps.vcp_features.len(), 1);
+
+    // and in the middle
+    let caps = Capabilities::parse(b"(prot(monitor)))vcp(10 12) )type(lcd))").unwrap();
+    assert_eq!(caps.vcp_features.len(), 2);
+    assert_eq!(caps.ty.as_deref(), Some("lcd"));
+
+    // an unterminated value list runs to the end of the string
+    let caps = Capabilities::parse(b"(vcp(10 14(05 08").unwrap();
+    assert_eq!(caps.vcp_features[&0x14].values, [0x05, 0x08]);
+
+    assert_eq!(Capabilities::parse(b"").unwrap(), Capabilities::default());
+    assert_eq!(Capabilities::parse(b"\0\0\0").unwrap(), Capabilities::default());
+}
+
+#[test]
+fn malformed() {
+    assert_eq!(
+        Capabilities::parse(b"(prot(monitor)vcp(10 zz))"),
+        error(21, CapabilitiesErrorKind::UnexpectedCharacter(b'z'))
+    );
+    assert_eq!(
+        Capabilities::parse(b"(prot(monitor)vcp(10 zz))")
+            .unwrap_err()
+            .to_string(),
+        "unexpected 'z' in capabilities at offset 21"
+    );
+    assert_eq!(
+        Capabilities::parse(b"(cmds(01 02 -3))"),
+        error(12, CapabilitiesErrorKind::UnexpectedCharacter(b'-'))
+    );
+    assert_eq!(
+        Capabilities::parse(b"(vcp(10)mccs_ver(x.1))"),
+        error(17, CapabilitiesErrorKind::InvalidVersion)
+    );
+
+    // an entry without a value
+    assert_eq!(
+        Capabilities::parse(b"(prot(monitor)model"),
+        error(19, CapabilitiesErrorKind::UnexpectedEnd)
+    );
+    assert_eq!(
+        Capabilities::parse(b"(prot(monitor) model type(lcd))"),
+        error(21, CapabilitiesErrorKind::UnexpectedCharacter(b't'))
+    );
+
+    // binary lengths that don't match their data
+    assert_eq!(
+        Capabilities::parse(b"(edid bin(8(abc)))"),
+        error(17, CapabilitiesErrorKind::UnexpectedEnd)
+    );
+    assert_eq!(
+        Capabilities::parse(b"(edid bin(2(abc)))"),
+        error(14, CapabilitiesErrorKind::InvalidLength)
+    );
+    assert_eq!(
+        Capabilities::parse(b"(edid bin(x(abc)))"),
+        error(10, CapabilitiesErrorKind::InvalidLength)
+    );
+
+    // nesting is bounded rather than recursing without limit
+    assert_eq!(
+        Capabilities::parse(&[b'('; 100_000]),
+        error(8, CapabilitiesErrorKind::NestingTooDeep)
+    );
+    assert!(Capabilities::parse(b"((((prot(monitor)))))").is_ok());
+}
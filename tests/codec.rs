@@ -0,0 +1,150 @@
+use {
+    ddc::{
+        commands::{
+            AnyCommand, CapabilitiesReply, CapabilitiesRequest, Command, CommandResult, DecodeCommand,
+            EnableApplicationReport, EncodeResult, GetTimingReport, GetVcpFeature, IdentificationReply,
+            IdentificationRequest, SaveCurrentSettings, SelfTestReply, SelfTestRequest, SetVcpFeature, TableRead,
+            TableResponse, TableWrite,
+        },
+        ErrorCode, TimingMessage, VcpValue,
+    },
+    quickcheck::quickcheck,
+};
+
+fn encode<C: Command>(command: &C) -> Vec<u8> {
+    let mut data = vec![0u8; command.len()];
+    let len = command.encode(&mut data).unwrap();
+    data.truncate(len);
+    data
+}
+
+fn roundtrip<'a, C: DecodeCommand<'a> + PartialEq + std::fmt::Debug>(command: C, data: &'a mut Vec<u8>) -> bool {
+    *data = encode(&command);
+    let decoded = C::decode_request(data).unwrap();
+    decoded == command && AnyCommand::decode_request(data).is_ok()
+}
+
+fn reply<R: EncodeResult>(result: &R) -> Vec<u8> {
+    let mut data = vec![0u8; R::MAX_LEN];
+    let len = result.encode_reply(&mut data).unwrap();
+    data.truncate(len);
+    data
+}
+
+fn truncate(mut bytes: Vec<u8>) -> Vec<u8> {
+    bytes.truncate(32);
+    bytes
+}
+
+quickcheck! {
+    fn get_vcp_feature(code: u8) -> bool {
+        roundtrip(GetVcpFeature::new(code), &mut Vec::new())
+    }
+
+    fn set_vcp_feature(code: u8, value: u16) -> bool {
+        roundtrip(SetVcpFeature::new(code, value), &mut Vec::new())
+    }
+
+    fn table_read(code: u8, offset: u16) -> bool {
+        roundtrip(TableRead::new(code, offset), &mut Vec::new())
+    }
+
+    fn table_write(code: u8, offset: u16, bytes: Vec<u8>) -> bool {
+        let bytes = truncate(bytes);
+        roundtrip(TableWrite::new(code, offset, &bytes), &mut Vec::new())
+    }
+
+    fn capabilities_request(offset: u16) -> bool {
+        roundtrip(CapabilitiesRequest::new(offset), &mut Vec::new())
+    }
+
+    fn enable_application_report(enable: bool) -> bool {
+        roundtrip(EnableApplicationReport::new(enable), &mut Vec::new())
+    }
+
+    fn vcp_value(code: u8, ty: u8, mh: u8, ml: u8, sh: u8, sl: u8) -> bool {
+        let command = GetVcpFeature::new(code);
+        let value = VcpValue { ty, mh, ml, sh, sl };
+        let mut data = [0u8; 8];
+        let len = command.encode_reply(Some(&value), &mut data).unwrap();
+        let decoded = command.decode(&data[..len]).unwrap();
+        (decoded.ty, decoded.mh, decoded.ml, decoded.sh, decoded.sl) == (ty, mh, ml, sh, sl)
+    }
+
+    fn table_response(offset: u16, bytes: Vec<u8>) -> bool {
+        let table = TableResponse::new(offset, &truncate(bytes)).unwrap();
+        TableResponse::decode(&reply(&table)).unwrap() == table
+            && TableRead::new(0, offset).decode(&reply(&table)).is_ok()
+    }
+
+    fn capabilities_reply(offset: u16, bytes: Vec<u8>) -> bool {
+        let caps = CapabilitiesReply::new(offset, &truncate(bytes)).unwrap();
+        CapabilitiesReply::decode(&reply(&caps)).unwrap() == caps
+            && CapabilitiesRequest::new(offset).decode(&reply(&caps)).is_ok()
+    }
+
+    fn timing_message(timing_status: u8, horizontal_frequency: u16, vertical_frequency: u16) -> bool {
+        let timing = TimingMessage {
+            timing_status,
+            horizontal_frequency,
+            vertical_frequency,
+        };
+        TimingMessage::decode(&reply(&timing)).unwrap() == timing
+    }
+
+    fn identification_reply(bytes: Vec<u8>) -> bool {
+        let id = IdentificationReply::new(&truncate(bytes)).unwrap();
+        IdentificationReply::decode(&reply(&id)).unwrap() == id
+    }
+
+    fn self_test_reply(bytes: Vec<u8>) -> bool {
+        let test = SelfTestReply::new(&truncate(bytes)).unwrap();
+        SelfTestReply::decode(&reply(&test)).unwrap() == test
+    }
+}
+
+#[test]
+fn unit_commands() {
+    assert!(roundtrip(SaveCurrentSettings, &mut Vec::new()));
+    assert!(roundtrip(GetTimingReport, &mut Vec::new()));
+    assert!(roundtrip(IdentificationRequest, &mut Vec::new()));
+    assert!(roundtrip(SelfTestRequest, &mut Vec::new()));
+    assert_eq!(reply(&()), Vec::<u8>::new());
+}
+
+#[test]
+fn unsupported_feature() {
+    let command = GetVcpFeature::new(0x10);
+    let mut data = [0u8; 8];
+    let len = command.encode_reply(None, &mut data).unwrap();
+    assert_eq!(command.decode(&data[..len]), Err(ErrorCode::UnsupportedFeature(0x10)));
+}
+
+#[test]
+fn invalid_requests() {
+    assert_eq!(
+        GetVcpFeature::decode_request(&[0x03, 0x10]),
+        Err(ErrorCode::OpcodeMismatch {
+            expected: 0x01,
+            actual: 0x03
+        })
+    );
+    assert_eq!(
+        SetVcpFeature::decode_request(&[0x03, 0x10]),
+        Err(ErrorCode::LengthMismatch { expected: 4, actual: 2 })
+    );
+    assert_eq!(
+        AnyCommand::decode_request(&[0x99, 0x01]).unwrap(),
+        AnyCommand::Raw(ddc::commands::RawCommand::new(vec![0x99, 0x01], 0x7f))
+    );
+}
+
+#[test]
+fn oversized_table_fragment() {
+    let mut data = vec![0xe4, 0x00, 0x00];
+    data.extend_from_slice(&[0xaa; 33]);
+    assert_eq!(TableResponse::decode(&data), Err(ErrorCode::InvalidLength));
+
+    data.pop();
+    assert_eq!(TableResponse::decode(&data).unwrap().bytes(), &[0xaa; 32][..]);
+}
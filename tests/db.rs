@@ -0,0 +1,124 @@
+use ddc::{
+    caps::{Capabilities, Version},
+    db::{definition, versioned_definition, Access, Database, FeatureType, ValidationError, FEATURES, FEATURES_V3},
+    features::{InputSource, Mute, PowerMode},
+    VcpValue,
+};
+
+const V2_1: Version = Version { major: 2, minor: 1 };
+const V2_2: Version = Version { major: 2, minor: 2 };
+const V3_0: Version = Version { major: 3, minor: 0 };
+
+fn value(sh: u8, sl: u8, maximum: u16) -> VcpValue {
+    VcpValue {
+        ty: 0,
+        mh: (maximum >> 8) as u8,
+        ml: maximum as u8,
+        sh,
+        sl,
+    }
+}
+
+#[test]
+fn sorted() {
+    // lookups are binary searches
+    for features in [FEATURES, FEATURES_V3] {
+        assert!(features.windows(2).all(|w| w[0].code < w[1].code));
+        for feature in features {
+            assert_eq!(feature.values.windows(2).filter(|w| w[0].0 >= w[1].0).count(), 0);
+        }
+    }
+    for feature in FEATURES {
+        assert_eq!(definition(feature.code), Some(feature));
+    }
+    assert_eq!(definition(0x00), None);
+    assert_eq!(definition(0xe0), None);
+}
+
+#[test]
+fn shared_value_names() {
+    assert_eq!(definition(0x60).unwrap().values, InputSource::VALUES);
+    assert_eq!(definition(0x8d).unwrap().values, Mute::VALUES);
+    assert_eq!(definition(0xd6).unwrap().value_name(0x05), PowerMode::OffHard.name());
+    assert_eq!(InputSource::DisplayPort1.name(), Some("DisplayPort-1"));
+    assert_eq!(InputSource::Other(0x1b).name(), None);
+}
+
+#[test]
+fn format() {
+    let caps = Capabilities::parse(b"(vcp(10 60(0F 11) 73 D6(01 04) DF E9)vcpname(E9(Vendor))mccs_ver(2.1))").unwrap();
+    let db = Database::from_capabilities(&caps);
+    assert_eq!(db.features().count(), 6);
+
+    assert_eq!(db.format(0x10, &value(0, 50, 100)), "Brightness: 50 / 100");
+    assert_eq!(
+        db.format(0x60, &value(0, 0x0f, 3)),
+        "Input Source: DisplayPort-1 (0x0f)"
+    );
+    assert_eq!(db.format(0x60, &value(0, 0x1b, 3)), "Input Source: 0x1b");
+    assert_eq!(db.format(0xd6, &value(0, 0x04, 5)), "Power Mode: Off (0x04)");
+    // the maximum of the version is meaningless
+    assert_eq!(db.format(0xdf, &value(0x02, 0x01, 0xffff)), "VCP Version: 2.1");
+    // manufacturer specific features are named by the display
+    assert_eq!(db.format(0xe9, &value(0x00, 0x32, 100)), "Vendor: 0x0032");
+    // tables aren't defined before MCCS 2.2
+    assert_eq!(db.format(0x73, &value(0, 0, 0)), "VCP 0x73: 0x0000");
+    assert_eq!(db.format(0x12, &value(0, 0, 0)), "VCP 0x12: 0x0000");
+
+    let db = Database::new(Some(V2_2));
+    assert_eq!(db.format(0x73, &value(0, 0, 0)), "LUT Size: table");
+}
+
+#[test]
+fn mccs_3() {
+    // the speaker volume gained special values
+    let volume = versioned_definition(0x62, V3_0).unwrap();
+    assert_eq!(volume.since, V3_0);
+    assert_eq!(volume.value_name(0xff), Some("Mute"));
+    assert_eq!(versioned_definition(0x62, V2_2), definition(0x62));
+    assert_eq!(definition(0x62).unwrap().value_name(0xff), None);
+
+    // features not redefined are shared with 2.x
+    assert_eq!(versioned_definition(0x10, V3_0), definition(0x10));
+    assert_eq!(versioned_definition(0x73, V3_0).unwrap().ty, FeatureType::Table);
+    assert_eq!(versioned_definition(0x73, V2_1), None);
+
+    let db = Database::new(Some(V3_0));
+    assert_eq!(
+        db.format(0x62, &value(0, 0xff, 0xff)),
+        "Audio Speaker Volume: Mute (0xff)"
+    );
+    assert_eq!(db.format(0x62, &value(0, 30, 0xff)), "Audio Speaker Volume: 30 / 255");
+    let db = Database::new(Some(V2_2));
+    assert_eq!(
+        db.format(0x62, &value(0, 0xff, 0xff)),
+        "Audio Speaker Volume: 255 / 255"
+    );
+}
+
+#[test]
+fn validate() {
+    let caps = Capabilities::parse(b"(vcp(10 60(0F 11) D6(01 04) DF)mccs_ver(2.1))").unwrap();
+    let db = Database::from_capabilities(&caps);
+
+    assert_eq!(db.validate(0x60, 0x11, None), Ok(()));
+    assert_eq!(db.validate(0x60, 0x12, None), Err(ValidationError::InvalidValue));
+    assert_eq!(db.validate(0x60, 0x111, None), Err(ValidationError::InvalidValue));
+    assert_eq!(db.validate(0x10, 100, Some(&value(0, 50, 100))), Ok(()));
+    assert_eq!(
+        db.validate(0x10, 101, Some(&value(0, 50, 100))),
+        Err(ValidationError::OutOfRange { maximum: 100 })
+    );
+    assert_eq!(db.validate(0x10, 101, None), Ok(()));
+    assert_eq!(db.validate(0xdf, 1, None), Err(ValidationError::ReadOnly));
+    assert_eq!(db.validate(0x12, 1, None), Err(ValidationError::UnsupportedFeature));
+
+    let db = Database::new(Some(V2_2));
+    assert_eq!(db.get(0x73).unwrap().access(), Access::ReadOnly);
+    assert_eq!(db.validate(0x73, 1, None), Err(ValidationError::ReadOnly));
+    assert_eq!(db.validate(0x74, 1, None), Err(ValidationError::Table));
+    assert_eq!(
+        ValidationError::OutOfRange { maximum: 100 }.to_string(),
+        "value exceeds VCP feature maximum of 100"
+    );
+}
@@ -0,0 +1,60 @@
+use ddc::{
+    device::DdcDevice,
+    fault::{FaultInjector, FaultPolicy},
+    mock::{MockDisplay, MockFeature},
+    ErrorCode,
+};
+
+fn display(value: u16) -> MockDisplay {
+    let mut display = MockDisplay::new();
+    display.set_feature(0x10, MockFeature::Continuous { value, maximum: 100 });
+    display.set_feature(0x73, MockFeature::Table(vec![1, 2, 3]));
+    display.capabilities = b"(model(TEST)vcp(10 73))".to_vec();
+    display.identification = b"TEST".to_vec();
+    display
+}
+
+#[test]
+fn dyn_devices() {
+    // displays from different backends used together
+    let faults = FaultInjector::new(display(20), FaultPolicy {
+        corrupt_checksum: 1.0,
+        ..FaultPolicy::never()
+    });
+    let mut devices: Vec<Box<dyn DdcDevice + Send>> = vec![Box::new(display(10)), Box::new(faults)];
+
+    let device = &mut devices[0];
+    assert_eq!(device.get_vcp_feature(0x10).unwrap().value(), 10);
+    device.set_vcp_feature(0x10, 30).unwrap();
+    assert_eq!(device.get_vcp_feature(0x10).unwrap().value(), 30);
+    assert_eq!(device.table_read(0x73).unwrap(), [1, 2, 3]);
+    assert_eq!(device.capabilities().unwrap().model.as_deref(), Some("TEST"));
+    assert_eq!(device.identification().unwrap().bytes(), b"TEST");
+
+    // the backend's error is preserved behind the erased error type
+    let err = devices[1].get_vcp_feature(0x10).unwrap_err();
+    assert_eq!(err.downcast_ref::<ErrorCode>(), Some(&ErrorCode::InvalidChecksum));
+    let err = devices[0].get_vcp_feature(0x99).unwrap_err();
+    assert_eq!(
+        err.downcast_ref::<ErrorCode>(),
+        Some(&ErrorCode::UnsupportedFeature(0x99))
+    );
+}
+
+#[test]
+fn forwarding() {
+    fn value<D: DdcDevice>(mut device: D) -> u16 {
+        device.get_vcp_feature(0x10).unwrap().value()
+    }
+
+    let mut mock = display(10);
+    assert_eq!(value(&mut mock as &mut dyn DdcDevice), 10);
+    assert_eq!(value(&mut mock as &mut (dyn DdcDevice + Send)), 10);
+
+    let mut boxed: Box<dyn DdcDevice + Send> = Box::new(mock);
+    assert_eq!(value(&mut *boxed), 10);
+    assert_eq!(value(boxed), 10);
+
+    let boxed: Box<dyn DdcDevice> = Box::new(display(20));
+    assert_eq!(value(boxed), 20);
+}
@@ -0,0 +1,417 @@
+use ddc::edid::{
+    cta::{CtaExtension, DataBlock, DataBlocks, Hdmi, HdmiForum, ShortAudioDescriptor, ShortVideoDescriptor},
+    displayid::DisplayIdExtension,
+    extensions, AspectRatio, BaseBlock, Descriptor, Extension, Mode, ParseError,
+};
+
+const EDID: [u8; 128] = [
+    0x00, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x00, 0x10, 0xac, 0xc4, 0xa0, 0x4c, 0x33, 0x32, 0x30, 0x0c, 0x1c, 0x01,
+    0x04, 0xa5, 0x3c, 0x22, 0x78, 0x3a, 0xee, 0x91, 0xa3, 0x54, 0x4c, 0x99, 0x26, 0x0f, 0x50, 0x54, 0xa5, 0x4b, 0x00,
+    0x71, 0x4f, 0x81, 0x80, 0xd1, 0xc0, 0xa9, 0xc0, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x56, 0x5e, 0x00,
+    0xa0, 0xa0, 0xa0, 0x29, 0x50, 0x30, 0x20, 0x35, 0x00, 0x55, 0x50, 0x21, 0x00, 0x00, 0x1a, 0x00, 0x00, 0x00, 0xff,
+    0x00, 0x37, 0x4d, 0x54, 0x30, 0x31, 0x38, 0x36, 0x53, 0x32, 0x43, 0x48, 0x4c, 0x0a, 0x00, 0x00, 0x00, 0xfc, 0x00,
+    0x44, 0x45, 0x4c, 0x4c, 0x20, 0x55, 0x32, 0x37, 0x31, 0x39, 0x44, 0x0a, 0x20, 0x00, 0x00, 0x00, 0xfd, 0x00, 0x31,
+    0x4c, 0x1e, 0x5a, 0x19, 0x01, 0x0a, 0x20, 0x20, 0x20, 0x20, 0x20, 0x20, 0x01, 0x14,
+];
+
+fn mode(width: u16, height: u16, refresh_rate: u16) -> Mode {
+    Mode {
+        width,
+        height,
+        refresh_rate,
+    }
+}
+
+#[test]
+fn identity() {
+    let edid = BaseBlock::parse(&EDID).unwrap();
+    assert_eq!(edid.manufacturer(), "DEL");
+    assert_eq!(edid.product_code, 0xa0c4);
+    assert_eq!(edid.serial_number, 0x3032334c);
+    assert_eq!((edid.week, edid.year), (12, 2018));
+    assert_eq!((edid.version, edid.revision), (1, 4));
+    assert!(edid.is_digital());
+    assert_eq!((edid.width_cm, edid.height_cm), (60, 34));
+    assert_eq!(edid.gamma(), Some(2.2));
+    assert_eq!(edid.name(), Some("DELL U2719D"));
+    assert_eq!(edid.serial(), Some("7MT0186S2CHL"));
+    assert_eq!(edid.extension_count, 1);
+}
+
+#[test]
+fn chromaticity() {
+    let edid = BaseBlock::parse(&EDID).unwrap();
+    assert_eq!(edid.chromaticity.red, (655, 338));
+    assert_eq!(edid.chromaticity.white, (320, 337));
+}
+
+#[test]
+fn timings() {
+    let edid = BaseBlock::parse(&EDID).unwrap();
+
+    let established: Vec<_> = edid.established_timings.modes().collect();
+    assert_eq!(established, [
+        mode(720, 400, 70),
+        mode(640, 480, 60),
+        mode(640, 480, 75),
+        mode(800, 600, 60),
+        mode(800, 600, 75),
+        mode(1024, 768, 60),
+        mode(1024, 768, 75),
+        mode(1280, 1024, 75),
+    ]);
+
+    let standard: Vec<_> = edid.standard_timings.iter().flatten().map(|t| t.mode()).collect();
+    assert_eq!(standard, [
+        mode(1152, 864, 75),
+        mode(1280, 1024, 60),
+        mode(1920, 1080, 60),
+        mode(1600, 900, 60),
+    ]);
+    assert_eq!(edid.standard_timings[2].unwrap().aspect_ratio, AspectRatio::Wide16x9);
+
+    let preferred = edid.preferred_timing().unwrap();
+    assert_eq!(preferred.mode(), mode(2560, 1440, 60));
+    assert_eq!(preferred.pixel_clock_khz, 241_500);
+    assert_eq!(
+        (preferred.horizontal_front_porch, preferred.horizontal_sync_width),
+        (48, 32)
+    );
+    assert_eq!((preferred.vertical_front_porch, preferred.vertical_sync_width), (3, 5));
+    assert_eq!((preferred.width_mm, preferred.height_mm), (597, 336));
+    assert!(!preferred.is_interlaced());
+    assert_eq!(edid.detailed_timings().count(), 1);
+}
+
+#[test]
+fn range_limits() {
+    let edid = BaseBlock::parse(&EDID).unwrap();
+    let limits = edid.range_limits().unwrap();
+    assert_eq!((limits.min_vertical_hz, limits.max_vertical_hz), (49, 76));
+    assert_eq!((limits.min_horizontal_khz, limits.max_horizontal_khz), (30, 90));
+    assert_eq!(limits.max_pixel_clock_mhz, 250);
+    assert!(matches!(edid.descriptors[3], Descriptor::RangeLimits(..)));
+}
+
+#[test]
+fn invalid() {
+    assert_eq!(BaseBlock::parse(&EDID[..127]), Err(ParseError::InvalidLength));
+
+    let mut edid = EDID;
+    edid[20] ^= 0x01;
+    assert_eq!(BaseBlock::parse(&edid), Err(ParseError::InvalidChecksum));
+
+    edid[0] = 0x01;
+    assert_eq!(BaseBlock::parse(&edid), Err(ParseError::InvalidHeader));
+}
+
+/// The data block collection of a CTA-861.3 extension, laid out the way HDR
+/// monitors with an HDMI 2.0 input commonly report it.
+#[rustfmt::skip]
+const CTA_DATA_BLOCKS: &[u8] = &[
+    // video: 1080p60 (native), 480p 16:9, 480p 4:3, a reserved SVD, 2160p60 and 2160p30
+    0x46, 0x90, 0x04, 0x03, 0x80, 0x61, 0x5f,
+    // audio: 2 channel LPCM at 32, 44.1 and 48 kHz
+    0x23, 0x09, 0x07, 0x07,
+    // speaker allocation: front left/right
+    0x83, 0x01, 0x00, 0x00,
+    // HDMI 1.4 VSDB: physical address 1.0.0.0, deep colour flags and 600 MHz
+    0x67, 0x03, 0x0c, 0x00, 0x10, 0x00, 0x38, 0x78,
+    // HDMI Forum VSDB: version 1, 600 MHz, SCDC present
+    0x67, 0xd8, 0x5d, 0xc4, 0x01, 0x78, 0x80, 0x07,
+    // colorimetry: BT.2020 RGB and YCC
+    0xe3, 0x05, 0xc0, 0x00,
+    // HDR static metadata: SDR, PQ and HLG with luminance codes
+    0xe6, 0x06, 0x0d, 0x01, 0x5a, 0x5a, 0x20,
+];
+
+/// The 1080p60 detailed timing of the CTA extension.
+const CTA_TIMING: [u8; 18] = [
+    0x02, 0x3a, 0x80, 0x18, 0x71, 0x38, 0x2d, 0x40, 0x58, 0x2c, 0x45, 0x00, 0x56, 0x50, 0x21, 0x00, 0x00, 0x1e,
+];
+
+/// A DisplayID 1.3 type I timing: 3840x2160 CVT reduced blanking, preferred.
+const TIMING_TYPE_I: [u8; 20] = [
+    0x4c, 0xd0, 0x00, 0x84, 0xff, 0x0e, 0x9f, 0x00, 0x2f, 0x80, 0x1f, 0x00, 0x6f, 0x08, 0x3d, 0x00, 0x02, 0x00, 0x04,
+    0x00,
+];
+
+/// A DisplayID 2.0 type VII timing: 2560x1440 at 144 Hz.
+const TIMING_TYPE_VII: [u8; 20] = [
+    0x5d, 0xf3, 0x08, 0x00, 0xff, 0x09, 0x9f, 0x00, 0x2f, 0x00, 0x1f, 0x00, 0x9f, 0x05, 0x38, 0x00, 0x02, 0x00, 0x04,
+    0x00,
+];
+
+fn fix_checksum(block: &mut [u8]) {
+    let sum = block[..127].iter().fold(0u8, |sum, &b| sum.wrapping_add(b));
+    block[127] = sum.wrapping_neg();
+}
+
+fn cta_extension() -> [u8; 128] {
+    let mut block = [0u8; 128];
+    let offset = 4 + CTA_DATA_BLOCKS.len();
+    block[..4].copy_from_slice(&[0x02, 0x03, offset as u8, 0xf1]);
+    block[4..offset].copy_from_slice(CTA_DATA_BLOCKS);
+    block[offset..offset + 18].copy_from_slice(&CTA_TIMING);
+    fix_checksum(&mut block);
+    block
+}
+
+/// A DisplayID extension holding a single section of `blocks`.
+fn displayid_extension(version: u8, blocks: &[&[u8]]) -> [u8; 128] {
+    let mut block = [0u8; 128];
+    let len = blocks.iter().map(|b| b.len()).sum::<usize>();
+    block[..5].copy_from_slice(&[0x70, version, len as u8, 0x03, 0x00]);
+    let mut offset = 5;
+    for data in blocks {
+        block[offset..offset + data.len()].copy_from_slice(data);
+        offset += data.len();
+    }
+    let sum = block[1..offset].iter().fold(0u8, |sum, &b| sum.wrapping_add(b));
+    block[offset] = sum.wrapping_neg();
+    fix_checksum(&mut block);
+    block
+}
+
+fn data_block(tag: u8, payload: &[u8]) -> Vec<u8> {
+    let mut block = vec![tag, 0x00, payload.len() as u8];
+    block.extend_from_slice(payload);
+    block
+}
+
+#[test]
+fn cta_flags() {
+    let block = cta_extension();
+    let cta = CtaExtension::parse(&block).unwrap();
+    assert_eq!(cta.revision, 3);
+    assert!(cta.underscan() && cta.basic_audio() && cta.ycbcr444() && cta.ycbcr422());
+    assert_eq!(cta.native_timing_count(), 1);
+    assert_eq!(cta.data_blocks().count(), 7);
+
+    let timings: Vec<_> = cta.detailed_timings().collect();
+    assert_eq!(timings.len(), 1);
+    assert_eq!(timings[0].mode(), mode(1920, 1080, 60));
+    assert_eq!(timings[0].pixel_clock_khz, 148_500);
+}
+
+#[test]
+fn cta_video_audio() {
+    let block = cta_extension();
+    let cta = CtaExtension::parse(&block).unwrap();
+
+    // the reserved SVD is skipped
+    let svds: Vec<_> = cta.video_descriptors().collect();
+    assert_eq!(svds, [
+        ShortVideoDescriptor { vic: 16, native: true },
+        ShortVideoDescriptor { vic: 4, native: false },
+        ShortVideoDescriptor { vic: 3, native: false },
+        ShortVideoDescriptor { vic: 97, native: false },
+        ShortVideoDescriptor { vic: 95, native: false },
+    ]);
+    for reserved in [0, 128, 254, 255] {
+        assert_eq!(ShortVideoDescriptor::parse(reserved), None);
+    }
+    // VICs from 193 have no native flag
+    assert_eq!(
+        ShortVideoDescriptor::parse(193),
+        Some(ShortVideoDescriptor {
+            vic: 193,
+            native: false
+        })
+    );
+
+    let sads: Vec<_> = cta.audio_descriptors().collect();
+    assert_eq!(sads, [ShortAudioDescriptor {
+        format: 1,
+        channels: 2,
+        sample_rates: 0x07,
+        detail: 0x07,
+    }]);
+}
+
+#[test]
+fn cta_hdmi() {
+    let block = cta_extension();
+    let cta = CtaExtension::parse(&block).unwrap();
+
+    assert_eq!(
+        cta.hdmi(),
+        Some(Hdmi {
+            physical_address: 0x1000,
+            flags: 0x38,
+            max_tmds_clock_mhz: Some(600),
+        })
+    );
+    let forum = cta.data_blocks().find_map(|block| match block {
+        DataBlock::HdmiForum(forum) => Some(forum),
+        _ => None,
+    });
+    assert_eq!(
+        forum,
+        Some(HdmiForum {
+            version: 1,
+            max_tmds_rate_mhz: 600,
+            flags: 0x80,
+        })
+    );
+
+    // a truncated HDMI VSDB is reported as an unknown vendor block
+    assert_eq!(
+        DataBlock::parse(3, &[0x03, 0x0c, 0x00, 0x10]),
+        DataBlock::VendorSpecific {
+            oui: 0x000c03,
+            payload: &[0x10],
+        }
+    );
+    // and a missing TMDS clock isn't reported as zero
+    assert_eq!(
+        DataBlock::parse(3, &[0x03, 0x0c, 0x00, 0x20, 0x00, 0x00, 0x00]),
+        DataBlock::Hdmi(Hdmi {
+            physical_address: 0x2000,
+            flags: 0,
+            max_tmds_clock_mhz: None,
+        })
+    );
+}
+
+#[test]
+fn cta_colorimetry_hdr() {
+    let block = cta_extension();
+    let cta = CtaExtension::parse(&block).unwrap();
+
+    let colorimetry = cta.colorimetry().unwrap();
+    assert!(colorimetry.bt2020_rgb() && colorimetry.bt2020_ycc());
+    assert!(!colorimetry.dci_p3());
+
+    let hdr = cta.hdr_static_metadata().unwrap();
+    assert!(hdr.sdr() && hdr.pq() && hdr.hlg());
+    assert_eq!(hdr.descriptors, 0x01);
+    // 50 * 2^(90 / 32) nits
+    let max = hdr.max_luminance_nits().unwrap();
+    assert!((max - 351.2).abs() < 1.0, "{}", max);
+    assert_eq!(hdr.max_frame_average_luminance_nits(), Some(max));
+    let min = hdr.min_luminance_nits().unwrap();
+    assert!((min - max * (32.0f32 / 255.0).powi(2) / 100.0).abs() < 0.001, "{}", min);
+
+    // the luminance codes are optional
+    match DataBlock::parse(7, &[0x06, 0x05, 0x01]) {
+        DataBlock::HdrStaticMetadata(hdr) => {
+            assert!(hdr.pq() && !hdr.hlg());
+            assert_eq!(hdr.max_luminance_nits(), None);
+            assert_eq!(hdr.min_luminance_nits(), None);
+        },
+        block => panic!("{:?}", block),
+    }
+}
+
+#[test]
+fn cta_malformed() {
+    // the length overruns the collection
+    assert_eq!(DataBlocks::new(&[0x23, 0x09, 0x07, 0x07, 0x45, 0x01, 0x02]).count(), 1);
+
+    assert_eq!(DataBlock::parse(7, &[0x0f, 0x01]), DataBlock::Extended {
+        tag: 0x0f,
+        payload: &[0x01],
+    });
+
+    let mut block = cta_extension();
+    block[2] = 0x02;
+    fix_checksum(&mut block);
+    assert_eq!(CtaExtension::parse(&block), Err(ParseError::InvalidLength));
+
+    // revision 1 has no data blocks
+    let mut block = cta_extension();
+    block[1] = 0x01;
+    fix_checksum(&mut block);
+    let cta = CtaExtension::parse(&block).unwrap();
+    assert_eq!(cta.data_blocks().count(), 0);
+    assert_eq!(cta.detailed_timings().count(), 1);
+
+    let mut block = cta_extension();
+    block[10] ^= 0x01;
+    assert_eq!(CtaExtension::parse(&block), Err(ParseError::InvalidChecksum));
+    assert_eq!(CtaExtension::parse(&block[..127]), Err(ParseError::InvalidLength));
+}
+
+#[test]
+fn displayid_type_i() {
+    let block = displayid_extension(0x12, &[&data_block(0x03, &TIMING_TYPE_I)]);
+    let displayid = DisplayIdExtension::parse(&block).unwrap();
+    assert!(!displayid.is_v2());
+    assert_eq!(displayid.product_type, 0x03);
+
+    let timings: Vec<_> = displayid.timings().collect();
+    assert_eq!(timings.len(), 1);
+    let timing = timings[0];
+    assert_eq!(timing.pixel_clock_khz, 533_250);
+    assert!(timing.is_preferred() && !timing.is_interlaced());
+    assert_eq!((timing.horizontal_blanking, timing.vertical_blanking), (160, 62));
+    // the sync polarity bit isn't part of the front porch
+    assert_eq!((timing.horizontal_front_porch, timing.horizontal_sync_width), (48, 32));
+    assert_eq!((timing.vertical_front_porch, timing.vertical_sync_width), (3, 5));
+    assert_eq!(timing.refresh_rate_millihertz(), 59_996);
+    assert_eq!(timing.mode(), mode(3840, 2160, 60));
+}
+
+#[test]
+fn displayid_type_vii() {
+    let cta = data_block(0x81, &[0x42, 0x10, 0x04]);
+    let block = displayid_extension(0x20, &[&data_block(0x22, &TIMING_TYPE_VII), &cta]);
+    let displayid = DisplayIdExtension::parse(&block).unwrap();
+    assert!(displayid.is_v2());
+    assert_eq!(displayid.data_blocks().count(), 2);
+
+    // type VII pixel clocks are in kHz rather than 10 kHz units
+    let timing = displayid.timings().next().unwrap();
+    assert_eq!(timing.pixel_clock_khz, 586_590);
+    assert!(!timing.is_preferred());
+    assert_eq!(timing.mode(), mode(2560, 1440, 144));
+
+    let blocks: Vec<_> = displayid.cta_data_blocks().collect();
+    assert_eq!(blocks, [DataBlock::Video(&[0x10, 0x04])]);
+}
+
+#[test]
+fn displayid_malformed() {
+    let mut block = displayid_extension(0x12, &[&data_block(0x03, &TIMING_TYPE_I)]);
+    // the section checksum is checked separately from the block checksum
+    block[10] = block[10].wrapping_add(1);
+    block[126] = block[126].wrapping_sub(1);
+    assert_eq!(block.iter().fold(0u8, |sum, &b| sum.wrapping_add(b)), 0);
+    assert_eq!(DisplayIdExtension::parse(&block), Err(ParseError::InvalidChecksum));
+
+    let mut block = displayid_extension(0x12, &[]);
+    block[2] = 0x80;
+    fix_checksum(&mut block);
+    assert_eq!(DisplayIdExtension::parse(&block), Err(ParseError::InvalidLength));
+
+    // a block length past the section ends iteration
+    let truncated = [0x03, 0x00, 0x14, 0x00, 0x00];
+    let block = displayid_extension(0x12, &[&truncated]);
+    let displayid = DisplayIdExtension::parse(&block).unwrap();
+    assert_eq!(displayid.data_blocks().count(), 0);
+}
+
+#[test]
+fn extension_blocks() {
+    let mut block_map = [0u8; 128];
+    block_map[0] = 0xf0;
+    fix_checksum(&mut block_map);
+
+    let mut edid = EDID.to_vec();
+    edid.extend_from_slice(&cta_extension());
+    edid.extend_from_slice(&displayid_extension(0x20, &[&data_block(0x22, &TIMING_TYPE_VII)]));
+    edid.extend_from_slice(&block_map);
+
+    let blocks: Vec<_> = extensions(&edid).collect();
+    assert_eq!(blocks.len(), 3);
+    assert!(matches!(blocks[0], Ok(Extension::Cta(..))));
+    assert!(matches!(blocks[1], Ok(Extension::DisplayId(..))));
+    assert!(matches!(blocks[2], Ok(Extension::Other { tag: 0xf0, .. })));
+
+    edid[128 + 10] ^= 0x01;
+    edid.truncate(edid.len() - 1);
+    let blocks: Vec<_> = extensions(&edid).collect();
+    assert_eq!(blocks[0], Err(ParseError::InvalidChecksum));
+    assert_eq!(blocks[2], Err(ParseError::InvalidLength));
+}
@@ -0,0 +1,150 @@
+use ddc::{
+    edid::{EdidReadError, BLOCK_LEN, HEADER},
+    Eddc, Edid,
+};
+
+/// An EDID EEPROM that reads back at most `chunk` bytes at a time, and
+/// nothing past the end of its contents.
+struct Eeprom {
+    data: Vec<u8>,
+    chunk: usize,
+    fail_segment: Option<u8>,
+    reads: Vec<(u8, u8)>,
+}
+
+impl Eeprom {
+    fn new(data: Vec<u8>) -> Self {
+        Eeprom {
+            data,
+            chunk: 0x100,
+            fail_segment: None,
+            reads: Vec::new(),
+        }
+    }
+}
+
+impl Edid for Eeprom {
+    type EdidError = &'static str;
+
+    fn read_edid(&mut self, offset: u8, data: &mut [u8]) -> Result<usize, Self::EdidError> {
+        self.read_eddc_edid(0, offset, data)
+    }
+}
+
+impl Eddc for Eeprom {
+    fn read_eddc_edid(&mut self, segment: u8, offset: u8, data: &mut [u8]) -> Result<usize, Self::EdidError> {
+        self.reads.push((segment, offset));
+        if Some(segment) == self.fail_segment {
+            return Err("nak")
+        }
+
+        let start = (segment as usize * 0x100 + offset as usize).min(self.data.len());
+        let len = data.len().min(self.chunk).min(self.data.len() - start);
+        data[..len].copy_from_slice(&self.data[start..start + len]);
+        Ok(len)
+    }
+}
+
+/// An EDID with `extensions` extension blocks and valid checksums.
+fn edid(extensions: u8) -> Vec<u8> {
+    let mut edid = vec![0u8; BLOCK_LEN * (1 + extensions as usize)];
+    edid[..8].copy_from_slice(&HEADER);
+    edid[126] = extensions;
+    for (i, block) in edid.chunks_mut(BLOCK_LEN).enumerate() {
+        if i > 0 {
+            block[0] = 0x70;
+            block[1] = i as u8;
+        }
+        fix_checksum(block);
+    }
+    edid
+}
+
+fn fix_checksum(block: &mut [u8]) {
+    let sum = block[..127].iter().fold(0u8, |sum, &b| sum.wrapping_add(b));
+    block[127] = sum.wrapping_neg();
+}
+
+#[test]
+fn base_block_only() {
+    let mut eeprom = Eeprom::new(edid(0));
+    assert_eq!(eeprom.read_full_edid().unwrap(), edid(0));
+    assert_eq!(eeprom.read_edid_blocks().unwrap(), edid(0));
+}
+
+#[test]
+fn extension_limit() {
+    // without E-DDC only the first extension is addressable
+    let mut eeprom = Eeprom::new(edid(3));
+    assert_eq!(eeprom.read_edid_blocks().unwrap(), &edid(3)[..2 * BLOCK_LEN]);
+    assert_eq!(eeprom.reads, [(0, 0x00), (0, 0x80)]);
+
+    eeprom.reads.clear();
+    assert_eq!(eeprom.read_full_edid().unwrap(), edid(3));
+    assert_eq!(eeprom.reads, [(0, 0x00), (0, 0x80), (1, 0x00), (1, 0x80)]);
+}
+
+#[test]
+fn partial_reads() {
+    let mut eeprom = Eeprom::new(edid(1));
+    eeprom.chunk = 32;
+    assert_eq!(eeprom.read_full_edid().unwrap(), edid(1));
+    assert_eq!(eeprom.reads.len(), 2 * BLOCK_LEN / 32);
+    assert_eq!(eeprom.reads[5], (0, 0xa0));
+}
+
+#[test]
+fn invalid_header() {
+    let mut data = edid(1);
+    data[0] = 0xff;
+    fix_checksum(&mut data[..BLOCK_LEN]);
+    assert_eq!(Eeprom::new(data).read_full_edid(), Err(EdidReadError::InvalidHeader));
+}
+
+#[test]
+fn invalid_checksum() {
+    let mut data = edid(3);
+    data[10] ^= 1;
+    assert_eq!(
+        Eeprom::new(data).read_full_edid(),
+        Err(EdidReadError::InvalidChecksum { block: 0 })
+    );
+
+    let mut data = edid(3);
+    data[2 * BLOCK_LEN + 10] ^= 1;
+    let mut eeprom = Eeprom::new(data);
+    assert_eq!(
+        eeprom.read_full_edid(),
+        Err(EdidReadError::InvalidChecksum { block: 2 })
+    );
+    // block 2 isn't read without E-DDC
+    assert!(eeprom.read_edid_blocks().is_ok());
+}
+
+#[test]
+fn truncated() {
+    // the extension count promises more blocks than the EEPROM holds
+    let mut data = edid(2);
+    data.truncate(2 * BLOCK_LEN + 100);
+    assert_eq!(
+        Eeprom::new(data).read_full_edid(),
+        Err(EdidReadError::Truncated { block: 2, len: 100 })
+    );
+
+    assert_eq!(
+        Eeprom::new(Vec::new()).read_full_edid(),
+        Err(EdidReadError::Truncated { block: 0, len: 0 })
+    );
+}
+
+#[test]
+fn io_error() {
+    let mut eeprom = Eeprom::new(edid(3));
+    eeprom.fail_segment = Some(1);
+    assert_eq!(eeprom.read_full_edid(), Err(EdidReadError::Io("nak")));
+    assert_eq!(EdidReadError::Io("nak").to_string(), "EDID read failed: nak");
+    assert_eq!(
+        EdidReadError::<&str>::Truncated { block: 2, len: 100 }.to_string(),
+        "EDID block 2 truncated after 100 bytes"
+    );
+}
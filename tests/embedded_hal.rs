@@ -0,0 +1,80 @@
+use {
+    ddc::{
+        hal::{HalDdc, HalI2c},
+        i2c::{I2cBus, I2cError},
+        Ddc, Eddc,
+    },
+    embedded_hal_mock::eh1::{
+        delay::NoopDelay,
+        i2c::{Mock, Transaction},
+    },
+};
+
+#[test]
+fn vcp_feature() {
+    let mut i2c = Mock::new(&[
+        Transaction::write(0x37, vec![0x51, 0x82, 0x01, 0x10, 0xac]),
+        Transaction::read(0x37, vec![
+            0x6e, 0x88, 0x02, 0x00, 0x10, 0x00, 0x00, 0x64, 0x00, 0x32, 0xf2,
+        ]),
+        Transaction::write(0x37, vec![0x51, 0x84, 0x03, 0x10, 0x00, 0x28, 0x80]),
+    ]);
+    let mut ddc = HalDdc::from_hal(i2c.clone(), NoopDelay::new());
+
+    let value = ddc.get_vcp_feature(0x10).unwrap();
+    assert_eq!(value.value(), 0x32);
+    assert_eq!(value.maximum(), 0x64);
+
+    ddc.set_vcp_feature(0x10, 0x28).unwrap();
+
+    i2c.done();
+}
+
+#[test]
+fn eddc_segment() {
+    // embedded-hal transactions address a single device, and the segment
+    // pointer would be reset by a separate transfer
+    let mut i2c = Mock::new(&[]);
+    let mut ddc = HalDdc::from_hal(i2c.clone(), NoopDelay::new());
+
+    let mut data = [0u8; 2];
+    assert!(matches!(
+        ddc.read_eddc_edid(1, 0x80, &mut data),
+        Err(I2cError::Unsupported)
+    ));
+
+    i2c.done();
+}
+
+#[test]
+fn eddc_first_segment() {
+    let mut i2c = Mock::new(&[Transaction::write_read(0x50, vec![0x80], vec![0x02, 0x03])]);
+    let mut ddc = HalDdc::from_hal(i2c.clone(), NoopDelay::new());
+
+    let mut data = [0u8; 2];
+    assert_eq!(ddc.read_eddc_edid(0, 0x80, &mut data).unwrap(), 2);
+    assert_eq!(data, [0x02, 0x03]);
+
+    i2c.done();
+}
+
+#[test]
+fn combined_transaction() {
+    let mut i2c = Mock::new(&[
+        Transaction::transaction_start(0x50),
+        Transaction::write(0x50, vec![0x01]),
+        Transaction::write(0x50, vec![0x80]),
+        Transaction::read(0x50, vec![0x02, 0x03]),
+        Transaction::transaction_end(0x50),
+    ]);
+    let mut bus = HalI2c(i2c.clone());
+
+    let mut data = [0u8; 2];
+    assert_eq!(
+        bus.write_write_read(0x50, &[0x01], 0x50, &[0x80], &mut data).unwrap(),
+        2
+    );
+    assert_eq!(data, [0x02, 0x03]);
+
+    i2c.done();
+}
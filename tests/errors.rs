@@ -0,0 +1,92 @@
+use ddc::{caps::Capabilities, ErrorCode};
+
+/// Errors caused by a corrupted, mismatched or missing reply.
+#[allow(deprecated)]
+fn transient() -> Vec<ErrorCode> {
+    vec![
+        ErrorCode::InvalidOffset,
+        ErrorCode::InvalidLength,
+        ErrorCode::InvalidChecksum,
+        ErrorCode::InvalidData,
+        ErrorCode::InvalidOpcode,
+        ErrorCode::LengthMismatch {
+            expected: 11,
+            actual: 5,
+        },
+        ErrorCode::OpcodeMismatch {
+            expected: 0x02,
+            actual: 0xe3,
+        },
+        ErrorCode::OffsetMismatch {
+            expected: 0x20,
+            actual: 0x00,
+        },
+        ErrorCode::NullResponse,
+        ErrorCode::WrongFeature {
+            expected: 0x10,
+            actual: 0x12,
+        },
+    ]
+}
+
+/// Errors that repeating the command won't fix.
+fn permanent() -> Vec<ErrorCode> {
+    vec![
+        ErrorCode::UnsupportedFeature(0x10),
+        ErrorCode::UnknownResultCode(0x02),
+        ErrorCode::BufferTooSmall,
+        ErrorCode::ReplyTooLong { limit: 0x100 },
+        ErrorCode::Invalid("custom".into()),
+        ErrorCode::InvalidCapabilities(Capabilities::parse(b"(vcp(zz))").unwrap_err()),
+    ]
+}
+
+#[test]
+fn is_retryable() {
+    for e in transient() {
+        assert!(e.is_retryable(), "{:?}", e);
+    }
+    for e in permanent() {
+        assert!(!e.is_retryable(), "{:?}", e);
+    }
+}
+
+#[test]
+fn is_unsupported() {
+    for e in transient().into_iter().chain(permanent()) {
+        assert_eq!(
+            e.is_unsupported(),
+            matches!(e, ErrorCode::UnsupportedFeature(..)),
+            "{:?}",
+            e
+        );
+    }
+}
+
+#[test]
+fn display() {
+    assert_eq!(
+        ErrorCode::OpcodeMismatch {
+            expected: 0x02,
+            actual: 0xe3
+        }
+        .to_string(),
+        "DDC/CI opcode mismatch: expected 0x02, got 0xe3"
+    );
+    assert_eq!(
+        ErrorCode::WrongFeature {
+            expected: 0x10,
+            actual: 0x12
+        }
+        .to_string(),
+        "DDC/CI VCP code mismatch: expected 0x10, got 0x12"
+    );
+    assert_eq!(
+        ErrorCode::UnsupportedFeature(0xd6).to_string(),
+        "unsupported VCP code 0xd6"
+    );
+    #[allow(deprecated)]
+    let opcode = ErrorCode::InvalidOpcode;
+    assert_eq!(opcode.to_string(), "DDC/CI VCP opcode mismatch");
+    assert_eq!(ErrorCode::Invalid("custom".into()).to_string(), "custom");
+}
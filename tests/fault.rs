@@ -0,0 +1,179 @@
+use {
+    core::time::Duration,
+    ddc::{
+        commands::CapabilitiesRequest,
+        fault::{Fault, FaultInjector, FaultPolicy},
+        mock::{MockDisplay, MockFeature},
+        retry::Retry,
+        Ddc, DdcCommand, DdcTable, ErrorCode, Timer,
+    },
+};
+
+/// Records injected delays instead of sleeping.
+#[derive(Default)]
+struct Delays(Vec<Duration>);
+
+impl Timer for Delays {
+    fn sleep(&mut self, duration: Duration) {
+        self.0.push(duration)
+    }
+}
+
+fn display() -> MockDisplay {
+    let mut display = MockDisplay::new();
+    display.set_feature(0x10, MockFeature::Continuous {
+        value: 50,
+        maximum: 100,
+    });
+    display.set_feature(0x73, MockFeature::Table((0..100).collect()));
+    display.capabilities = (0..200).map(|i| b'a' + (i % 26) as u8).collect();
+    display
+}
+
+/// A policy that always injects `fault`.
+fn only(seed: u64, fault: Fault) -> FaultPolicy {
+    let mut policy = FaultPolicy {
+        seed,
+        ..FaultPolicy::never()
+    };
+    match fault {
+        Fault::CorruptChecksum => policy.corrupt_checksum = 1.0,
+        Fault::Truncate => policy.truncate = 1.0,
+        Fault::Pad => policy.pad = 1.0,
+        Fault::NullMessage => policy.null_message = 1.0,
+        Fault::WrongOpcode => policy.wrong_opcode = 1.0,
+        Fault::RepeatFragment => policy.repeat_fragment = 1.0,
+    }
+    policy
+}
+
+fn injector(policy: FaultPolicy) -> FaultInjector<MockDisplay, Delays> {
+    FaultInjector::with_timer(display(), policy, Delays::default())
+}
+
+#[test]
+fn faults() {
+    for seed in 0..20 {
+        let get = |fault| {
+            let mut ddc = injector(only(seed, fault));
+            let res = ddc.get_vcp_feature(0x10);
+            assert_eq!(ddc.last_fault(), Some(fault));
+            res.unwrap_err()
+        };
+
+        assert_eq!(get(Fault::CorruptChecksum), ErrorCode::InvalidChecksum);
+        assert_eq!(get(Fault::NullMessage), ErrorCode::NullResponse);
+        match get(Fault::Truncate) {
+            // a short read, never one without a payload
+            ErrorCode::LengthMismatch { expected: 11, actual } => assert!((3..11).contains(&actual)),
+            e => panic!("unexpected error {:?}", e),
+        }
+        match get(Fault::WrongOpcode) {
+            ErrorCode::OpcodeMismatch { expected: 0x02, actual } => assert_ne!(actual, 0x02),
+            e => panic!("unexpected error {:?}", e),
+        }
+    }
+}
+
+#[test]
+fn pad() {
+    for seed in 0..20 {
+        let mut ddc = injector(only(seed, Fault::Pad));
+        // a full reply buffer leaves no room for padding
+        assert_eq!(ddc.get_vcp_feature(0x10).unwrap().value(), 50);
+        assert_eq!(ddc.last_fault(), None);
+
+        ddc.inner_mut().capabilities = b"(vcp(10))".to_vec();
+        let reply = ddc.execute(CapabilitiesRequest::new(0)).unwrap();
+        assert_eq!(ddc.last_fault(), Some(Fault::Pad));
+        assert!(reply.bytes().starts_with(b"(vcp(10))"));
+        assert!((10..=17).contains(&reply.bytes().len()));
+    }
+}
+
+#[test]
+fn repeat_fragment() {
+    let mut ddc = injector(only(1, Fault::RepeatFragment));
+    // the first fragment has nothing to repeat
+    assert!(ddc.get_vcp_feature(0x10).is_ok());
+    assert_eq!(ddc.last_fault(), None);
+    assert_eq!(
+        ddc.capabilities_string(),
+        Err(ErrorCode::OffsetMismatch {
+            expected: 32,
+            actual: 0
+        })
+    );
+    assert_eq!(ddc.last_fault(), Some(Fault::RepeatFragment));
+    assert_eq!(
+        ddc.table_read(0x73),
+        Err(ErrorCode::OffsetMismatch {
+            expected: 32,
+            actual: 0
+        })
+    );
+}
+
+#[test]
+fn latency() {
+    let mut ddc = injector(FaultPolicy {
+        latency: 1.0,
+        max_latency: Duration::from_millis(100),
+        ..FaultPolicy::never()
+    });
+    for _ in 0..10 {
+        assert_eq!(ddc.get_vcp_feature(0x10).unwrap().value(), 50);
+        assert_eq!(ddc.last_fault(), None);
+    }
+    let (_, delays) = ddc.into_inner();
+    assert_eq!(delays.0.len(), 10);
+    assert!(delays.0.iter().all(|&delay| delay <= Duration::from_millis(100)));
+}
+
+#[test]
+fn seeded() {
+    let run = |seed| {
+        let mut ddc = injector(FaultPolicy::uniform(seed, 0.1));
+        (0..100)
+            .map(|_| (ddc.get_vcp_feature(0x10), ddc.last_fault()))
+            .collect::<Vec<_>>()
+    };
+    let results = run(42);
+    assert_eq!(results, run(42));
+    assert_ne!(results, run(43));
+    assert!(results.iter().any(|(res, _)| res.is_ok()));
+    // every error is explained by the fault that caused it
+    assert!(results.iter().all(|(res, fault)| res.is_ok() == fault.is_none()));
+}
+
+#[test]
+fn retried() {
+    for fault in [
+        Fault::CorruptChecksum,
+        Fault::Truncate,
+        Fault::NullMessage,
+        Fault::WrongOpcode,
+    ] {
+        // a fault that always occurs is retried until the attempts run out
+        let mut ddc = Retry::with_timer(injector(only(5, fault)), Delays::default());
+        assert!(ddc.get_vcp_feature(0x10).unwrap_err().is_retryable());
+        assert_eq!(ddc.inner_ref().last_fault(), Some(fault));
+
+        let attempts = ddc.policy().read_attempts as usize;
+        let (_, backoff) = ddc.into_inner();
+        assert_eq!(backoff.0.len(), attempts - 1, "{:?}", fault);
+    }
+
+    // while occasional ones are recovered from, except padding which
+    // decodes as a valid fragment
+    let mut policy = FaultPolicy::uniform(7, 0.1);
+    policy.pad = 0.0;
+    policy.repeat_fragment = 0.3;
+    let mut ddc = Retry::with_timer(injector(policy), Delays::default());
+    ddc.policy_mut().read_attempts = 10;
+    ddc.policy_mut().fragment_attempts = 10;
+    assert_eq!(ddc.capabilities_string().unwrap(), display().capabilities);
+    assert_eq!(ddc.table_read(0x73).unwrap(), (0..100).collect::<Vec<u8>>());
+    assert_eq!(ddc.get_vcp_feature(0x10).unwrap().value(), 50);
+    assert!(!ddc.into_inner().1 .0.is_empty());
+}
@@ -0,0 +1,84 @@
+use ddc::{
+    features::{Brightness, ColorPreset, Continuous, Feature, InputSource, Mute, PowerMode, Volume},
+    mock::{MockDisplay, MockFeature},
+    Ddc, VcpValue,
+};
+
+/// Checks that every byte survives decoding and encoding, and that only
+/// values not defined by MCCS decode to `other`.
+fn round_trip<F>(values: &[(u8, &str)], other: fn(u8) -> F)
+where
+    F: Feature<Value = F, Setting = F> + Copy + PartialEq + core::fmt::Debug,
+{
+    for value in 0..=0xffu8 {
+        let decoded = F::decode(&VcpValue::from_value(value as u16));
+        assert_eq!(F::encode(decoded), value as u16);
+        let defined = values.iter().any(|&(v, _)| v == value);
+        assert_eq!(decoded == other(value), !defined, "{:#04x}", value);
+    }
+}
+
+#[test]
+fn non_continuous_round_trip() {
+    round_trip(InputSource::VALUES, InputSource::Other);
+    round_trip(PowerMode::VALUES, PowerMode::Other);
+    round_trip(ColorPreset::VALUES, ColorPreset::Other);
+    round_trip(Mute::VALUES, Mute::Other);
+
+    assert_eq!(InputSource::from(0x11), InputSource::Hdmi1);
+    assert_eq!(u8::from(InputSource::DisplayPort1), 0x0f);
+    assert_eq!(InputSource::from(0x1b), InputSource::Other(0x1b));
+    // the high byte is ignored
+    assert_eq!(InputSource::decode(&VcpValue::from_value(0x0111)), InputSource::Hdmi1);
+}
+
+#[test]
+fn normalize() {
+    assert_eq!(InputSource::Other(0x11).normalize(), InputSource::Hdmi1);
+    assert_eq!(InputSource::Other(0x1b).normalize(), InputSource::Other(0x1b));
+    assert_eq!(PowerMode::Other(0x04).normalize(), PowerMode::Off);
+    assert_eq!(Mute::Other(0x01).normalize(), Mute::Muted);
+    assert_eq!(Mute::from(false), Mute::Unmuted);
+}
+
+#[test]
+fn continuous() {
+    let value = Brightness::decode(&VcpValue {
+        ty: 0,
+        mh: 0,
+        ml: 200,
+        sh: 0,
+        sl: 50,
+    });
+    assert_eq!(value, Continuous {
+        value: 50,
+        maximum: 200
+    });
+    assert_eq!(value.percent(), 25);
+    assert_eq!(value.from_percent(50), 100);
+    assert_eq!(value.from_percent(150), 200);
+    assert_eq!(Continuous::default().percent(), 0);
+    assert_eq!(Volume::encode(30), 30);
+}
+
+#[test]
+fn display() {
+    let mut ddc = MockDisplay::new();
+    ddc.set_feature(0x60, MockFeature::NonContinuous {
+        value: 0x0f,
+        maximum: 3,
+    });
+    ddc.set_feature(0x10, MockFeature::Continuous {
+        value: 50,
+        maximum: 100,
+    });
+
+    assert_eq!(ddc.get_feature::<InputSource>().unwrap(), InputSource::DisplayPort1);
+    // `MockDisplay::set_feature` shadows the trait method
+    Ddc::set_feature::<InputSource>(&mut ddc, InputSource::Other(0x11)).unwrap();
+    assert_eq!(ddc.get_feature::<InputSource>().unwrap(), InputSource::Hdmi1);
+
+    let brightness = ddc.get_feature::<Brightness>().unwrap();
+    Ddc::set_feature::<Brightness>(&mut ddc, brightness.from_percent(80)).unwrap();
+    assert_eq!(ddc.get_vcp_feature(0x10).unwrap().value(), 80);
+}
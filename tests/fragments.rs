@@ -0,0 +1,182 @@
+use {
+    core::time::Duration,
+    ddc::{
+        fragments::{Fragments, Limits},
+        mock::{MockDisplay, MockFeature},
+        Ddc, DdcCommandMarker, DdcCommandRaw, DdcCommandRawMarker, DdcHost, DdcTable, Delay, ErrorCode,
+    },
+};
+
+/// Counts the requests made of a `MockDisplay`, corrupting the reply to the
+/// request numbered `fail_at`.
+struct Counted {
+    display: MockDisplay,
+    fail_at: Option<usize>,
+    calls: usize,
+}
+
+impl Counted {
+    fn new(capabilities: &[u8]) -> Self {
+        let mut display = MockDisplay::new();
+        display.capabilities = capabilities.to_vec();
+        Counted {
+            display,
+            fail_at: None,
+            calls: 0,
+        }
+    }
+}
+
+impl DdcHost for Counted {
+    type Error = ErrorCode;
+}
+
+impl DdcCommandRaw for Counted {
+    fn execute_raw<'a>(
+        &mut self,
+        data: &[u8],
+        out: &'a mut [u8],
+        response_delay: Duration,
+    ) -> Result<&'a mut [u8], Self::Error> {
+        let call = self.calls;
+        self.calls += 1;
+        let res = self.display.execute_raw(data, out, response_delay)?;
+        match self.fail_at == Some(call) {
+            true => Err(ErrorCode::InvalidChecksum),
+            false => Ok(res),
+        }
+    }
+}
+
+impl DdcCommandRawMarker for Counted {
+    fn set_sleep_delay(&mut self, _: Delay) {}
+}
+
+impl DdcCommandMarker for Counted {}
+
+fn capabilities(len: usize) -> Vec<u8> {
+    (0..len).map(|i| b'a' + (i % 26) as u8).collect()
+}
+
+#[test]
+fn progress() {
+    let expected = capabilities(100);
+    let mut ddc = Counted::new(&expected);
+    let mut offsets = Vec::new();
+    let mut progress = |offset| offsets.push(offset);
+
+    let mut value = Vec::new();
+    Fragments::capabilities(&mut ddc)
+        .on_progress(&mut progress)
+        .read_to_end(&mut value)
+        .unwrap();
+    assert_eq!(value, expected);
+    // the terminating empty fragment doesn't report progress
+    assert_eq!(offsets, [32, 64, 96, 100]);
+    assert_eq!(ddc.calls, 5);
+}
+
+#[test]
+fn resume() {
+    let expected = capabilities(100);
+    let mut ddc = Counted::new(&expected);
+    ddc.fail_at = Some(2);
+
+    let mut value = Vec::new();
+    let mut fragments = Fragments::capabilities(&mut ddc);
+    assert_eq!(fragments.read_to_end(&mut value), Err(ErrorCode::InvalidChecksum));
+    assert_eq!(value, &expected[..64]);
+    assert_eq!(fragments.offset(), 64);
+    assert!(!fragments.is_done());
+
+    // a failed read stays failed until resumed
+    assert!(fragments.next().is_none());
+    fragments.resume();
+    fragments.read_to_end(&mut value).unwrap();
+    assert!(fragments.is_done());
+    assert_eq!(value, expected);
+    // only the failed fragment was requested again
+    assert_eq!(ddc.calls, 6);
+}
+
+#[test]
+fn resume_buffer() {
+    let expected = capabilities(100);
+    let mut ddc = Counted::new(&expected);
+
+    let mut buf = [0u8; 80];
+    let mut fragments = Fragments::capabilities(&mut ddc);
+    assert_eq!(fragments.read_into(&mut buf), Err(ErrorCode::BufferTooSmall));
+    assert_eq!(&buf[..64], &expected[..64]);
+    // the fragment that didn't fit is requested again
+    assert_eq!(fragments.offset(), 64);
+
+    let mut rest = [0u8; 40];
+    let mut fragments = Fragments::capabilities(&mut ddc).start_at(64);
+    assert_eq!(fragments.read_into(&mut rest), Ok(36));
+    assert_eq!(&rest[..36], &expected[64..]);
+}
+
+#[test]
+fn limits() {
+    // a display that keeps replying is cut off by the default limit
+    let mut ddc = Counted::new(&capabilities(0x1100));
+    assert_eq!(
+        ddc.capabilities_string(),
+        Err(ErrorCode::ReplyTooLong { limit: 0x1000 })
+    );
+    assert_eq!(ddc.calls, 0x1000 / 32 + 1);
+
+    let mut ddc = Counted::new(&capabilities(200));
+    let mut value = Vec::new();
+    let mut fragments = Fragments::capabilities(&mut ddc).limits(Limits {
+        max_len: 100,
+        stop_at_nul: true,
+    });
+    assert_eq!(
+        fragments.read_to_end(&mut value),
+        Err(ErrorCode::ReplyTooLong { limit: 100 })
+    );
+    assert_eq!(value.len(), 96);
+    assert_eq!(fragments.offset(), 96);
+
+    // a value that ends exactly at the limit is complete
+    let mut ddc = Counted::new(&capabilities(96));
+    let mut fragments = Fragments::capabilities(&mut ddc).limits(Limits {
+        max_len: 96,
+        stop_at_nul: true,
+    });
+    let mut value = Vec::new();
+    fragments.read_to_end(&mut value).unwrap();
+    assert_eq!(value.len(), 96);
+}
+
+#[test]
+fn early_nul() {
+    let expected = b"(prot(monitor)vcp(10))".to_vec();
+    let mut caps = expected.clone();
+    caps.push(0);
+    caps.extend_from_slice(&[0xff; 60]);
+
+    // reading stops at the NUL without requesting further fragments
+    let mut ddc = Counted::new(&caps);
+    assert_eq!(ddc.capabilities_string().unwrap(), expected);
+    assert_eq!(ddc.calls, 1);
+
+    let mut ddc = Counted::new(&caps);
+    let mut value = Vec::new();
+    Fragments::capabilities(&mut ddc)
+        .limits(Limits {
+            max_len: 0x1000,
+            stop_at_nul: false,
+        })
+        .read_to_end(&mut value)
+        .unwrap();
+    assert_eq!(value, caps);
+
+    // tables may contain NUL bytes
+    let mut ddc = Counted::new(&[]);
+    let table = vec![1, 0, 2, 0, 3];
+    ddc.display.set_feature(0x73, MockFeature::Table(table.clone()));
+    assert_eq!(ddc.table_read(0x73).unwrap(), table);
+}
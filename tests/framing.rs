@@ -0,0 +1,92 @@
+use {
+    core::time::Duration,
+    ddc::{DdcCommand, DdcCommandRaw, DdcCommandRawMarker, DdcHost, Delay, ErrorCode},
+};
+
+/// A backend that never talks to a display, for the provided framing helpers.
+struct Framing;
+
+impl DdcHost for Framing {
+    type Error = ErrorCode;
+}
+
+impl DdcCommandRaw for Framing {
+    fn execute_raw<'a>(&mut self, _: &[u8], _: &'a mut [u8], _: Duration) -> Result<&'a mut [u8], ErrorCode> {
+        unreachable!()
+    }
+}
+
+impl DdcCommandRawMarker for Framing {
+    fn set_sleep_delay(&mut self, _: Delay) {}
+}
+
+/// Frames a display to host reply with a valid checksum.
+fn reply(payload: &[u8]) -> Vec<u8> {
+    let mut packet = vec![0x6e, 0x80 | payload.len() as u8];
+    packet.extend_from_slice(payload);
+    packet.push(packet.iter().fold(0x50, |sum, v| sum ^ v));
+    packet
+}
+
+#[test]
+fn encode_command() {
+    let mut packet = [0u8; 5];
+    assert_eq!(Framing::encode_command(&[0x01, 0x10], &mut packet), &[
+        0x51, 0x82, 0x01, 0x10, 0xac
+    ]);
+}
+
+#[test]
+fn vcp_reply() {
+    let mut packet = vec![0x6e, 0x88, 0x02, 0x00, 0x10, 0x00, 0x00, 0x64, 0x00, 0x32, 0xf2];
+    assert_eq!(reply(&packet[2..10]), packet);
+    assert_eq!(Framing::decode_response(&mut packet).unwrap().unwrap(), &[
+        0x02, 0x00, 0x10, 0x00, 0x00, 0x64, 0x00, 0x32
+    ]);
+}
+
+#[test]
+fn null_message() {
+    let mut packet = reply(&[]);
+    assert_eq!(packet, [0x6e, 0x80, 0xbe]);
+    assert_eq!(Framing::decode_response(&mut packet), Ok(None));
+}
+
+#[test]
+fn trailing_bytes() {
+    // reads are often longer than the reply
+    let mut packet = reply(&[0xe3, 0x00, 0x00, b'(']);
+    packet.extend_from_slice(&[0xff; 8]);
+    assert_eq!(Framing::decode_response(&mut packet).unwrap().unwrap(), &[
+        0xe3, 0x00, 0x00, b'('
+    ]);
+}
+
+#[test]
+fn invalid_packets() {
+    assert_eq!(Framing::decode_response(&mut []), Err(ErrorCode::InvalidLength));
+    assert_eq!(
+        Framing::decode_response(&mut [0x6e, 0x80]),
+        Err(ErrorCode::InvalidLength)
+    );
+
+    let mut packet = reply(&[0x02, 0x00]);
+    packet[0] = 0x6f;
+    assert_eq!(Framing::decode_response(&mut packet), Err(ErrorCode::InvalidData));
+
+    // the length byte must have its high bit set
+    let mut packet = reply(&[0x02, 0x00]);
+    packet[1] = 0x02;
+    assert_eq!(Framing::decode_response(&mut packet), Err(ErrorCode::InvalidLength));
+
+    let mut packet = reply(&[0x02, 0x00, 0x10]);
+    packet.truncate(4);
+    assert_eq!(
+        Framing::decode_response(&mut packet),
+        Err(ErrorCode::LengthMismatch { expected: 6, actual: 4 })
+    );
+
+    let mut packet = reply(&[0x02, 0x00, 0x10]);
+    *packet.last_mut().unwrap() ^= 0x01;
+    assert_eq!(Framing::decode_response(&mut packet), Err(ErrorCode::InvalidChecksum));
+}
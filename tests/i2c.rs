@@ -0,0 +1,125 @@
+use {
+    core::time::Duration,
+    ddc::{
+        i2c::{I2cBus, I2cDdc, I2cError},
+        Eddc, Edid, Timer,
+    },
+};
+
+#[derive(Debug, PartialEq, Eq)]
+enum Transfer {
+    Write(u16, Vec<u8>),
+    Read(u16, usize),
+    WriteRead(u16, Vec<u8>, usize),
+    WriteWriteRead(u16, Vec<u8>, u16, Vec<u8>, usize),
+}
+
+/// Records the transfers made on the bus, reading back zeroes.
+#[derive(Default)]
+struct Bus(Vec<Transfer>);
+
+impl I2cBus for Bus {
+    type Error = ();
+
+    fn write(&mut self, address: u16, data: &[u8]) -> Result<(), Self::Error> {
+        self.0.push(Transfer::Write(address, data.to_vec()));
+        Ok(())
+    }
+
+    fn read(&mut self, address: u16, data: &mut [u8]) -> Result<usize, Self::Error> {
+        self.0.push(Transfer::Read(address, data.len()));
+        Ok(data.len())
+    }
+
+    fn write_read(&mut self, address: u16, data: &[u8], out: &mut [u8]) -> Result<usize, Self::Error> {
+        self.0.push(Transfer::WriteRead(address, data.to_vec(), out.len()));
+        Ok(out.len())
+    }
+
+    fn write_write_read(
+        &mut self,
+        first_address: u16,
+        first: &[u8],
+        address: u16,
+        data: &[u8],
+        out: &mut [u8],
+    ) -> Result<usize, I2cError<Self::Error>> {
+        self.0.push(Transfer::WriteWriteRead(
+            first_address,
+            first.to_vec(),
+            address,
+            data.to_vec(),
+            out.len(),
+        ));
+        Ok(out.len())
+    }
+}
+
+struct NoDelay;
+
+impl Timer for NoDelay {
+    fn sleep(&mut self, _: Duration) {}
+}
+
+#[test]
+fn eddc_single_transfer() {
+    let mut ddc = I2cDdc::with_timer(Bus::default(), NoDelay);
+
+    let mut data = [0u8; 0x80];
+    assert_eq!(ddc.read_eddc_edid(1, 0x80, &mut data), Ok(0x80));
+    assert_eq!(ddc.inner_ref().0, [Transfer::WriteWriteRead(
+        0x30,
+        vec![0x01],
+        0x50,
+        vec![0x80],
+        0x80
+    )]);
+}
+
+#[test]
+fn eddc_first_segment() {
+    let mut ddc = I2cDdc::with_timer(Bus::default(), NoDelay);
+
+    // the segment pointer isn't written for the first segment
+    let mut data = [0u8; 0x80];
+    assert_eq!(ddc.read_eddc_edid(0, 0x80, &mut data), Ok(0x80));
+    assert_eq!(ddc.read_edid(0, &mut data), Ok(0x80));
+    assert_eq!(ddc.inner_ref().0, [
+        Transfer::WriteRead(0x50, vec![0x80], 0x80),
+        Transfer::WriteRead(0x50, vec![0x00], 0x80),
+    ]);
+}
+
+#[test]
+fn default_write_write_read() {
+    struct Split(Bus);
+
+    impl I2cBus for Split {
+        type Error = ();
+
+        fn write(&mut self, address: u16, data: &[u8]) -> Result<(), Self::Error> {
+            self.0.write(address, data)
+        }
+
+        fn read(&mut self, address: u16, data: &mut [u8]) -> Result<usize, Self::Error> {
+            self.0.read(address, data)
+        }
+    }
+
+    let mut bus = Split(Bus::default());
+    assert_eq!(bus.write_write_read(0x50, &[1], 0x50, &[0], &mut [0u8; 4]), Ok(4));
+    assert_eq!(bus.0 .0, [
+        Transfer::Write(0x50, vec![1]),
+        Transfer::Write(0x50, vec![0]),
+        Transfer::Read(0x50, 4),
+    ]);
+
+    // the segment pointer would be reset between separate transfers
+    let mut bus = Split(Bus::default());
+    let mut ddc = I2cDdc::with_timer(&mut bus, NoDelay);
+    assert_eq!(
+        ddc.read_eddc_edid(1, 0x80, &mut [0u8; 0x80]),
+        Err(I2cError::Unsupported)
+    );
+    assert_eq!(bus.0 .0, []);
+}
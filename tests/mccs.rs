@@ -0,0 +1,29 @@
+use ddc::{VcpValue, VcpValueType};
+
+#[test]
+fn value_conversion() {
+    let value = VcpValue {
+        ty: 1,
+        mh: 0x01,
+        ml: 0x02,
+        sh: 0x03,
+        sl: 0x04,
+    };
+    let converted = mccs::Value::from(value);
+    assert_eq!(
+        (converted.ty, converted.maximum(), converted.value()),
+        (1, 0x0102, 0x0304)
+    );
+    assert_eq!(VcpValue::from(converted), value);
+}
+
+#[test]
+fn value_type_conversion() {
+    for ty in [VcpValueType::SetParameter, VcpValueType::Momentary] {
+        assert_eq!(VcpValueType::from(mccs::ValueType::from(ty)), ty);
+    }
+    assert!(matches!(
+        mccs::ValueType::from(VcpValue::from_value(0).ty().unwrap()),
+        mccs::ValueType::SetParameter
+    ));
+}
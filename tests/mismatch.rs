@@ -0,0 +1,129 @@
+use {
+    core::time::Duration,
+    ddc::{
+        commands::{CapabilitiesRequest, GetVcpFeature, TableRead},
+        Command, Ddc, DdcCommandMarker, DdcCommandRaw, DdcCommandRawMarker, DdcHost, DdcTable, Delay, ErrorCode,
+    },
+};
+
+/// Answers every request with the same reply payload, as a display would
+/// when a reply to an earlier request is read back late.
+struct Stale {
+    reply: Vec<u8>,
+    delay: Option<Delay>,
+}
+
+impl Stale {
+    fn new(reply: &[u8]) -> Self {
+        Stale {
+            reply: reply.to_vec(),
+            delay: None,
+        }
+    }
+}
+
+impl DdcHost for Stale {
+    type Error = ErrorCode;
+}
+
+impl DdcCommandRaw for Stale {
+    fn execute_raw<'a>(&mut self, _: &[u8], out: &'a mut [u8], _: Duration) -> Result<&'a mut [u8], ErrorCode> {
+        out[..self.reply.len()].copy_from_slice(&self.reply);
+        Ok(&mut out[..self.reply.len()])
+    }
+}
+
+impl DdcCommandRawMarker for Stale {
+    fn set_sleep_delay(&mut self, delay: Delay) {
+        self.delay = Some(delay);
+    }
+}
+
+impl DdcCommandMarker for Stale {}
+
+const BRIGHTNESS: [u8; 8] = [0x02, 0x00, 0x10, 0x00, 0x00, 0x64, 0x00, 0x32];
+
+#[test]
+fn wrong_feature() {
+    assert_eq!(
+        GetVcpFeature::new(0x12).decode(&BRIGHTNESS),
+        Err(ErrorCode::WrongFeature {
+            expected: 0x12,
+            actual: 0x10,
+        })
+    );
+    assert_eq!(GetVcpFeature::new(0x10).decode(&BRIGHTNESS).unwrap().value(), 0x32);
+
+    // an unsupported feature is reported for the feature the display names
+    let mut unsupported = BRIGHTNESS;
+    unsupported[1] = 0x01;
+    assert_eq!(
+        GetVcpFeature::new(0x10).decode(&unsupported),
+        Err(ErrorCode::UnsupportedFeature(0x10))
+    );
+
+    let mut ddc = Stale::new(&BRIGHTNESS);
+    assert_eq!(
+        ddc.get_vcp_feature(0x12),
+        Err(ErrorCode::WrongFeature {
+            expected: 0x12,
+            actual: 0x10,
+        })
+    );
+    // the failed command delay applies before the next attempt
+    assert!(ddc.delay.as_ref().unwrap().remaining() > Duration::from_millis(30));
+    assert_eq!(ddc.get_vcp_feature(0x10).unwrap().value(), 0x32);
+}
+
+#[test]
+fn offset_mismatch() {
+    let fragment = [0xe3, 0x00, 0x20, b'v', b'c', b'p'];
+    assert_eq!(
+        CapabilitiesRequest::new(0x40).decode(&fragment),
+        Err(ErrorCode::OffsetMismatch {
+            expected: 0x40,
+            actual: 0x20,
+        })
+    );
+    assert_eq!(
+        CapabilitiesRequest::new(0x20).decode(&fragment).unwrap().bytes(),
+        b"vcp"
+    );
+
+    let table = [0xe4, 0x01, 0x00, 0xaa, 0xbb];
+    assert_eq!(
+        TableRead::new(0x73, 0x0000).decode(&table),
+        Err(ErrorCode::OffsetMismatch {
+            expected: 0x0000,
+            actual: 0x0100,
+        })
+    );
+    assert_eq!(TableRead::new(0x73, 0x0100).decode(&table).unwrap().bytes(), &[
+        0xaa, 0xbb
+    ]);
+
+    // the display keeps repeating the first fragment
+    let mut ddc = Stale::new(&[0xe3, 0x00, 0x00, b'(', b'v', b'c', b'p']);
+    assert_eq!(
+        ddc.capabilities_string(),
+        Err(ErrorCode::OffsetMismatch { expected: 4, actual: 0 })
+    );
+
+    let mut ddc = Stale::new(&[0xe4, 0x00, 0x08, 0xaa]);
+    assert_eq!(
+        ddc.table_read(0x73),
+        Err(ErrorCode::OffsetMismatch { expected: 0, actual: 8 })
+    );
+}
+
+#[test]
+fn opcode_mismatch() {
+    let mut ddc = Stale::new(&BRIGHTNESS);
+    assert_eq!(
+        ddc.table_read(0x73),
+        Err(ErrorCode::OpcodeMismatch {
+            expected: 0xe4,
+            actual: 0x02,
+        })
+    );
+}
@@ -0,0 +1,179 @@
+use ddc::{
+    commands::{CommandResult, TableResponse},
+    mock::{MockDisplay, MockFeature},
+    Ddc, DdcTable, Eddc, Edid, ErrorCode,
+};
+
+const CAPABILITIES: &[u8] =
+    b"(prot(monitor)type(lcd)model(TEST)cmds(01 02 03 0C E3 F3)vcp(02 04 10 12 14(05 08 0B) 60(0F 11 12) D6(01 04))mccs_ver(2.1))";
+
+fn display() -> MockDisplay {
+    let mut ddc = MockDisplay::new();
+    ddc.set_feature(0x10, MockFeature::Continuous {
+        value: 50,
+        maximum: 100,
+    });
+    ddc.set_feature(0x60, MockFeature::NonContinuous {
+        value: 0x0f,
+        maximum: 3,
+    });
+    ddc.set_feature(0x73, MockFeature::Table((0..70).collect()));
+    ddc.capabilities = CAPABILITIES.to_vec();
+    ddc
+}
+
+/// An EDID with `extensions` extension blocks and valid checksums.
+fn edid(extensions: u8) -> Vec<u8> {
+    let mut edid = vec![0u8; 128 * (1 + extensions as usize)];
+    edid[..8].copy_from_slice(&[0x00, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x00]);
+    edid[126] = extensions;
+    for (i, block) in edid.chunks_mut(128).enumerate() {
+        if i > 0 {
+            block[0] = 0x02;
+            block[1] = i as u8;
+        }
+        let sum = block[..127].iter().fold(0u8, |sum, &b| sum.wrapping_add(b));
+        block[127] = sum.wrapping_neg();
+    }
+    edid
+}
+
+#[test]
+fn vcp_feature() {
+    let mut ddc = display();
+
+    let value = ddc.get_vcp_feature(0x10).unwrap();
+    assert_eq!((value.value(), value.maximum()), (50, 100));
+
+    ddc.set_vcp_feature(0x10, 70).unwrap();
+    assert_eq!(ddc.get_vcp_feature(0x10).unwrap().value(), 70);
+
+    // out of range for a continuous feature
+    ddc.set_vcp_feature(0x10, 101).unwrap();
+    assert_eq!(ddc.get_vcp_feature(0x10).unwrap().value(), 70);
+
+    let value = ddc.get_vcp_feature(0x60).unwrap();
+    assert_eq!((value.value(), value.maximum()), (0x0f, 3));
+    ddc.set_vcp_feature(0x60, 0x11).unwrap();
+    assert_eq!(ddc.get_vcp_feature(0x60).unwrap().value(), 0x11);
+}
+
+#[test]
+fn unsupported_feature() {
+    let mut ddc = display();
+
+    assert_eq!(ddc.get_vcp_feature(0x99), Err(ErrorCode::UnsupportedFeature(0x99)));
+    // table reads have no unsupported flag, so the display replies with the null message
+    assert_eq!(ddc.table_read(0x99), Err(ErrorCode::NullResponse));
+    // setting an unknown feature has no reply to report the failure with
+    ddc.set_vcp_feature(0x99, 1).unwrap();
+    assert!(!ddc.features.contains_key(&0x99));
+}
+
+#[test]
+fn save_and_timing() {
+    let mut ddc = display();
+    ddc.timing.horizontal_frequency = 0x1234;
+
+    ddc.save_current_settings().unwrap();
+    ddc.save_current_settings().unwrap();
+    assert_eq!(ddc.save_count, 2);
+    assert_eq!(ddc.get_timing_report().unwrap().horizontal_frequency, 0x1234);
+}
+
+#[test]
+fn identification_and_self_test() {
+    let mut ddc = display();
+    ddc.identification = b"TEST 1234".to_vec();
+    ddc.self_test = vec![0x00];
+
+    assert_eq!(ddc.identification().unwrap().bytes(), b"TEST 1234");
+    assert_eq!(ddc.self_test().unwrap().bytes(), &[0x00]);
+
+    ddc.enable_application_report(true).unwrap();
+    assert!(ddc.application_report);
+    ddc.enable_application_report(false).unwrap();
+    assert!(!ddc.application_report);
+}
+
+#[test]
+fn capabilities_fragments() {
+    let mut ddc = display();
+
+    // spans several 32 byte fragments
+    assert!(CAPABILITIES.len() > 3 * 32);
+    assert_eq!(ddc.capabilities_string().unwrap(), CAPABILITIES);
+
+    // a multiple of the fragment size ends with an empty fragment
+    ddc.capabilities = vec![b'x'; 64];
+    assert_eq!(ddc.capabilities_string().unwrap(), vec![b'x'; 64]);
+
+    ddc.capabilities.clear();
+    assert_eq!(ddc.capabilities_string().unwrap(), Vec::<u8>::new());
+}
+
+#[test]
+fn capabilities() {
+    let mut ddc = display();
+
+    let caps = ddc.capabilities().unwrap();
+    assert_eq!(caps.model.as_deref(), Some("TEST"));
+    assert_eq!(caps.vcp_features[&0x60].values, [0x0f, 0x11, 0x12]);
+
+    ddc.capabilities = b"(vcp(10 zz))".to_vec();
+    assert!(matches!(ddc.capabilities(), Err(ErrorCode::InvalidCapabilities(e)) if e.position == 8));
+}
+
+#[test]
+fn table() {
+    let mut ddc = display();
+
+    assert_eq!(ddc.table_read(0x73).unwrap(), (0..70).collect::<Vec<u8>>());
+
+    // spans two fragments
+    ddc.table_write(0x73, 2, &[0xaa; 40]).unwrap();
+    let table = ddc.table_read(0x73).unwrap();
+    assert_eq!(table.len(), 70);
+    assert_eq!(&table[..2], &[0, 1]);
+    assert_eq!(&table[2..42], &[0xaa; 40][..]);
+    assert_eq!(table[42], 42);
+
+    // writes past the end grow the table
+    ddc.table_write(0x73, 70, &[0xbb; 26]).unwrap();
+    assert_eq!(ddc.table_read(0x73).unwrap().len(), 96);
+
+    ddc.set_feature(0x74, MockFeature::Table(Vec::new()));
+    assert_eq!(ddc.table_read(0x74).unwrap(), Vec::<u8>::new());
+}
+
+#[test]
+fn table_empty_fragment() {
+    // opcode and offset without any data terminates a table read
+    let response = TableResponse::decode(&[0xe4, 0x00, 0x40]).unwrap();
+    assert_eq!(response.offset, 0x40);
+    assert_eq!(response.bytes(), &[][..]);
+
+    assert_eq!(TableResponse::decode(&[0xe4, 0x00]), Err(ErrorCode::InvalidLength));
+}
+
+#[test]
+fn edid_segments() {
+    let mut ddc = display();
+    ddc.edid = edid(3);
+
+    let mut data = [0u8; 0x80];
+    assert_eq!(ddc.read_edid(0, &mut data).unwrap(), 0x80);
+    assert_eq!(&data[..], &ddc.edid[..0x80]);
+
+    assert_eq!(ddc.read_eddc_edid(1, 0x80, &mut data).unwrap(), 0x80);
+    assert_eq!(&data[..], &ddc.edid[0x180..0x200]);
+
+    // reads don't cross segment boundaries
+    let mut data = [0u8; 0x100];
+    assert_eq!(ddc.read_eddc_edid(0, 0x80, &mut data).unwrap(), 0x80);
+
+    assert_eq!(ddc.read_eddc_edid(2, 0, &mut data), Err(ErrorCode::InvalidOffset));
+
+    assert_eq!(ddc.read_edid_blocks().unwrap(), &ddc.edid[..0x100]);
+    assert_eq!(ddc.read_full_edid().unwrap(), ddc.edid);
+}
@@ -0,0 +1,284 @@
+use ddc::{
+    responder::{decode_request, encode_reply, respond, Handler},
+    ErrorCode, FeatureCode, TimingMessage, VcpValue,
+};
+
+/// A display that supports every command, recording the commands that have
+/// no reply.
+#[derive(Default)]
+struct Display {
+    brightness: u16,
+    table: Vec<u8>,
+    saved: bool,
+    application_report: Option<bool>,
+    table_writes: Vec<(FeatureCode, u16, Vec<u8>)>,
+}
+
+impl Handler for Display {
+    fn get_vcp_feature(&mut self, code: FeatureCode) -> Option<VcpValue> {
+        match code {
+            0x10 => Some(VcpValue {
+                ty: 0,
+                mh: 0,
+                ml: 100,
+                sh: (self.brightness >> 8) as u8,
+                sl: self.brightness as u8,
+            }),
+            _ => None,
+        }
+    }
+
+    fn set_vcp_feature(&mut self, code: FeatureCode, value: u16) {
+        if code == 0x10 {
+            self.brightness = value;
+        }
+    }
+
+    fn save_current_settings(&mut self) {
+        self.saved = true;
+    }
+
+    fn capabilities(&mut self, offset: u16, buf: &mut [u8]) -> usize {
+        let caps = &b"(vcp(10 73))"[offset as usize..];
+        let len = caps.len().min(buf.len());
+        buf[..len].copy_from_slice(&caps[..len]);
+        len
+    }
+
+    fn table_read(&mut self, code: FeatureCode, offset: u16, buf: &mut [u8]) -> Option<usize> {
+        match code {
+            0x73 => {
+                let data = &self.table[offset as usize..];
+                let len = data.len().min(buf.len());
+                buf[..len].copy_from_slice(&data[..len]);
+                Some(len)
+            },
+            _ => None,
+        }
+    }
+
+    fn table_write(&mut self, code: FeatureCode, offset: u16, data: &[u8]) {
+        self.table_writes.push((code, offset, data.to_vec()));
+    }
+
+    fn timing_report(&mut self) -> Option<TimingMessage> {
+        Some(TimingMessage {
+            timing_status: 0x02,
+            horizontal_frequency: 0x1234,
+            vertical_frequency: 0x0258,
+        })
+    }
+
+    fn identification(&mut self, buf: &mut [u8]) -> Option<usize> {
+        buf[..4].copy_from_slice(b"TEST");
+        Some(4)
+    }
+
+    fn self_test(&mut self, buf: &mut [u8]) -> Option<usize> {
+        buf[0] = 0x00;
+        Some(1)
+    }
+
+    fn enable_application_report(&mut self, enable: bool) {
+        self.application_report = Some(enable);
+    }
+}
+
+/// A display that only implements the required VCP feature methods.
+struct Minimal;
+
+impl Handler for Minimal {
+    fn get_vcp_feature(&mut self, _: FeatureCode) -> Option<VcpValue> {
+        None
+    }
+
+    fn set_vcp_feature(&mut self, _: FeatureCode, _: u16) {}
+}
+
+/// Frames a host to display request with a valid checksum.
+fn request(payload: &[u8]) -> Vec<u8> {
+    let mut packet = vec![0x51, 0x80 | payload.len() as u8];
+    packet.extend_from_slice(payload);
+    packet.push(packet.iter().fold(0x6e, |sum, v| sum ^ v));
+    packet
+}
+
+/// Frames a display to host reply with a valid checksum.
+fn reply(payload: &[u8]) -> Vec<u8> {
+    let mut packet = vec![0x6e, 0x80 | payload.len() as u8];
+    packet.extend_from_slice(payload);
+    packet.push(packet.iter().fold(0x50, |sum, v| sum ^ v));
+    packet
+}
+
+fn respond_to<H: Handler>(handler: &mut H, payload: &[u8]) -> Vec<u8> {
+    let mut out = [0u8; 0x7f + 3];
+    respond(handler, &request(payload), &mut out).unwrap().to_vec()
+}
+
+#[test]
+fn get_vcp_feature() {
+    let mut display = Display {
+        brightness: 50,
+        ..Default::default()
+    };
+    assert_eq!(
+        respond_to(&mut display, &[0x01, 0x10]),
+        reply(&[0x02, 0x00, 0x10, 0x00, 0x00, 100, 0x00, 50])
+    );
+    // unsupported features are reported with the result code
+    assert_eq!(
+        respond_to(&mut display, &[0x01, 0x99]),
+        reply(&[0x02, 0x01, 0x99, 0x00, 0x00, 0x00, 0x00, 0x00])
+    );
+    assert_eq!(
+        respond_to(&mut Minimal, &[0x01, 0x10]),
+        reply(&[0x02, 0x01, 0x10, 0x00, 0x00, 0x00, 0x00, 0x00])
+    );
+}
+
+#[test]
+fn set_vcp_feature() {
+    let mut display = Display::default();
+    assert_eq!(respond_to(&mut display, &[0x03, 0x10, 0x01, 0x02]), []);
+    assert_eq!(display.brightness, 0x0102);
+}
+
+#[test]
+fn timing_report() {
+    assert_eq!(
+        respond_to(&mut Display::default(), &[0x07]),
+        reply(&[0x4e, 0x02, 0x12, 0x34, 0x02, 0x58])
+    );
+    assert_eq!(respond_to(&mut Minimal, &[0x07]), reply(&[]));
+}
+
+#[test]
+fn save_current_settings() {
+    let mut display = Display::default();
+    assert_eq!(respond_to(&mut display, &[0x0c]), []);
+    assert!(display.saved);
+    assert_eq!(respond_to(&mut Minimal, &[0x0c]), []);
+}
+
+#[test]
+fn self_test() {
+    assert_eq!(respond_to(&mut Display::default(), &[0xb1]), reply(&[0xa1, 0x00]));
+    assert_eq!(respond_to(&mut Minimal, &[0xb1]), reply(&[]));
+}
+
+#[test]
+fn table_read() {
+    let mut display = Display {
+        table: (0..40).collect(),
+        ..Default::default()
+    };
+
+    let mut expected = vec![0xe4, 0x00, 0x00];
+    expected.extend(0..32);
+    assert_eq!(respond_to(&mut display, &[0xe2, 0x73, 0x00, 0x00]), reply(&expected));
+
+    let mut expected = vec![0xe4, 0x00, 0x20];
+    expected.extend(32..40);
+    assert_eq!(respond_to(&mut display, &[0xe2, 0x73, 0x00, 0x20]), reply(&expected));
+
+    // the end of the table is an empty fragment
+    assert_eq!(
+        respond_to(&mut display, &[0xe2, 0x73, 0x00, 0x28]),
+        reply(&[0xe4, 0x00, 0x28])
+    );
+    // tables have no unsupported flag
+    assert_eq!(respond_to(&mut display, &[0xe2, 0x74, 0x00, 0x00]), reply(&[]));
+    assert_eq!(respond_to(&mut Minimal, &[0xe2, 0x73, 0x00, 0x00]), reply(&[]));
+}
+
+#[test]
+fn table_write() {
+    let mut display = Display::default();
+    assert_eq!(respond_to(&mut display, &[0xe7, 0x73, 0x01, 0x02, 0xaa, 0xbb]), []);
+    assert_eq!(respond_to(&mut display, &[0xe7, 0x73, 0x00, 0x00]), []);
+    assert_eq!(display.table_writes, [
+        (0x73, 0x0102, vec![0xaa, 0xbb]),
+        (0x73, 0x0000, vec![])
+    ]);
+}
+
+#[test]
+fn identification() {
+    assert_eq!(
+        respond_to(&mut Display::default(), &[0xf1]),
+        reply(&[0xe1, b'T', b'E', b'S', b'T'])
+    );
+    assert_eq!(respond_to(&mut Minimal, &[0xf1]), reply(&[]));
+}
+
+#[test]
+fn capabilities() {
+    let mut display = Display::default();
+    let mut expected = vec![0xe3, 0x00, 0x02];
+    expected.extend_from_slice(b"cp(10 73))");
+    assert_eq!(respond_to(&mut display, &[0xf3, 0x00, 0x02]), reply(&expected));
+    assert_eq!(
+        respond_to(&mut display, &[0xf3, 0x00, 0x0c]),
+        reply(&[0xe3, 0x00, 0x0c])
+    );
+    // without a capabilities string every offset is the end
+    assert_eq!(
+        respond_to(&mut Minimal, &[0xf3, 0x00, 0x00]),
+        reply(&[0xe3, 0x00, 0x00])
+    );
+}
+
+#[test]
+fn enable_application_report() {
+    let mut display = Display::default();
+    assert_eq!(respond_to(&mut display, &[0xf5, 0x01]), []);
+    assert_eq!(display.application_report, Some(true));
+    assert_eq!(respond_to(&mut display, &[0xf5, 0x00]), []);
+    assert_eq!(display.application_report, Some(false));
+}
+
+#[test]
+fn unrecognized() {
+    let mut display = Display::default();
+    assert_eq!(respond_to(&mut display, &[0x99]), reply(&[]));
+    // known opcodes with the wrong length
+    assert_eq!(respond_to(&mut display, &[0x01]), reply(&[]));
+    assert_eq!(respond_to(&mut display, &[0x03, 0x10, 0x01]), reply(&[]));
+    assert_eq!(respond_to(&mut display, &[0xf3, 0x00]), reply(&[]));
+    assert_eq!(display.brightness, 0);
+}
+
+#[test]
+fn framing() {
+    let mut out = [0u8; 0x7f + 3];
+    assert_eq!(decode_request(&request(&[0x01, 0x10])), Ok(&[0x01, 0x10][..]));
+
+    let mut packet = request(&[0x01, 0x10]);
+    *packet.last_mut().unwrap() ^= 1;
+    assert_eq!(
+        respond(&mut Minimal, &packet, &mut out),
+        Err(ErrorCode::InvalidChecksum)
+    );
+
+    let mut packet = request(&[0x01, 0x10]);
+    packet[0] = 0x50;
+    assert_eq!(respond(&mut Minimal, &packet, &mut out), Err(ErrorCode::InvalidData));
+
+    let mut packet = request(&[0x01, 0x10]);
+    packet[1] = 0x02;
+    assert_eq!(respond(&mut Minimal, &packet, &mut out), Err(ErrorCode::InvalidLength));
+
+    let packet = request(&[0x01, 0x10]);
+    assert_eq!(
+        respond(&mut Minimal, &packet[..4], &mut out),
+        Err(ErrorCode::LengthMismatch { expected: 5, actual: 4 })
+    );
+
+    // the reply must fit the caller's buffer
+    assert_eq!(
+        respond(&mut Minimal, &request(&[0x01, 0x10]), &mut [0u8; 8]),
+        Err(ErrorCode::BufferTooSmall)
+    );
+    assert_eq!(encode_reply(&[0u8; 0x80], &mut out), Err(ErrorCode::InvalidLength));
+}
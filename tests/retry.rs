@@ -0,0 +1,215 @@
+use {
+    core::time::Duration,
+    ddc::{
+        commands::Command,
+        fault::{FaultInjector, FaultPolicy},
+        mock::{MockDisplay, MockFeature},
+        retry::{CommandClass, Retry, RetryPolicy, RetryableError},
+        Ddc, DdcCommand, DdcCommandRaw, DdcCommandRawMarker, DdcHost, Delay, ErrorCode, Timer,
+    },
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Error {
+    Ddc(ErrorCode),
+    Transport,
+}
+
+impl From<ErrorCode> for Error {
+    fn from(e: ErrorCode) -> Self {
+        Error::Ddc(e)
+    }
+}
+
+impl RetryableError for Error {
+    fn error_code(&self) -> Option<&ErrorCode> {
+        match *self {
+            Error::Ddc(ref e) => Some(e),
+            Error::Transport => None,
+        }
+    }
+}
+
+/// Reports a transport error after the display has handled a command, as
+/// when the bus fails after the request was written.
+struct Flaky {
+    display: MockDisplay,
+    failures: Vec<bool>,
+    calls: usize,
+}
+
+impl Flaky {
+    fn new(failures: &[bool]) -> Self {
+        let mut display = MockDisplay::new();
+        display.set_feature(0x10, MockFeature::Continuous {
+            value: 50,
+            maximum: 100,
+        });
+        Flaky {
+            display,
+            failures: failures.to_vec(),
+            calls: 0,
+        }
+    }
+}
+
+impl DdcHost for Flaky {
+    type Error = Error;
+}
+
+impl DdcCommandRaw for Flaky {
+    fn execute_raw<'a>(
+        &mut self,
+        data: &[u8],
+        out: &'a mut [u8],
+        response_delay: Duration,
+    ) -> Result<&'a mut [u8], Self::Error> {
+        let fail = self.failures.get(self.calls).cloned().unwrap_or(false);
+        self.calls += 1;
+        let res = self.display.execute_raw(data, out, response_delay)?;
+        match fail {
+            true => Err(Error::Transport),
+            false => Ok(res),
+        }
+    }
+}
+
+impl DdcCommandRawMarker for Flaky {
+    fn set_sleep_delay(&mut self, _: Delay) {}
+}
+
+/// Records backoff delays instead of sleeping.
+#[derive(Default)]
+struct Backoff(Vec<Duration>);
+
+impl Timer for Backoff {
+    fn sleep(&mut self, duration: Duration) {
+        self.0.push(duration)
+    }
+}
+
+#[test]
+fn default_policy() {
+    let policy = RetryPolicy::default();
+    assert_eq!(policy.attempts(CommandClass::Write), 1);
+    assert!(policy.attempts(CommandClass::Read) > 1);
+    assert!(policy.attempts(CommandClass::Fragment) > 1);
+
+    assert_eq!(CommandClass::from_opcode(0x03), CommandClass::Write);
+    assert_eq!(CommandClass::from_opcode(0x0c), CommandClass::Write);
+    assert_eq!(CommandClass::from_opcode(0xf3), CommandClass::Fragment);
+    assert_eq!(CommandClass::from_opcode(0x01), CommandClass::Read);
+
+    assert!(policy.is_retryable(&Error::Transport));
+    assert!(policy.is_retryable(&Error::Ddc(ErrorCode::InvalidChecksum)));
+    assert!(!policy.is_retryable(&Error::Ddc(ErrorCode::UnsupportedFeature(0x10))));
+}
+
+#[test]
+fn writes_apply_once() {
+    // the write reached the display before the transport failed
+    let mut ddc = Retry::with_timer(Flaky::new(&[true]), Backoff::default());
+    assert_eq!(ddc.save_current_settings(), Err(Error::Transport));
+    assert_eq!(ddc.inner_ref().display.save_count, 1);
+    assert!(ddc.into_inner().1 .0.is_empty());
+
+    // which is why retrying writes must be opted into
+    let mut ddc = Retry::with_timer(Flaky::new(&[true]), Backoff::default());
+    ddc.policy_mut().write_attempts = 2;
+    assert_eq!(ddc.save_current_settings(), Ok(()));
+    assert_eq!(ddc.inner_ref().display.save_count, 2);
+}
+
+#[test]
+fn empty_command() {
+    // a command without an opcode can't be classified, so it isn't retried
+    struct Empty;
+
+    impl Command for Empty {
+        type Buffer = [u8; 1];
+        type Ok = ();
+
+        const DELAY_COMMAND_MS: u64 = 0;
+        const DELAY_RESPONSE_MS: u64 = 0;
+        const MAX_LEN: usize = 1;
+        const MIN_LEN: usize = 0;
+
+        fn len(&self) -> usize {
+            0
+        }
+
+        fn encode(&self, _: &mut [u8]) -> Result<usize, ErrorCode> {
+            Ok(0)
+        }
+    }
+
+    let mut ddc = Retry::with_timer(Flaky::new(&[true]), Backoff::default());
+    assert!(ddc.execute(Empty).is_err());
+    assert_eq!(ddc.inner_ref().calls, 1);
+}
+
+#[test]
+fn reads_retry() {
+    let mut ddc = Retry::with_timer(Flaky::new(&[true, true]), Backoff::default());
+    assert_eq!(ddc.get_vcp_feature(0x10).unwrap().value(), 50);
+    assert_eq!(ddc.inner_ref().calls, 3);
+    assert_eq!(ddc.into_inner().1 .0, [
+        Duration::from_millis(10),
+        Duration::from_millis(20)
+    ]);
+
+    let mut ddc = Retry::with_timer(Flaky::new(&[true; 3]), Backoff::default());
+    assert_eq!(ddc.get_vcp_feature(0x10), Err(Error::Transport));
+    assert_eq!(ddc.inner_ref().calls, 3);
+
+    ddc.set_policy(RetryPolicy::never());
+    ddc.inner_mut().calls = 0;
+    ddc.inner_mut().failures = vec![true];
+    assert_eq!(ddc.get_vcp_feature(0x10), Err(Error::Transport));
+    assert_eq!(ddc.inner_ref().calls, 1);
+
+    // unsupported features fail the same way every time
+    let mut ddc = Retry::with_timer(Flaky::new(&[]), Backoff::default());
+    assert_eq!(
+        ddc.get_vcp_feature(0x99),
+        Err(Error::Ddc(ErrorCode::UnsupportedFeature(0x99)))
+    );
+    assert_eq!(ddc.inner_ref().calls, 1);
+}
+
+#[test]
+fn backoff() {
+    let policy = RetryPolicy {
+        backoff: Duration::from_millis(100),
+        backoff_factor: 3,
+        max_backoff: Duration::from_millis(500),
+        ..Default::default()
+    };
+    assert_eq!(policy.backoff(1), Duration::from_millis(100));
+    assert_eq!(policy.backoff(2), Duration::from_millis(300));
+    assert_eq!(policy.backoff(3), Duration::from_millis(500));
+    assert_eq!(policy.backoff(100), Duration::from_millis(500));
+}
+
+#[test]
+fn corrupted_replies() {
+    let mut display = MockDisplay::new();
+    display.set_feature(0x10, MockFeature::Continuous {
+        value: 50,
+        maximum: 100,
+    });
+    display.capabilities = (0..200).map(|i| b'a' + (i % 26) as u8).collect();
+    let expected = display.capabilities.clone();
+
+    let faults = FaultInjector::with_timer(display, FaultPolicy::uniform(3, 0.2), Backoff::default());
+    let mut ddc = Retry::with_timer(faults, Backoff::default());
+    ddc.policy_mut().read_attempts = 10;
+    ddc.policy_mut().fragment_attempts = 10;
+
+    for value in 0..20 {
+        ddc.set_vcp_feature(0x10, value).unwrap();
+        assert_eq!(ddc.get_vcp_feature(0x10).unwrap().value(), value);
+    }
+    assert_eq!(ddc.capabilities_string().unwrap(), expected);
+    assert!(!ddc.into_inner().1 .0.is_empty());
+}
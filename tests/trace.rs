@@ -0,0 +1,138 @@
+use {
+    core::time::Duration,
+    ddc::{
+        fault::{FaultInjector, FaultPolicy},
+        mock::{MockDisplay, MockFeature},
+        trace::{Record, RecordedError, Recorder, Replay, ReplayError, Request, Trace},
+        Ddc, Eddc, Edid, ErrorCode,
+    },
+};
+
+fn display() -> MockDisplay {
+    let mut display = MockDisplay::new();
+    display.set_feature(0x10, MockFeature::Continuous {
+        value: 50,
+        maximum: 100,
+    });
+    display.capabilities = b"(prot(monitor)vcp(10))".to_vec();
+    display.edid = vec![0u8; 256];
+    display
+}
+
+#[test]
+fn record_replay() {
+    let mut recorder = Recorder::new(FaultInjector::new(display(), FaultPolicy::never()));
+    let value = recorder.get_vcp_feature(0x10).unwrap();
+    recorder.set_vcp_feature(0x10, 20).unwrap();
+    let caps = recorder.capabilities_string().unwrap();
+    recorder.inner_mut().set_policy(FaultPolicy {
+        corrupt_checksum: 1.0,
+        ..FaultPolicy::never()
+    });
+    assert_eq!(recorder.get_vcp_feature(0x10), Err(ErrorCode::InvalidChecksum));
+    let mut edid = [0u8; 128];
+    recorder.read_edid(0, &mut edid).unwrap();
+    recorder.read_eddc_edid(1, 0, &mut edid).unwrap_err();
+
+    let (_, trace) = recorder.into_inner();
+    let text = format!("  # indented comments are skipped\n{}", trace);
+    assert!(text.contains("! [InvalidChecksum] DDC/CI checksum mismatch\n"));
+    let parsed: Trace = text.parse().unwrap();
+    assert_eq!(parsed, trace);
+
+    let mut replay = Replay::new(parsed);
+    assert_eq!(replay.get_vcp_feature(0x10).unwrap(), value);
+    replay.set_vcp_feature(0x10, 20).unwrap();
+    assert_eq!(replay.capabilities_string().unwrap(), caps);
+    // the recorded protocol error is returned as it was
+    assert!(matches!(
+        replay.get_vcp_feature(0x10),
+        Err(ReplayError::Ddc(ErrorCode::InvalidChecksum))
+    ));
+
+    // a request the display never saw
+    let position = replay.position();
+    match replay.get_vcp_feature(0x12) {
+        Err(ReplayError::Diverged {
+            index,
+            expected: Some(Request::Edid { .. }),
+            actual: Request::Command { ref data, .. },
+        }) => {
+            assert_eq!(index, position);
+            assert_eq!(data, &[0x01, 0x12]);
+        },
+        res => panic!("unexpected result {:?}", res),
+    }
+    assert_eq!(replay.position(), position);
+
+    replay.read_edid(0, &mut edid).unwrap();
+    assert!(matches!(
+        replay.read_eddc_edid(1, 0, &mut edid),
+        Err(ReplayError::Recorded(..))
+    ));
+    replay.finish().unwrap();
+}
+
+#[test]
+fn error_codes() {
+    let codes = [
+        ErrorCode::InvalidChecksum,
+        ErrorCode::NullResponse,
+        ErrorCode::LengthMismatch {
+            expected: 11,
+            actual: 4,
+        },
+        ErrorCode::OpcodeMismatch {
+            expected: 0x02,
+            actual: 0xe4,
+        },
+        ErrorCode::OffsetMismatch {
+            expected: 32,
+            actual: 0,
+        },
+        ErrorCode::UnsupportedFeature(0x99),
+        ErrorCode::WrongFeature {
+            expected: 0x10,
+            actual: 0x12,
+        },
+        ErrorCode::ReplyTooLong { limit: 0x1000 },
+    ];
+    let trace = Trace {
+        records: codes
+            .iter()
+            .map(|code| Record {
+                time: Duration::ZERO,
+                elapsed: Duration::from_micros(40),
+                request: Request::Command {
+                    data: vec![0x01, 0x10],
+                    reply_len: 11,
+                    response_delay: Duration::from_millis(40),
+                },
+                result: Err(RecordedError {
+                    code: Some(code.clone()),
+                    message: code.to_string(),
+                }),
+            })
+            .collect(),
+    };
+    assert_eq!(trace.to_string().parse::<Trace>().unwrap(), trace);
+
+    // errors without a code, or with one this version doesn't know
+    let trace: Trace = "0 1 command 0 11 01 10 ! [Timeout] I2C error\n0 1 command 0 11 01 10 ! [NullResponse]"
+        .parse()
+        .unwrap();
+    assert_eq!(
+        trace.records[0].result,
+        Err(RecordedError {
+            code: None,
+            message: "[Timeout] I2C error".into(),
+        })
+    );
+    assert_eq!(
+        trace.records[1].result,
+        Err(RecordedError {
+            code: Some(ErrorCode::NullResponse),
+            message: "".into(),
+        })
+    );
+}